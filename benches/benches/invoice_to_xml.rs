@@ -11,6 +11,7 @@ use eb_interface_rs::{
     payment_method::{PaymentMethod, PaymentMethodPaymentCard},
     reduction_and_surcharge::{ReductionAndSurchargeValue, ReductionListLineItem},
     tax::{TaxCategory, TaxItem},
+    vat_id::VatId,
 };
 use rust_decimal::Decimal;
 
@@ -32,7 +33,7 @@ pub fn bench(c: &mut Criterion) {
         "EUR",
         "993433000298",
         "2020-01-01",
-        Biller::new("ATU51507409")
+        Biller::new(VatId::new("ATU51507409").unwrap())
             .with_further_identification(FurtherIdentification::new("0012345", FurtherIdentificationType::DVR))
             .with_address(
                 Address::new("Schrauben Mustermann", "Wien", "1020", "Österreich")
@@ -41,7 +42,7 @@ pub fn bench(c: &mut Criterion) {
                     .with_phone("+43 / 1 / 78 56 789")
                     .with_email("schrauben@mustermann.at"),
             ),
-        InvoiceRecipient::new("ATU18708634")
+        InvoiceRecipient::new(VatId::new("ATU18708637").unwrap())
             .with_order_reference(OrderReference::new("test"))
             .with_address(
                 Address::new("Mustermann GmbH", "Graz", "8010", "Österreich")
@@ -59,13 +60,15 @@ pub fn bench(c: &mut Criterion) {
         )
         .with_position_number(1)
         .with_description("Schraubenzieher")
-        .with_base_quantity(Decimal::from(1)),
+        .with_base_quantity(Decimal::from(1))
+        .unwrap_or_else(|e| panic!("{e}")),
     )
     .with_item(
         DetailsItem::new(Decimal::from(1), "STK", Decimal::from(5), TaxItem::new(Decimal::from(10), TaxCategory::AA))
             .with_position_number(2)
             .with_description("Handbuch zur Schraube")
             .with_base_quantity(Decimal::from(1))
+            .unwrap_or_else(|e| panic!("{e}"))
             .with_reduction(
                 ReductionListLineItem::new(Decimal::from(5), ReductionAndSurchargeValue::Amount(Decimal::from(2)))
                     .with_comment("reduction"),
@@ -76,11 +79,51 @@ pub fn bench(c: &mut Criterion) {
     .with_payment_method(
         PaymentMethod::payment_card(
             PaymentMethodPaymentCard::new("123456*4321")
-                .and_then(|s| Ok(s.with_card_holder_name("Name")))
+                .map(|s| s.with_card_holder_name("Name"))
                 .unwrap_or_else(|e| panic!("{e}")),
         )
         .with_comment("Comment"),
     );
 
     g.bench_function("invoice_to_xml", |b| b.iter(|| invoice.to_xml()));
+    g.finish();
+
+    let mut g = c.benchmark_group("invoice_10000_items");
+    g.sample_size(20);
+    g.warm_up_time(core::time::Duration::from_millis(500));
+    g.measurement_time(core::time::Duration::from_secs(5));
+
+    let invoice = large_invoice(10_000);
+
+    g.bench_function("to_xml", |b| b.iter(|| invoice.to_xml()));
+    g.bench_function("write_xml", |b| b.iter(|| invoice.write_xml(std::io::sink())));
+    g.finish();
+}
+
+fn large_invoice(items: u64) -> Invoice<'static> {
+    let mut invoice = Invoice::new(
+        "test",
+        "EUR",
+        "993433000298",
+        "2020-01-01",
+        Biller::new(VatId::new("ATU51507409").unwrap()),
+        InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+    );
+    for i in 1..=items {
+        invoice = invoice.with_item(
+            DetailsItem::new(
+                Decimal::from(i % 100 + 1),
+                "STK",
+                Decimal::new(1020 + i as i64, 2),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_position_number(i)
+            .with_description(format!("Schraube {i}"))
+            .with_reduction(
+                ReductionListLineItem::new(Decimal::from(5), ReductionAndSurchargeValue::Amount(Decimal::from(2)))
+                    .with_comment("reduction"),
+            ),
+        );
+    }
+    invoice
 }
@@ -1,79 +1,226 @@
-use crate::xml::{ToXml, XmlElement};
+use std::{borrow::Cow, fmt};
 
-#[derive(Default)]
+use crate::{
+    cii::{CiiWriter, ToCii},
+    parse::{FromXml, ParseError, XmlNode, non_empty},
+    ubl::UblWriter,
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, Default, PartialEq)]
 pub struct Address<'a> {
-    name: &'a str,
-    street: Option<&'a str>,
-    town: &'a str,
-    zip: &'a str,
-    country: &'a str,
-    country_code: Option<&'a str>,
-    phone: Option<Vec<&'a str>>,
-    email: Option<Vec<&'a str>>,
+    name: Cow<'a, str>,
+    street: Option<Cow<'a, str>>,
+    town: Cow<'a, str>,
+    zip: Cow<'a, str>,
+    country: Cow<'a, str>,
+    country_code: Option<Cow<'a, str>>,
+    phone: Option<Vec<Cow<'a, str>>>,
+    email: Option<Vec<Cow<'a, str>>>,
 }
 
 impl<'a> Address<'a> {
-    pub fn new(name: &'a str, town: &'a str, zip: &'a str, country: &'a str) -> Self {
+    pub fn new(
+        name: impl Into<Cow<'a, str>>,
+        town: impl Into<Cow<'a, str>>,
+        zip: impl Into<Cow<'a, str>>,
+        country: impl Into<Cow<'a, str>>,
+    ) -> Self {
         Address {
-            name,
-            town,
-            zip,
-            country,
+            name: name.into(),
+            town: town.into(),
+            zip: zip.into(),
+            country: country.into(),
             ..Default::default()
         }
     }
 
-    pub fn with_street(mut self, street: &'a str) -> Self {
-        self.street = Some(street);
+    pub fn with_street(mut self, street: impl Into<Cow<'a, str>>) -> Self {
+        self.street = Some(street.into());
         self
     }
 
-    pub fn with_country_code(mut self, country_code: &'a str) -> Self {
-        self.country_code = Some(country_code);
+    pub fn with_country_code(mut self, country_code: impl Into<Cow<'a, str>>) -> Self {
+        self.country_code = Some(country_code.into());
         self
     }
 
-    pub fn with_phone(mut self, phone_number: &'a str) -> Self {
-        self.phone.get_or_insert_with(Vec::new).push(phone_number);
+    pub fn with_phone(mut self, phone_number: impl Into<Cow<'a, str>>) -> Self {
+        self.phone.get_or_insert_with(Vec::new).push(phone_number.into());
         self
     }
 
-    pub fn with_email(mut self, email_address: &'a str) -> Self {
-        self.email.get_or_insert_with(Vec::new).push(email_address);
+    pub fn with_email(mut self, email_address: impl Into<Cow<'a, str>>) -> Self {
+        self.email.get_or_insert_with(Vec::new).push(email_address.into());
         self
     }
+
+    pub fn into_owned(self) -> Address<'static> {
+        Address {
+            name: Cow::Owned(self.name.into_owned()),
+            street: self.street.map(|s| Cow::Owned(s.into_owned())),
+            town: Cow::Owned(self.town.into_owned()),
+            zip: Cow::Owned(self.zip.into_owned()),
+            country: Cow::Owned(self.country.into_owned()),
+            country_code: self.country_code.map(|c| Cow::Owned(c.into_owned())),
+            phone: self.phone.map(|p| p.into_iter().map(|p| Cow::Owned(p.into_owned())).collect()),
+            email: self.email.map(|e| e.into_iter().map(|e| Cow::Owned(e.into_owned())).collect()),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Writes the UBL address element with the given name (`PostalAddress` or `Address`). The name of the address
+    /// belongs to the surrounding party.
+    pub(crate) fn write_ubl(&self, w: &mut UblWriter, name: &str) -> fmt::Result {
+        w.start(name)?;
+
+        if let Some(s) = &self.street {
+            w.text_element("cbc:StreetName", s)?;
+        }
+
+        w.text_element("cbc:CityName", &self.town)?;
+        w.text_element("cbc:PostalZone", &self.zip)?;
+
+        if let Some(cc) = &self.country_code {
+            w.start("cac:Country")?;
+            w.text_element("cbc:IdentificationCode", cc)?;
+            w.end("cac:Country")?;
+        }
+
+        w.end(name)
+    }
+
+    /// Writes the first phone number and email address as UBL `Contact` of a party without `Contact`.
+    pub(crate) fn write_ubl_contact(&self, w: &mut UblWriter) -> fmt::Result {
+        let phone_number = self.phone.iter().flatten().next();
+        let email_address = self.email.iter().flatten().next();
+        if phone_number.is_none() && email_address.is_none() {
+            return Ok(());
+        }
+
+        w.start("cac:Contact")?;
+
+        if let Some(phone_number) = phone_number {
+            w.text_element("cbc:Telephone", phone_number)?;
+        }
+
+        if let Some(email_address) = email_address {
+            w.text_element("cbc:ElectronicMail", email_address)?;
+        }
+
+        w.end("cac:Contact")
+    }
+
+    /// Writes the first phone number and email address as CII `DefinedTradeContact` of a party without `Contact`.
+    pub(crate) fn write_cii_contact(&self, w: &mut CiiWriter) -> fmt::Result {
+        let phone_number = self.phone.iter().flatten().next();
+        let email_address = self.email.iter().flatten().next();
+        if phone_number.is_none() && email_address.is_none() {
+            return Ok(());
+        }
+
+        w.start("ram:DefinedTradeContact")?;
+
+        if let Some(phone_number) = phone_number {
+            w.start("ram:TelephoneUniversalCommunication")?;
+            w.text_element("ram:CompleteNumber", phone_number)?;
+            w.end("ram:TelephoneUniversalCommunication")?;
+        }
+
+        if let Some(email_address) = email_address {
+            w.start("ram:EmailURIUniversalCommunication")?;
+            w.text_element("ram:URIID", email_address)?;
+            w.end("ram:EmailURIUniversalCommunication")?;
+        }
+
+        w.end("ram:DefinedTradeContact")
+    }
 }
 
 impl ToXml for Address<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("Address").with_text_element("Name", self.name);
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("Address")?;
+        w.text_element("Name", &self.name)?;
 
-        if let Some(s) = self.street {
-            e = e.with_text_element("Street", s);
+        if let Some(s) = &self.street {
+            w.text_element("Street", s)?;
         }
 
-        e = e
-            .with_text_element("Town", self.town)
-            .with_text_element("ZIP", self.zip);
+        w.text_element("Town", &self.town)?;
+        w.text_element("ZIP", &self.zip)?;
 
-        let mut ce = XmlElement::new("Country").with_text(self.country);
-        if let Some(cc) = self.country_code {
-            ce = ce.with_attr("CountryCode", cc);
+        w.start("Country")?;
+        if let Some(cc) = &self.country_code {
+            w.attr("CountryCode", cc)?;
         }
-        e = e.with_element(&ce);
+        w.text(&self.country)?;
+        w.end("Country")?;
 
         if let Some(phone_numbers) = &self.phone {
             for phone_number in phone_numbers {
-                e = e.with_text_element("Phone", *phone_number);
+                w.text_element("Phone", phone_number)?;
             }
         }
 
         if let Some(email_addresses) = &self.email {
             for email_address in email_addresses {
-                e = e.with_text_element("Email", *email_address);
+                w.text_element("Email", email_address)?;
             }
         }
 
-        e.to_xml()
+        w.end("Address")
+    }
+}
+
+/// Writes a CII `PostalTradeAddress`. The name of the address belongs to the surrounding party.
+impl ToCii for Address<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:PostalTradeAddress")?;
+        w.text_element("ram:PostcodeCode", &self.zip)?;
+
+        if let Some(s) = &self.street {
+            w.text_element("ram:LineOne", s)?;
+        }
+
+        w.text_element("ram:CityName", &self.town)?;
+
+        if let Some(cc) = &self.country_code {
+            w.text_element("ram:CountryID", cc)?;
+        }
+
+        w.end("ram:PostalTradeAddress")
+    }
+}
+
+impl<'a> FromXml<'a> for Address<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let country = node.required_child("Country")?;
+        Ok(Address {
+            name: node.required_child_text("Name")?,
+            street: node.child_text("Street"),
+            town: node.required_child_text("Town")?,
+            zip: node.required_child_text("ZIP")?,
+            country: country.text(),
+            country_code: country.attr("CountryCode"),
+            phone: non_empty(node.children("Phone").map(XmlNode::text).collect()),
+            email: non_empty(node.children("Email").map(XmlNode::text).collect()),
+        })
+    }
+}
+
+impl Validate for Address<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("Name", &self.name);
+        v.required("Town", &self.town);
+        v.required("ZIP", &self.zip);
+        v.required("Country", &self.country);
+
+        if let Some(cc) = &self.country_code {
+            v.country_code("Country/@CountryCode", cc);
+        }
     }
 }
@@ -0,0 +1,56 @@
+use rust_decimal::Decimal;
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    parse::{FromXml, ParseError, XmlNode},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+/// An amount, which is not subject to VAT and is added to (or, if negative, subtracted from) the payable amount,
+/// e.g. a deposit for returnable packaging.
+#[derive(Debug, PartialEq)]
+pub struct BelowTheLineItem<'a> {
+    description: Cow<'a, str>,
+    pub(crate) line_item_amount: Decimal,
+}
+
+impl<'a> BelowTheLineItem<'a> {
+    pub fn new(description: impl Into<Cow<'a, str>>, line_item_amount: Decimal) -> Self {
+        BelowTheLineItem {
+            description: description.into(),
+            line_item_amount,
+        }
+    }
+
+    pub fn into_owned(self) -> BelowTheLineItem<'static> {
+        BelowTheLineItem {
+            description: Cow::Owned(self.description.into_owned()),
+            line_item_amount: self.line_item_amount,
+        }
+    }
+}
+
+impl ToXml for BelowTheLineItem<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("BelowTheLineItem")?;
+        w.text_element("Description", &self.description)?;
+        w.text_element("LineItemAmount", w.round(self.line_item_amount))?;
+        w.end("BelowTheLineItem")
+    }
+}
+
+impl<'a> FromXml<'a> for BelowTheLineItem<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(BelowTheLineItem {
+            description: node.required_child_text("Description")?,
+            line_item_amount: node.required_child_decimal("LineItemAmount")?,
+        })
+    }
+}
+
+impl Validate for BelowTheLineItem<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("Description", &self.description);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{borrow::Cow, fmt, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{codes::is_country_code, error::Error};
+
+const BIC_REGEX_STR: &str = r"^[A-Z]{4}[A-Z]{2}[0-9A-Z]{2}([0-9A-Z]{3})?$";
+static BIC_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(BIC_REGEX_STR).unwrap());
+
+/// An ISO 9362 business identifier code, e.g. `BKAUATWW` or `BKAUATWWXXX`: a 4-letter bank code, an ISO 3166
+/// country code, a 2-character location code and an optional 3-character branch code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bic<'a>(Cow<'a, str>);
+
+impl<'a> Bic<'a> {
+    /// Validates the BIC, after converting it to uppercase.
+    pub fn new(bic: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let bic = bic.into();
+        let bic = if bic.bytes().any(|b| b.is_ascii_lowercase()) {
+            Cow::Owned(bic.to_ascii_uppercase())
+        } else {
+            bic
+        };
+        let bic = Bic(bic);
+        bic.check()?;
+        Ok(bic)
+    }
+
+    /// Wraps a BIC as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(bic: Cow<'a, str>) -> Self {
+        Bic(bic)
+    }
+
+    /// Checks the structure and the country code of the BIC.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if !BIC_REGEX.is_match(&self.0) {
+            return Err(Error::RegexMismatch {
+                field: "BIC",
+                pattern: BIC_REGEX_STR,
+                value: self.0.to_string(),
+            });
+        }
+        if !is_country_code(self.country_code()) {
+            return Err(Error::UnknownValue {
+                field: "BIC",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn bank_code(&self) -> &str {
+        self.0.get(0..4).unwrap_or_default()
+    }
+
+    pub fn country_code(&self) -> &str {
+        self.0.get(4..6).unwrap_or_default()
+    }
+
+    pub fn location_code(&self) -> &str {
+        self.0.get(6..8).unwrap_or_default()
+    }
+
+    /// Returns the branch code of an 11-character BIC, `XXX` being the primary office.
+    pub fn branch_code(&self) -> Option<&str> {
+        self.0.get(8..11)
+    }
+
+    pub fn into_owned(self) -> Bic<'static> {
+        Bic(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for Bic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_8_and_11_character_bics() {
+        let bic = Bic::new("BKAUATWW").unwrap();
+        assert_eq!(bic.as_str(), "BKAUATWW");
+        assert_eq!(
+            (bic.bank_code(), bic.country_code(), bic.location_code(), bic.branch_code()),
+            ("BKAU", "AT", "WW", None)
+        );
+
+        let bic = Bic::new("RZOOAT2L303").unwrap();
+        assert_eq!(
+            (bic.bank_code(), bic.country_code(), bic.location_code(), bic.branch_code()),
+            ("RZOO", "AT", "2L", Some("303"))
+        );
+    }
+
+    #[test]
+    fn accepts_user_assigned_country_codes() {
+        assert_eq!(Bic::new("RBKOXKPR").unwrap().country_code(), "XK");
+    }
+
+    #[test]
+    fn normalises_to_uppercase() {
+        assert_eq!(Bic::new("bkauatwwxxx").unwrap().to_string(), "BKAUATWWXXX");
+        assert!(matches!(Bic::new("BKAUATWW").unwrap(), Bic(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_bics() {
+        for bic in [
+            "x",
+            "BKAUATW",
+            "BKAUATWWX",
+            "BKAUATWWXXXX",
+            "BKA1ATWW",
+            "BKAUA1WW",
+            "BKAU ATWW",
+        ] {
+            assert_eq!(
+                Bic::new(bic),
+                Err(Error::RegexMismatch {
+                    field: "BIC",
+                    pattern: BIC_REGEX_STR,
+                    value: bic.to_ascii_uppercase(),
+                }),
+                "{bic}"
+            );
+        }
+        assert_eq!(
+            Bic::new("BKAUXXWW"),
+            Err(Error::UnknownValue {
+                field: "BIC",
+                value: "BKAUXXWW".to_string(),
+            })
+        );
+    }
+}
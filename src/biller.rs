@@ -1,14 +1,23 @@
+use std::fmt;
+
 use crate::{
     address::Address,
+    cii::{CiiWriter, ToCii, write_trade_party},
     contact::Contact,
+    error::Error,
     identification::FurtherIdentification,
     order_reference::OrderReference,
-    xml::{ToXml, XmlElement},
+    parse::{FromXml, ParseError, XmlNode, non_empty},
+    ubl::{ToUbl, UblWriter, write_party},
+    validation::{Validate, Validator},
+    vat_id::VatId,
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
 };
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct Biller<'a> {
-    vat_identification_number: &'a str,
+    vat_identification_number: VatId<'a>,
     further_identification: Option<Vec<FurtherIdentification<'a>>>,
     order_reference: Option<OrderReference<'a>>,
     address: Option<Address<'a>>,
@@ -16,7 +25,7 @@ pub struct Biller<'a> {
 }
 
 impl<'a> Biller<'a> {
-    pub fn new(vat_identification_number: &'a str) -> Self {
+    pub fn new(vat_identification_number: VatId<'a>) -> Self {
         Biller {
             vat_identification_number,
             ..Default::default()
@@ -42,31 +51,110 @@ impl<'a> Biller<'a> {
         self.contact = Some(contact);
         self
     }
+
+    pub fn into_owned(self) -> Biller<'static> {
+        Biller {
+            vat_identification_number: self.vat_identification_number.into_owned(),
+            further_identification: self
+                .further_identification
+                .map(|fis| fis.into_iter().map(FurtherIdentification::into_owned).collect()),
+            order_reference: self.order_reference.map(OrderReference::into_owned),
+            address: self.address.map(Address::into_owned),
+            contact: self.contact.map(Contact::into_owned),
+        }
+    }
+
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        match &self.contact {
+            Some(contact) => contact.check_version(version),
+            None => Ok(()),
+        }
+    }
 }
 
 impl ToXml for Biller<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("Biller")
-            .with_text_element("VATIdentificationNumber", self.vat_identification_number);
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("Biller")?;
+        w.text_element("VATIdentificationNumber", &self.vat_identification_number)?;
 
         if let Some(fis) = &self.further_identification {
             for fi in fis {
-                e = e.with_element(fi)
+                w.element(fi)?;
             }
         }
 
         if let Some(or) = &self.order_reference {
-            e = e.with_element(or);
+            w.element(or)?;
         }
 
         if let Some(a) = &self.address {
-            e = e.with_element(a);
+            w.element(a)?;
         }
 
         if let Some(c) = &self.contact {
-            e = e.with_element(c);
+            w.element(c)?;
         }
 
-        e.to_xml()
+        w.end("Biller")
+    }
+}
+
+impl ToUbl for Biller<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        write_party(
+            w,
+            "cac:AccountingSupplierParty",
+            self.vat_identification_number.as_str(),
+            self.address.as_ref(),
+            self.contact.as_ref(),
+        )
+    }
+}
+
+impl ToCii for Biller<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        write_trade_party(
+            w,
+            "ram:SellerTradeParty",
+            self.vat_identification_number.as_str(),
+            self.address.as_ref(),
+            self.contact.as_ref(),
+        )
+    }
+}
+
+impl<'a> FromXml<'a> for Biller<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(Biller {
+            vat_identification_number: VatId::unchecked(node.required_child_text("VATIdentificationNumber")?),
+            further_identification: non_empty(node.children_from_xml("FurtherIdentification")?),
+            order_reference: node.child_from_xml("OrderReference")?,
+            address: node.child_from_xml("Address")?,
+            contact: node.child_from_xml("Contact")?,
+        })
+    }
+}
+
+impl Validate for Biller<'_> {
+    fn validate(&self, v: &mut Validator) {
+        if let Err(error) = self.vat_identification_number.check() {
+            v.error("VATIdentificationNumber", error);
+        }
+
+        if let Some(fis) = &self.further_identification {
+            v.children("FurtherIdentification", fis);
+        }
+
+        if let Some(or) = &self.order_reference {
+            v.child("OrderReference", |v| or.validate(v));
+        }
+
+        if let Some(a) = &self.address {
+            v.child("Address", |v| a.validate(v));
+        }
+
+        if let Some(c) = &self.contact {
+            v.child("Contact", |v| c.validate(v));
+        }
     }
 }
@@ -0,0 +1,579 @@
+use rust_decimal::Decimal;
+use std::{
+    borrow::Cow,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    address::Address,
+    contact::Contact,
+    delivery::Delivery,
+    details::DetailsItem,
+    document::{DocumentType, RelatedDocument},
+    import::{AllowanceCharge, DerivedTotal, GENERATING_SYSTEM, Party, PaymentDetails, PaymentMeans, Reader},
+    invoice::{Invoice, Totals},
+    order_reference::OrderReference,
+    parse::{ParseError, ParseErrorKind, XmlNode},
+    payment_conditions::PaymentConditions,
+    payment_method::{PaymentMethodPaymentCard, PaymentMethodUniversalBankTransactionBeneficiaryAccount},
+    reduction_and_surcharge::ReductionAndSurchargeValue,
+    rounding::RoundingPolicy,
+    tax::TaxItem,
+    xml::XmlWriter,
+};
+
+pub(crate) const RSM_NAMESPACE: &str = "urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100";
+pub(crate) const RAM_NAMESPACE: &str =
+    "urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100";
+pub(crate) const UDT_NAMESPACE: &str = "urn:un:unece:uncefact:data:standard:UnqualifiedDataType:100";
+pub(crate) const QDT_NAMESPACE: &str = "urn:un:unece:uncefact:data:standard:QualifiedDataType:100";
+
+/// The profile of a written CII document, see `Invoice::to_cii`.
+///
+/// XRechnung requires the buyer reference (Leitweg-ID), which is taken from the `OrderID` of the invoice recipient.
+/// Only Extended states the delivery date and address of a line item, the other profiles write the delivery date as
+/// a billing period of that day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CiiProfile {
+    #[default]
+    En16931,
+    XRechnung,
+    Extended,
+}
+
+impl fmt::Display for CiiProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CiiProfile::En16931 => "EN 16931",
+                CiiProfile::XRechnung => "XRechnung",
+                CiiProfile::Extended => "Extended",
+            }
+        )
+    }
+}
+
+impl CiiProfile {
+    /// The specification identifier (`GuidelineSpecifiedDocumentContextParameter`) of the profile.
+    pub fn guideline_id(&self) -> &'static str {
+        match self {
+            CiiProfile::En16931 => "urn:cen.eu:en16931:2017",
+            CiiProfile::XRechnung => "urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0",
+            CiiProfile::Extended => "urn:cen.eu:en16931:2017#conformant#urn:factur-x.eu:1p0:extended",
+        }
+    }
+}
+
+/// Writes a CII D16B document in the given profile. Only the tax total amount states the currency of the invoice.
+pub(crate) struct CiiWriter<'w, 'c> {
+    xml: XmlWriter<'w>,
+    currency: &'c str,
+    profile: CiiProfile,
+}
+
+impl<'w, 'c> CiiWriter<'w, 'c> {
+    pub(crate) fn new(out: &'w mut dyn fmt::Write, currency: &'c str, profile: CiiProfile) -> Self {
+        CiiWriter {
+            xml: XmlWriter::new(out),
+            currency,
+            profile,
+        }
+    }
+
+    pub(crate) fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.xml = self.xml.with_rounding(rounding);
+        self
+    }
+
+    pub(crate) fn currency(&self) -> &'c str {
+        self.currency
+    }
+
+    pub(crate) fn profile(&self) -> CiiProfile {
+        self.profile
+    }
+
+    pub(crate) fn amount(&mut self, name: &str, amount: Decimal) -> fmt::Result {
+        let amount = self.round(amount);
+        self.text_element(name, amount)
+    }
+
+    /// Writes an ebInterface date (`YYYY-MM-DD`) in the CII date format `102` (`YYYYMMDD`).
+    pub(crate) fn date(&mut self, name: &str, date: &str) -> fmt::Result {
+        self.start(name)?;
+        self.start("udt:DateTimeString")?;
+        self.attr("format", "102")?;
+        self.text(date.replace('-', ""))?;
+        self.end("udt:DateTimeString")?;
+        self.end(name)
+    }
+
+    pub(crate) fn element(&mut self, element: &impl ToCii) -> fmt::Result {
+        element.write_cii(self)
+    }
+}
+
+impl<'w> Deref for CiiWriter<'w, '_> {
+    type Target = XmlWriter<'w>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.xml
+    }
+}
+
+impl DerefMut for CiiWriter<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.xml
+    }
+}
+
+pub(crate) trait ToCii {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result;
+}
+
+/// Writes the `SellerTradeParty` of the biller or the `BuyerTradeParty` of the invoice recipient.
+/// The VAT identification number `00000000` (none) gets omitted.
+pub(crate) fn write_trade_party(
+    w: &mut CiiWriter,
+    name: &str,
+    vat_identification_number: &str,
+    address: Option<&Address>,
+    contact: Option<&Contact>,
+) -> fmt::Result {
+    w.start(name)?;
+    w.text_element("ram:Name", address.map_or(vat_identification_number, Address::name))?;
+
+    match (contact, address) {
+        (Some(contact), _) => w.element(contact)?,
+        (None, Some(address)) => address.write_cii_contact(w)?,
+        (None, None) => {}
+    }
+
+    if let Some(address) = address {
+        w.element(address)?;
+    }
+
+    if vat_identification_number != "00000000" {
+        w.start("ram:SpecifiedTaxRegistration")?;
+        w.start("ram:ID")?;
+        w.attr("schemeID", "VA")?;
+        w.text(vat_identification_number)?;
+        w.end("ram:ID")?;
+        w.end("ram:SpecifiedTaxRegistration")?;
+    }
+
+    w.end(name)
+}
+
+/// Reads a CII D16B `CrossIndustryInvoice` of any profile, the counterpart of the `ToCii` implementations.
+pub(crate) fn read_invoice<'n, 'a>(r: &mut Reader<'n, 'a>, root: &'n XmlNode<'a>) -> Result<Invoice<'a>, ParseError> {
+    if root.name != "CrossIndustryInvoice" {
+        return Err(root.error(ParseErrorKind::UnexpectedElement(root.name.to_string())));
+    }
+    match root.namespace() {
+        Some(RSM_NAMESPACE) => {}
+        ns => return Err(root.error(ParseErrorKind::UnsupportedNamespace(ns.unwrap_or_default().to_string()))),
+    }
+
+    r.skip(root, "ExchangedDocumentContext");
+
+    let document = r.required_child(root, "ExchangedDocument")?;
+    let transaction = r.required_child(root, "SupplyChainTradeTransaction")?;
+    let agreement = r.required_child(transaction, "ApplicableHeaderTradeAgreement")?;
+    let settlement = r.required_child(transaction, "ApplicableHeaderTradeSettlement")?;
+
+    let seller = r.required_child(agreement, "SellerTradeParty")?;
+    let buyer = r.required_child(agreement, "BuyerTradeParty")?;
+    let biller = read_trade_party(r, seller).biller();
+    let mut invoice_recipient = read_trade_party(r, buyer).invoice_recipient();
+    if let Some(order_reference) = read_order_reference(r, agreement) {
+        invoice_recipient = invoice_recipient.with_order_reference(order_reference);
+    }
+
+    let issue_date = r.required_child(document, "IssueDateTime")?;
+    let mut invoice = Invoice::new(
+        GENERATING_SYSTEM,
+        r.required_text(settlement, "InvoiceCurrencyCode")?,
+        r.required_text(document, "ID")?,
+        read_date(r, issue_date)?,
+        biller,
+        invoice_recipient,
+    );
+
+    if let Some(node) = r.child(document, "TypeCode") {
+        let document_type = DocumentType::from_type_code(&node.text()).unwrap_or_else(|| {
+            r.loss(node, format!("unknown type code {}, read as Invoice", node.text()));
+            DocumentType::Invoice
+        });
+        invoice = invoice.with_document_type(document_type);
+    }
+
+    if let Some(content) = r.child(document, "IncludedNote").and_then(|n| r.text(n, &["Content"])) {
+        invoice = invoice.with_document_title(content);
+    }
+
+    let delivery = r.child(transaction, "ApplicableHeaderTradeDelivery");
+    if let Some(delivery) = read_delivery(r, delivery, settlement)? {
+        invoice = invoice.with_delivery(delivery);
+    }
+
+    for document in r.children(settlement, "InvoiceReferencedDocument") {
+        let mut related_document = RelatedDocument::new(r.required_text(document, "IssuerAssignedID")?);
+        if let Some(date) = r.child(document, "FormattedIssueDateTime") {
+            related_document = related_document.with_invoice_date(read_date(r, date)?);
+        }
+        invoice = invoice.with_related_document(related_document);
+    }
+
+    let payment_terms = r.child(settlement, "SpecifiedTradePaymentTerms");
+    let details = PaymentDetails {
+        payment_reference: r.child(settlement, "PaymentReference"),
+        creditor_id: r.child(settlement, "CreditorReferenceID"),
+        mandate_reference: payment_terms.and_then(|t| r.child(t, "DirectDebitMandateID")),
+        debit_collection_date: None,
+    };
+    let mut payment_means = Vec::new();
+    for node in r.children(settlement, "SpecifiedTradeSettlementPaymentMeans") {
+        payment_means.push((node, read_payment_means(r, node)?));
+    }
+    if let Some(payment_method) = r.payment_method(payment_means, details) {
+        invoice = invoice.with_payment_method(payment_method);
+    }
+
+    if let Some(payment_terms) = payment_terms {
+        let description = r.child(payment_terms, "Description");
+        match r.child(payment_terms, "DueDateDateTime") {
+            Some(due_date) => {
+                let mut payment_conditions = PaymentConditions::new(read_date(r, due_date)?);
+                if let Some(description) = description {
+                    payment_conditions = payment_conditions.with_comment(description.text());
+                }
+                invoice = invoice.with_payment_conditions(payment_conditions);
+            }
+            None => {
+                if let Some(description) = description {
+                    r.loss(description, "payment terms without due date, dropped");
+                }
+            }
+        }
+    }
+
+    for node in r.children(settlement, "SpecifiedTradeAllowanceCharge") {
+        let allowance_charge = read_allowance_charge(r, node)?;
+        let trade_tax = r.required_child(node, "CategoryTradeTax")?;
+        let tax_item = read_trade_tax(r, trade_tax)?;
+        invoice = allowance_charge.add_to_invoice(invoice, tax_item);
+    }
+
+    r.skip(settlement, "ApplicableTradeTax");
+
+    let summation = r.required_child(settlement, "SpecifiedTradeSettlementHeaderMonetarySummation")?;
+    if let Some(prepaid_amount) = r.decimal(summation, &["TotalPrepaidAmount"])? {
+        invoice = invoice.with_prepaid_amount(prepaid_amount);
+    }
+    if let Some(rounding_amount) = r.decimal(summation, &["RoundingAmount"])? {
+        invoice = invoice.with_rounding_amount(rounding_amount);
+    }
+    let stated_totals: [(&str, DerivedTotal); 7] = [
+        ("LineTotalAmount", |t| t.line_total_amount),
+        ("ChargeTotalAmount", |t| t.charge_total_amount),
+        ("AllowanceTotalAmount", |t| t.allowance_total_amount),
+        ("TaxBasisTotalAmount", Totals::tax_basis_total_amount),
+        ("TaxTotalAmount", |t| t.tax_total_amount),
+        ("GrandTotalAmount", |t| t.total_gross_amount),
+        ("DuePayableAmount", |t| t.payable_amount),
+    ];
+    for (name, derived) in stated_totals {
+        let node = r.child(summation, name);
+        r.stated_total(node, derived);
+    }
+
+    for node in r.children(transaction, "IncludedSupplyChainTradeLineItem") {
+        invoice = invoice.with_item(read_line(r, node)?);
+    }
+
+    Ok(invoice)
+}
+
+/// Reads a date (e.g. `IssueDateTime`) in the format `102` (`YYYYMMDD`) as ebInterface date (`YYYY-MM-DD`).
+fn read_date<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Result<Cow<'a, str>, ParseError> {
+    let date_time_string = r.required_child(node, "DateTimeString")?;
+    let date = date_time_string.text();
+    match date_time_string.attr("format").as_deref() {
+        Some("102") if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(Cow::Owned(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])))
+        }
+        Some("102") => Err(date_time_string.error(ParseErrorKind::InvalidValue {
+            name: date_time_string.name.to_string(),
+            value: date.into_owned(),
+        })),
+        format => Err(date_time_string.error(ParseErrorKind::InvalidValue {
+            name: "format".to_string(),
+            value: format.unwrap_or_default().to_string(),
+        })),
+    }
+}
+
+/// Reads a `SellerTradeParty` or `BuyerTradeParty`, the first VAT registration (`schemeID` `VA`) is the VAT
+/// identification number.
+fn read_trade_party<'n, 'a>(r: &mut Reader<'n, 'a>, party: &'n XmlNode<'a>) -> Party<'a> {
+    let mut vat_identification_number = None;
+    for tax_registration in r.children(party, "SpecifiedTaxRegistration") {
+        match tax_registration.child("ID") {
+            Some(id) if id.attr("schemeID").as_deref() == Some("VA") && vat_identification_number.is_none() => {
+                r.skip(tax_registration, "ID");
+                vat_identification_number = Some(id.text());
+            }
+            _ => r.dropped(tax_registration, "tax registration besides the VAT identification number, dropped"),
+        }
+    }
+
+    let name = r.child(party, "Name");
+    let mut address = match r.child(party, "PostalTradeAddress") {
+        Some(postal_trade_address) => {
+            Some(read_address(r, postal_trade_address, name.map(XmlNode::text).unwrap_or_default()))
+        }
+        None => {
+            if let Some(name) = name
+                && Some(name.text()) != vat_identification_number
+            {
+                r.loss(name, "name without postal address, dropped");
+            }
+            None
+        }
+    };
+
+    let contact = match r.child(party, "DefinedTradeContact") {
+        Some(contact) => {
+            let name = r.text(contact, &["PersonName"]);
+            let phone = r.text(contact, &["TelephoneUniversalCommunication", "CompleteNumber"]);
+            let email = r.text(contact, &["EmailURIUniversalCommunication", "URIID"]);
+            r.contact(contact, name, phone, email, &mut address)
+        }
+        None => None,
+    };
+
+    Party {
+        vat_identification_number,
+        address,
+        contact,
+    }
+}
+
+/// Reads a `PostalTradeAddress`. The country code is also the country name.
+fn read_address<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>, name: Cow<'a, str>) -> Address<'a> {
+    let country_code = r.text(node, &["CountryID"]);
+    let mut address = Address::new(
+        name,
+        r.text(node, &["CityName"]).unwrap_or_default(),
+        r.text(node, &["PostcodeCode"]).unwrap_or_default(),
+        country_code.clone().unwrap_or_default(),
+    );
+    if let Some(street) = r.text(node, &["LineOne"]) {
+        address = address.with_street(street);
+    }
+    if let Some(country_code) = country_code {
+        address = address.with_country_code(country_code);
+    }
+    address
+}
+
+/// Reads the `BuyerOrderReferencedDocument`, or else the `BuyerReference` as order ID.
+fn read_order_reference<'n, 'a>(r: &mut Reader<'n, 'a>, agreement: &'n XmlNode<'a>) -> Option<OrderReference<'a>> {
+    let buyer_reference = r.child(agreement, "BuyerReference");
+    let Some(order_id) = r.text(agreement, &["BuyerOrderReferencedDocument", "IssuerAssignedID"]) else {
+        return buyer_reference.map(|b| OrderReference::new(b.text()));
+    };
+
+    if let Some(buyer_reference) = buyer_reference
+        && buyer_reference.text() != order_id
+    {
+        r.loss(buyer_reference, "buyer reference besides the order reference, dropped");
+    }
+    Some(OrderReference::new(order_id))
+}
+
+/// Reads the delivery of the invoice or a line from its trade delivery (`ShipToTradeParty` and
+/// `ActualDeliverySupplyChainEvent`) and trade settlement (`BillingSpecifiedPeriod`). A billing period of a single
+/// day is read as delivery date, a delivery date besides a longer period gets dropped.
+fn read_delivery<'n, 'a>(
+    r: &mut Reader<'n, 'a>,
+    trade_delivery: Option<&'n XmlNode<'a>>,
+    trade_settlement: &'n XmlNode<'a>,
+) -> Result<Option<Delivery<'a>>, ParseError> {
+    let period = match r.child(trade_settlement, "BillingSpecifiedPeriod") {
+        Some(period) => match (r.child(period, "StartDateTime"), r.child(period, "EndDateTime")) {
+            (Some(start), Some(end)) => Some((read_date(r, start)?, read_date(r, end)?)),
+            _ => {
+                r.dropped(period, "billing period without start and end date, dropped");
+                None
+            }
+        },
+        None => None,
+    };
+    let event = trade_delivery.and_then(|d| r.child(d, "ActualDeliverySupplyChainEvent"));
+    let date = match event.and_then(|e| r.child(e, "OccurrenceDateTime")) {
+        Some(date) => Some(read_date(r, date)?),
+        None => None,
+    };
+
+    let mut delivery = match (period, date) {
+        (Some((start, end)), date) if start == end && date.as_ref().is_none_or(|date| *date == start) => {
+            Delivery::date(start)
+        }
+        (Some((start, end)), date) => {
+            if let (Some(event), Some(_)) = (event, date) {
+                r.loss(event, "delivery date besides the billing period, dropped");
+            }
+            Delivery::period(start, end)
+        }
+        (None, Some(date)) => Delivery::date(date),
+        (None, None) => {
+            if let Some(ship_to) = trade_delivery.and_then(|d| d.child("ShipToTradeParty")) {
+                r.dropped(ship_to, "delivery address without date, dropped");
+            }
+            return Ok(None);
+        }
+    };
+
+    if let Some(ship_to) = trade_delivery.and_then(|d| r.child(d, "ShipToTradeParty"))
+        && let Some(address) = r.child(ship_to, "PostalTradeAddress")
+    {
+        let name = r.text(ship_to, &["Name"]).unwrap_or_default();
+        delivery = delivery.with_address(read_address(r, address, name));
+    }
+
+    Ok(Some(delivery))
+}
+
+/// Reads a `SpecifiedTradeSettlementPaymentMeans` by its UNTDID 4461 code, like the UBL `PaymentMeans`.
+fn read_payment_means<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Result<PaymentMeans<'a>, ParseError> {
+    let code_node = r.required_child(node, "TypeCode")?;
+    let code = code_node.text();
+
+    Ok(match code.as_ref() {
+        "30" | "31" | "42" | "58" => {
+            let account = r.child(node, "PayeePartyCreditorFinancialAccount");
+            PaymentMeans::CreditTransfer(PaymentMethodUniversalBankTransactionBeneficiaryAccount::imported(
+                account.and_then(|a| r.text(a, &["IBANID"])),
+                account.and_then(|a| r.text(a, &["ProprietaryID"])),
+                r.text(node, &["PayeeSpecifiedCreditorFinancialInstitution", "BICID"]),
+                account.and_then(|a| r.text(a, &["AccountName"])),
+            ))
+        }
+        "49" | "59" => PaymentMeans::DirectDebit {
+            iban: r.text(node, &["PayerPartyDebtorFinancialAccount", "IBANID"]),
+        },
+        "48" | "54" | "55" => {
+            let card = r.required_child(node, "ApplicableTradeSettlementFinancialCard")?;
+            PaymentMeans::PaymentCard(PaymentMethodPaymentCard::imported(
+                r.required_text(card, "ID")?,
+                r.text(card, &["CardholderName"]),
+            ))
+        }
+        "1" | "ZZZ" => PaymentMeans::Other,
+        _ => {
+            r.loss(code_node, format!("payment means code {code} has no counterpart, read as OtherPayment"));
+            PaymentMeans::Other
+        }
+    })
+}
+
+/// Reads a `SpecifiedTradeAllowanceCharge`, like the UBL `AllowanceCharge`.
+fn read_allowance_charge<'n, 'a>(
+    r: &mut Reader<'n, 'a>,
+    node: &'n XmlNode<'a>,
+) -> Result<AllowanceCharge<'a>, ParseError> {
+    let charge_indicator = r.required_child(node, "ChargeIndicator")?;
+    let charge = r.required_child(charge_indicator, "Indicator")?.value()?;
+
+    if let Some(reason_code) = r.child(node, "ReasonCode")
+        && reason_code.text() != if charge { "ZZZ" } else { "95" }
+    {
+        r.loss(reason_code, format!("reason code {} has no counterpart, dropped", reason_code.text()));
+    }
+
+    let amount = r.required_child(node, "ActualAmount")?.decimal()?;
+    let value = match r.decimal(node, &["CalculationPercent"])? {
+        Some(percentage) => ReductionAndSurchargeValue::PercentageAndAmount(percentage, amount),
+        None => ReductionAndSurchargeValue::Amount(amount),
+    };
+
+    Ok(AllowanceCharge {
+        charge,
+        base_amount: r.decimal(node, &["BasisAmount"])?.unwrap_or(amount),
+        value,
+        comment: r.text(node, &["Reason"]),
+    })
+}
+
+/// Reads an `ApplicableTradeTax` of a line or the `CategoryTradeTax` of a reduction or surcharge.
+fn read_trade_tax<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Result<TaxItem, ParseError> {
+    r.skip(node, "TypeCode");
+    let code = r.text(node, &["CategoryCode"]);
+    let percent = r.decimal(node, &["RateApplicablePercent"])?;
+    Ok(r.tax_item(node, code.as_deref(), percent))
+}
+
+/// Reads an `IncludedSupplyChainTradeLineItem`. The name of the product becomes the first description, each line
+/// of its description a further one.
+fn read_line<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Result<DetailsItem<'a>, ParseError> {
+    let line_document = r.required_child(node, "AssociatedDocumentLineDocument")?;
+    let product = r.required_child(node, "SpecifiedTradeProduct")?;
+    let agreement = r.required_child(node, "SpecifiedLineTradeAgreement")?;
+    let price = r.required_child(agreement, "NetPriceProductTradePrice")?;
+    let trade_delivery = r.required_child(node, "SpecifiedLineTradeDelivery")?;
+    let quantity = r.required_child(trade_delivery, "BilledQuantity")?;
+    let settlement = r.required_child(node, "SpecifiedLineTradeSettlement")?;
+    let trade_tax = r.required_child(settlement, "ApplicableTradeTax")?;
+
+    let mut line = DetailsItem::new(
+        quantity.decimal()?,
+        quantity.required_attr("unitCode")?,
+        r.required_child(price, "ChargeAmount")?.decimal()?,
+        read_trade_tax(r, trade_tax)?,
+    );
+
+    let id = r.required_child(line_document, "LineID")?;
+    match id.value() {
+        Ok(position_number) => line = line.with_position_number(position_number),
+        Err(_) => r.loss(id, format!("line ID {} isn't a position number, dropped", id.text())),
+    }
+
+    if let Some(name) = r.text(product, &["Name"]) {
+        line = line.with_description(name);
+    }
+    if let Some(description) = r.text(product, &["Description"]) {
+        if description.contains('\n') {
+            for d in description.split('\n') {
+                line = line.with_description(d.to_string());
+            }
+        } else {
+            line = line.with_description(description);
+        }
+    }
+
+    if let Some(base_quantity) = r.descendant(price, &["BasisQuantity"]) {
+        line = line.with_base_quantity(base_quantity.decimal()?).map_err(|_| {
+            base_quantity.error(ParseErrorKind::InvalidValue {
+                name: base_quantity.name.to_string(),
+                value: base_quantity.text().into_owned(),
+            })
+        })?;
+    }
+
+    if let Some(delivery) = read_delivery(r, Some(trade_delivery), settlement)? {
+        line = line.with_delivery(delivery);
+    }
+
+    for allowance_charge in r.children(settlement, "SpecifiedTradeAllowanceCharge") {
+        line = read_allowance_charge(r, allowance_charge)?.add_to_item(line);
+    }
+
+    r.skip(settlement, "SpecifiedTradeSettlementLineMonetarySummation");
+
+    Ok(line)
+}
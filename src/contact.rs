@@ -1,59 +1,146 @@
-use crate::xml::{ToXml, XmlElement};
+use std::{borrow::Cow, fmt};
 
-#[derive(Default)]
+use crate::{
+    cii::{CiiWriter, ToCii},
+    error::Error,
+    parse::{FromXml, ParseError, XmlNode, non_empty},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, Default, PartialEq)]
 pub struct Contact<'a> {
-    salutation: Option<&'a str>,
-    name: &'a str,
-    phone: Option<Vec<&'a str>>,
-    email: Option<Vec<&'a str>>,
+    salutation: Option<Cow<'a, str>>,
+    name: Cow<'a, str>,
+    phone: Option<Vec<Cow<'a, str>>>,
+    email: Option<Vec<Cow<'a, str>>>,
 }
 
 impl<'a> Contact<'a> {
-    pub fn new(name: &'a str) -> Self {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> Self {
         Contact {
-            name,
+            name: name.into(),
             ..Default::default()
         }
     }
 
-    pub fn with_salutation(mut self, salutation: &'a str) -> Self {
-        self.salutation = Some(salutation);
+    pub fn with_salutation(mut self, salutation: impl Into<Cow<'a, str>>) -> Self {
+        self.salutation = Some(salutation.into());
         self
     }
 
-    pub fn with_phone(mut self, phone_number: &'a str) -> Self {
-        self.phone.get_or_insert_with(Vec::new).push(phone_number);
+    pub fn with_phone(mut self, phone_number: impl Into<Cow<'a, str>>) -> Self {
+        self.phone.get_or_insert_with(Vec::new).push(phone_number.into());
         self
     }
 
-    pub fn with_email(mut self, email_address: &'a str) -> Self {
-        self.email.get_or_insert_with(Vec::new).push(email_address);
+    pub fn with_email(mut self, email_address: impl Into<Cow<'a, str>>) -> Self {
+        self.email.get_or_insert_with(Vec::new).push(email_address.into());
         self
     }
+
+    pub fn into_owned(self) -> Contact<'static> {
+        Contact {
+            salutation: self.salutation.map(|s| Cow::Owned(s.into_owned())),
+            name: Cow::Owned(self.name.into_owned()),
+            phone: self.phone.map(|p| p.into_iter().map(|p| Cow::Owned(p.into_owned())).collect()),
+            email: self.email.map(|e| e.into_iter().map(|e| Cow::Owned(e.into_owned())).collect()),
+        }
+    }
+
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        if version.has_contact() {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedByVersion {
+                version,
+                construct: "Contact".to_string(),
+            })
+        }
+    }
 }
 
 impl ToXml for Contact<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("Contact");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("Contact")?;
 
-        if let Some(s) = self.salutation {
-            e = e.with_text_element("Salutation", s);
+        if let Some(s) = &self.salutation {
+            w.text_element("Salutation", s)?;
         }
 
-        e = e.with_text_element("Name", self.name);
+        w.text_element("Name", &self.name)?;
 
         if let Some(phone_numbers) = &self.phone {
             for phone_number in phone_numbers {
-                e = e.with_text_element("Phone", *phone_number);
+                w.text_element("Phone", phone_number)?;
             }
         }
 
         if let Some(email_addresses) = &self.email {
             for email_address in email_addresses {
-                e = e.with_text_element("Email", *email_address);
+                w.text_element("Email", email_address)?;
             }
         }
 
-        e.to_xml()
+        w.end("Contact")
+    }
+}
+
+/// Writes a UBL `Contact` with the first phone number and email address. The salutation gets dropped.
+impl ToUbl for Contact<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:Contact")?;
+        w.text_element("cbc:Name", &self.name)?;
+
+        if let Some(phone_number) = self.phone.iter().flatten().next() {
+            w.text_element("cbc:Telephone", phone_number)?;
+        }
+
+        if let Some(email_address) = self.email.iter().flatten().next() {
+            w.text_element("cbc:ElectronicMail", email_address)?;
+        }
+
+        w.end("cac:Contact")
+    }
+}
+
+/// Writes a CII `DefinedTradeContact` with the first phone number and email address. The salutation gets dropped.
+impl ToCii for Contact<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:DefinedTradeContact")?;
+        w.text_element("ram:PersonName", &self.name)?;
+
+        if let Some(phone_number) = self.phone.iter().flatten().next() {
+            w.start("ram:TelephoneUniversalCommunication")?;
+            w.text_element("ram:CompleteNumber", phone_number)?;
+            w.end("ram:TelephoneUniversalCommunication")?;
+        }
+
+        if let Some(email_address) = self.email.iter().flatten().next() {
+            w.start("ram:EmailURIUniversalCommunication")?;
+            w.text_element("ram:URIID", email_address)?;
+            w.end("ram:EmailURIUniversalCommunication")?;
+        }
+
+        w.end("ram:DefinedTradeContact")
+    }
+}
+
+impl<'a> FromXml<'a> for Contact<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(Contact {
+            salutation: node.child_text("Salutation"),
+            name: node.required_child_text("Name")?,
+            phone: non_empty(node.children("Phone").map(XmlNode::text).collect()),
+            email: non_empty(node.children("Email").map(XmlNode::text).collect()),
+        })
+    }
+}
+
+impl Validate for Contact<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("Name", &self.name);
     }
 }
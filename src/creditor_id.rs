@@ -0,0 +1,164 @@
+use std::{borrow::Cow, fmt, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{codes::is_country_code, error::Error, iban::mod_97};
+
+const CREDITOR_ID_REGEX_STR: &str = r"^[A-Z]{2}[0-9]{2}[0-9A-Z]{3}[0-9A-Z]{1,28}$";
+static CREDITOR_ID_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(CREDITOR_ID_REGEX_STR).unwrap());
+
+/// A SEPA creditor identifier, e.g. `AT61ZZZ01234567890`: an ISO 3166 country code, two check digits, a
+/// 3-character business code (usually `ZZZ`) and the national identifier of the creditor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreditorId<'a>(Cow<'a, str>);
+
+impl<'a> CreditorId<'a> {
+    /// Validates the creditor identifier, after removing spaces and converting it to uppercase.
+    pub fn new(creditor_id: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let creditor_id = creditor_id.into();
+        let creditor_id = if creditor_id
+            .bytes()
+            .any(|b| b.is_ascii_whitespace() || b.is_ascii_lowercase())
+        {
+            Cow::Owned(
+                creditor_id
+                    .chars()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect(),
+            )
+        } else {
+            creditor_id
+        };
+        let creditor_id = CreditorId(creditor_id);
+        creditor_id.check()?;
+        Ok(creditor_id)
+    }
+
+    /// Wraps a creditor identifier as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(creditor_id: Cow<'a, str>) -> Self {
+        CreditorId(creditor_id)
+    }
+
+    /// Checks the structure, the country code and the check digits of the creditor identifier. Like the banks do,
+    /// the check digits only cover the country code and the national identifier, not the business code.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.0.len() > 35 {
+            return Err(Error::TooLong {
+                field: "CreditorID",
+                max: 35,
+                value: self.0.to_string(),
+            });
+        }
+        if !CREDITOR_ID_REGEX.is_match(&self.0) {
+            return Err(Error::RegexMismatch {
+                field: "CreditorID",
+                pattern: CREDITOR_ID_REGEX_STR,
+                value: self.0.to_string(),
+            });
+        }
+        if !is_country_code(self.country_code()) {
+            return Err(Error::UnknownValue {
+                field: "CreditorID",
+                value: self.0.to_string(),
+            });
+        }
+        if mod_97(self.national_identifier(), &self.0[..4]) != 1 {
+            return Err(Error::InvalidChecksum {
+                field: "CreditorID",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn country_code(&self) -> &str {
+        self.0.get(0..2).unwrap_or_default()
+    }
+
+    pub fn check_digits(&self) -> &str {
+        self.0.get(2..4).unwrap_or_default()
+    }
+
+    /// Returns the business code, which the creditor may choose freely to tell its business lines apart.
+    pub fn business_code(&self) -> &str {
+        self.0.get(4..7).unwrap_or_default()
+    }
+
+    pub fn national_identifier(&self) -> &str {
+        self.0.get(7..).unwrap_or_default()
+    }
+
+    pub fn into_owned(self) -> CreditorId<'static> {
+        CreditorId(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for CreditorId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_creditor_ids() {
+        let creditor_id = CreditorId::new("at61 zzz 01234567890").unwrap();
+        assert_eq!(creditor_id.to_string(), "AT61ZZZ01234567890");
+        assert_eq!(
+            (
+                creditor_id.country_code(),
+                creditor_id.check_digits(),
+                creditor_id.business_code(),
+                creditor_id.national_identifier()
+            ),
+            ("AT", "61", "ZZZ", "01234567890")
+        );
+        assert!(CreditorId::new("DE98ZZZ09999999999").is_ok());
+        assert!(CreditorId::new("XK75ZZZ01234567890").is_ok());
+    }
+
+    #[test]
+    fn ignores_the_business_code() {
+        assert!(CreditorId::new("DE98ABC09999999999").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_creditor_ids() {
+        assert_eq!(
+            CreditorId::new("AT62ZZZ01234567890"),
+            Err(Error::InvalidChecksum {
+                field: "CreditorID",
+                value: "AT62ZZZ01234567890".to_string(),
+            })
+        );
+        assert_eq!(
+            CreditorId::new("XX61ZZZ01234567890"),
+            Err(Error::UnknownValue {
+                field: "CreditorID",
+                value: "XX61ZZZ01234567890".to_string(),
+            })
+        );
+        assert!(matches!(
+            CreditorId::new("AT61ZZZ"),
+            Err(Error::RegexMismatch {
+                field: "CreditorID",
+                ..
+            })
+        ));
+        assert!(matches!(
+            CreditorId::new("AT61ZZZ0123456789012345678901234567890"),
+            Err(Error::TooLong {
+                field: "CreditorID",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    address::Address,
+    cii::CiiWriter,
+    contact::Contact,
+    error::Error,
+    parse::{FromXml, ParseError, ParseErrorKind, XmlNode},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, PartialEq)]
+enum DeliveryDateOrPeriod<'a> {
+    Date(Cow<'a, str>),
+    Period { from_date: Cow<'a, str>, to_date: Cow<'a, str> },
+}
+
+impl DeliveryDateOrPeriod<'_> {
+    fn into_owned(self) -> DeliveryDateOrPeriod<'static> {
+        match self {
+            DeliveryDateOrPeriod::Date(date) => DeliveryDateOrPeriod::Date(Cow::Owned(date.into_owned())),
+            DeliveryDateOrPeriod::Period { from_date, to_date } => DeliveryDateOrPeriod::Period {
+                from_date: Cow::Owned(from_date.into_owned()),
+                to_date: Cow::Owned(to_date.into_owned()),
+            },
+        }
+    }
+}
+
+/// The date or period of a delivery or service.
+/// ebInterface requires a `Delivery` either on the invoice or on each line item.
+#[derive(Debug, PartialEq)]
+pub struct Delivery<'a> {
+    delivery_id: Option<Cow<'a, str>>,
+    date_or_period: DeliveryDateOrPeriod<'a>,
+    address: Option<Address<'a>>,
+    contact: Option<Contact<'a>>,
+    description: Option<Cow<'a, str>>,
+}
+
+impl<'a> Delivery<'a> {
+    fn new(date_or_period: DeliveryDateOrPeriod<'a>) -> Self {
+        Delivery {
+            delivery_id: None,
+            date_or_period,
+            address: None,
+            contact: None,
+            description: None,
+        }
+    }
+
+    pub fn date(date: impl Into<Cow<'a, str>>) -> Self {
+        Delivery::new(DeliveryDateOrPeriod::Date(date.into()))
+    }
+
+    pub fn period(from_date: impl Into<Cow<'a, str>>, to_date: impl Into<Cow<'a, str>>) -> Self {
+        Delivery::new(DeliveryDateOrPeriod::Period {
+            from_date: from_date.into(),
+            to_date: to_date.into(),
+        })
+    }
+
+    pub fn with_delivery_id(mut self, delivery_id: impl Into<Cow<'a, str>>) -> Self {
+        self.delivery_id = Some(delivery_id.into());
+        self
+    }
+
+    pub fn with_address(mut self, address: Address<'a>) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn with_contact(mut self, contact: Contact<'a>) -> Self {
+        self.contact = Some(contact);
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn into_owned(self) -> Delivery<'static> {
+        Delivery {
+            delivery_id: self.delivery_id.map(|id| Cow::Owned(id.into_owned())),
+            date_or_period: self.date_or_period.into_owned(),
+            address: self.address.map(Address::into_owned),
+            contact: self.contact.map(Contact::into_owned),
+            description: self.description.map(|d| Cow::Owned(d.into_owned())),
+        }
+    }
+
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        match &self.contact {
+            Some(contact) => contact.check_version(version),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the period as UBL `InvoicePeriod`, which precedes the `Delivery` in UBL.
+    pub(crate) fn write_ubl_period(&self, w: &mut UblWriter) -> fmt::Result {
+        if let DeliveryDateOrPeriod::Period { from_date, to_date } = &self.date_or_period {
+            w.start("cac:InvoicePeriod")?;
+            w.text_element("cbc:StartDate", from_date)?;
+            w.text_element("cbc:EndDate", to_date)?;
+            w.end("cac:InvoicePeriod")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the period, or the date as a period of that day if `date_as_period`, as CII `BillingSpecifiedPeriod`.
+    pub(crate) fn write_cii_period(&self, w: &mut CiiWriter, date_as_period: bool) -> fmt::Result {
+        let (from_date, to_date) = match &self.date_or_period {
+            DeliveryDateOrPeriod::Period { from_date, to_date } => (from_date, to_date),
+            DeliveryDateOrPeriod::Date(date) if date_as_period => (date, date),
+            DeliveryDateOrPeriod::Date(_) => return Ok(()),
+        };
+        w.start("ram:BillingSpecifiedPeriod")?;
+        w.date("ram:StartDateTime", from_date)?;
+        w.date("ram:EndDateTime", to_date)?;
+        w.end("ram:BillingSpecifiedPeriod")
+    }
+
+    /// Writes the address (`ShipToTradeParty`) and the date (`ActualDeliverySupplyChainEvent`) into the surrounding
+    /// CII trade delivery. The period is written by `write_cii_period`, the delivery ID, the contact and the
+    /// description get dropped.
+    pub(crate) fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        if let Some(a) = &self.address {
+            w.start("ram:ShipToTradeParty")?;
+            w.text_element("ram:Name", a.name())?;
+            w.element(a)?;
+            w.end("ram:ShipToTradeParty")?;
+        }
+
+        if let DeliveryDateOrPeriod::Date(date) = &self.date_or_period {
+            w.start("ram:ActualDeliverySupplyChainEvent")?;
+            w.date("ram:OccurrenceDateTime", date)?;
+            w.end("ram:ActualDeliverySupplyChainEvent")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToXml for Delivery<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("Delivery")?;
+
+        if let Some(id) = &self.delivery_id {
+            w.text_element("DeliveryID", id)?;
+        }
+
+        match &self.date_or_period {
+            DeliveryDateOrPeriod::Date(date) => {
+                w.text_element("Date", date)?;
+            }
+            DeliveryDateOrPeriod::Period { from_date, to_date } => {
+                w.start("Period")?;
+                w.text_element("FromDate", from_date)?;
+                w.text_element("ToDate", to_date)?;
+                w.end("Period")?;
+            }
+        }
+
+        if let Some(a) = &self.address {
+            w.element(a)?;
+        }
+
+        if let Some(c) = &self.contact {
+            w.element(c)?;
+        }
+
+        if let Some(d) = &self.description {
+            w.text_element("Description", d)?;
+        }
+
+        w.end("Delivery")
+    }
+}
+
+/// Writes a UBL `Delivery` with the delivery ID, the date and the address, if there is any of them.
+/// The period is written by `write_ubl_period`, the contact and the description get dropped.
+impl ToUbl for Delivery<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        let date = match &self.date_or_period {
+            DeliveryDateOrPeriod::Date(date) => Some(date),
+            DeliveryDateOrPeriod::Period { .. } => None,
+        };
+        if self.delivery_id.is_none() && date.is_none() && self.address.is_none() {
+            return Ok(());
+        }
+
+        w.start("cac:Delivery")?;
+
+        if let Some(id) = &self.delivery_id {
+            w.text_element("cbc:ID", id)?;
+        }
+
+        if let Some(date) = date {
+            w.text_element("cbc:ActualDeliveryDate", date)?;
+        }
+
+        if let Some(a) = &self.address {
+            w.start("cac:DeliveryLocation")?;
+            a.write_ubl(w, "cac:Address")?;
+            w.end("cac:DeliveryLocation")?;
+            w.start("cac:DeliveryParty")?;
+            w.start("cac:PartyName")?;
+            w.text_element("cbc:Name", a.name())?;
+            w.end("cac:PartyName")?;
+            w.end("cac:DeliveryParty")?;
+        }
+
+        w.end("cac:Delivery")
+    }
+}
+
+impl<'a> FromXml<'a> for Delivery<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let date_or_period = match (node.child("Date"), node.child("Period")) {
+            (Some(date), _) => DeliveryDateOrPeriod::Date(date.text()),
+            (None, Some(period)) => DeliveryDateOrPeriod::Period {
+                from_date: period.required_child_text("FromDate")?,
+                to_date: period.required_child_text("ToDate")?,
+            },
+            (None, None) => return Err(node.error(ParseErrorKind::MissingElement("Date".to_string()))),
+        };
+
+        Ok(Delivery {
+            delivery_id: node.child_text("DeliveryID"),
+            date_or_period,
+            address: node.child_from_xml("Address")?,
+            contact: node.child_from_xml("Contact")?,
+            description: node.child_text("Description"),
+        })
+    }
+}
+
+impl Validate for Delivery<'_> {
+    fn validate(&self, v: &mut Validator) {
+        match &self.date_or_period {
+            DeliveryDateOrPeriod::Date(date) => v.date("Date", date),
+            DeliveryDateOrPeriod::Period { from_date, to_date } => {
+                v.date("Period/FromDate", from_date);
+                v.date("Period/ToDate", to_date);
+            }
+        }
+
+        if let Some(a) = &self.address {
+            v.child("Address", |v| a.validate(v));
+        }
+
+        if let Some(c) = &self.contact {
+            v.child("Contact", |v| c.validate(v));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_delivery_date() {
+        assert_eq!(
+            Delivery::date("2020-01-01").with_delivery_id("L-123").to_xml(),
+            "<Delivery><DeliveryID>L-123</DeliveryID><Date>2020-01-01</Date></Delivery>",
+        );
+    }
+
+    #[test]
+    fn generates_delivery_period() {
+        assert_eq!(
+            Delivery::period("2020-01-01", "2020-01-31")
+                .with_address(Address::new("Mustermann GmbH", "Graz", "8010", "Österreich"))
+                .with_contact(Contact::new("Max Mustermann"))
+                .with_description("Wartung")
+                .to_xml(),
+            "<Delivery><Period><FromDate>2020-01-01</FromDate><ToDate>2020-01-31</ToDate></Period><Address><Name>Mustermann GmbH</Name><Town>Graz</Town><ZIP>8010</ZIP><Country>Österreich</Country></Address><Contact><Name>Max Mustermann</Name></Contact><Description>Wartung</Description></Delivery>",
+        );
+    }
+}
@@ -1,31 +1,41 @@
-use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal::Decimal;
+use std::{borrow::Cow, fmt};
 
 use crate::{
+    cii::{CiiProfile, CiiWriter, ToCii},
     decimal::CloneAndRescale,
+    delivery::Delivery,
+    error::Error,
+    parse::{FromXml, ParseError, XmlNode},
     reduction_and_surcharge::{
         ReductionAndSurchargeListLineItemDetails, ReductionListLineItem, SurchargeListLineItem,
     },
-    tax::TaxItem,
-    xml::{ToXml, XmlElement},
+    rounding::RoundingPolicy,
+    tax::{TaxItem, TaxItemWithTaxableAmount},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
 };
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct DetailsItem<'a> {
     position_number: Option<u64>,
-    description: Vec<&'a str>,
+    description: Vec<Cow<'a, str>>,
     quantity: Decimal,
-    unit: &'a str,
+    unit: Cow<'a, str>,
     unit_price: Decimal,
     base_quantity: Option<Decimal>,
     reduction_and_surcharge: Option<ReductionAndSurchargeListLineItemDetails<'a>>,
+    pub(crate) delivery: Option<Delivery<'a>>,
     pub(crate) tax_item: TaxItem,
 }
 
 impl<'a> DetailsItem<'a> {
-    pub fn new(quantity: Decimal, unit: &'a str, unit_price: Decimal, tax_item: TaxItem) -> Self {
+    pub fn new(quantity: Decimal, unit: impl Into<Cow<'a, str>>, unit_price: Decimal, tax_item: TaxItem) -> Self {
         DetailsItem {
             quantity,
-            unit,
+            unit: unit.into(),
             unit_price,
             tax_item,
             ..Default::default()
@@ -37,14 +47,22 @@ impl<'a> DetailsItem<'a> {
         self
     }
 
-    pub fn with_description(mut self, description: &'a str) -> Self {
-        self.description.push(description);
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description.push(description.into());
         self
     }
 
-    pub fn with_base_quantity(mut self, base_quantity: Decimal) -> Self {
+    /// Sets the quantity the unit price refers to, e.g. `100` for a price per 100 pieces. It has to be greater
+    /// than zero.
+    pub fn with_base_quantity(mut self, base_quantity: Decimal) -> Result<Self, Error> {
+        if base_quantity <= Decimal::ZERO {
+            return Err(Error::NotPositive {
+                field: "BaseQuantity",
+                value: base_quantity,
+            });
+        }
         self.base_quantity = Some(base_quantity);
-        self
+        Ok(self)
     }
 
     pub fn with_reduction(mut self, reduction: ReductionListLineItem<'a>) -> Self {
@@ -67,88 +85,392 @@ impl<'a> DetailsItem<'a> {
         self
     }
 
-    pub(crate) fn line_item_amount(&self) -> Decimal {
-        let base_quantity = self.base_quantity.unwrap_or(Decimal::ONE);
+    /// Adds a reduction by the given percentage of the net price of the line item (`quantity * unit_price /
+    /// base_quantity`), which becomes its base amount.
+    pub fn with_percentage_reduction(self, percentage: Decimal) -> Self {
+        self.with_reduction(ReductionListLineItem::percentage(percentage))
+    }
+
+    /// Adds a reduction by the given percentage of the amount remaining after all reductions added before, e.g. 10 %
+    /// and then 5 % on the reduced amount. The reductions apply in the order they're added, which is also the order
+    /// they're written in.
+    pub fn with_cascading_percentage_reduction(self, percentage: Decimal) -> Self {
+        self.with_reduction(ReductionListLineItem::cascading_percentage(percentage))
+    }
+
+    /// Adds a surcharge by the given percentage of the net price of the line item, which becomes its base amount.
+    pub fn with_percentage_surcharge(self, percentage: Decimal) -> Self {
+        self.with_surcharge(SurchargeListLineItem::percentage(percentage))
+    }
+
+    pub fn with_delivery(mut self, delivery: Delivery<'a>) -> Self {
+        self.delivery = Some(delivery);
+        self
+    }
+
+    pub fn into_owned(self) -> DetailsItem<'static> {
+        DetailsItem {
+            position_number: self.position_number,
+            description: self.description.into_iter().map(|d| Cow::Owned(d.into_owned())).collect(),
+            quantity: self.quantity,
+            unit: Cow::Owned(self.unit.into_owned()),
+            unit_price: self.unit_price,
+            base_quantity: self.base_quantity,
+            reduction_and_surcharge: self
+                .reduction_and_surcharge
+                .map(ReductionAndSurchargeListLineItemDetails::into_owned),
+            delivery: self.delivery.map(Delivery::into_owned),
+            tax_item: self.tax_item,
+        }
+    }
+
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        if let Some(delivery) = &self.delivery {
+            delivery.check_version(version)?;
+        }
+        self.tax_item.check_version(version)
+    }
+
+    /// The net price of the line item, before reductions and surcharges. A base quantity of zero can only be read
+    /// from a document and gets reported by `validate`, the price is zero then.
+    fn price(&self) -> Decimal {
+        (self.quantity * self.unit_price)
+            .checked_div(self.base_quantity.unwrap_or(Decimal::ONE))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn line_item_amount(&self, rounding: RoundingPolicy) -> Decimal {
+        self.amounts(rounding).line_item_amount
+    }
+
+    /// Returns the amounts calculated for the line item with the given rounding policy (the one of the invoice),
+    /// exactly as they are written into the `ListLineItem`.
+    ///
+    /// All of them derive from the line item amount, which is the price of `quantity / base_quantity` units plus the
+    /// surcharges minus the reductions. It's the taxable amount of the line item as well.
+    pub fn amounts(&self, rounding: RoundingPolicy) -> DetailsItemAmounts {
+        let price = self.price();
 
         let reduction_and_surcharge_sum = match &self.reduction_and_surcharge {
-            Some(rs) => rs.sum(),
+            Some(rs) => rs.line_price(price).sum(rounding),
             None => Decimal::ZERO,
         };
 
-        (self.quantity * self.unit_price / base_quantity + reduction_and_surcharge_sum)
-            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+        let line_item_amount = rounding.round(price + reduction_and_surcharge_sum);
         /* + sum of other_vat_able_tax_list_line_item.tax_amount */
+        let tax_item = self.tax_item.taxable_amount(line_item_amount, rounding);
+
+        DetailsItemAmounts {
+            line_item_amount,
+            total_gross_amount: line_item_amount + tax_item.tax_amount(),
+            tax_item,
+        }
     }
 
-    pub(crate) fn line_item_total_gross_amount(&self) -> Decimal {
-        self.line_item_amount()
-            * ((self.tax_item.tax_percent + Decimal::ONE_HUNDRED) / Decimal::ONE_HUNDRED)
+    /// Writes a UBL `InvoiceLine` (or `CreditNoteLine`), identified by the position number or else by the given
+    /// (1-based) index. The first description becomes the name of the item.
+    fn write_ubl(&self, w: &mut UblWriter, index: usize) -> fmt::Result {
+        let (line, quantity) = if w.is_credit_note() {
+            ("cac:CreditNoteLine", "cbc:CreditedQuantity")
+        } else {
+            ("cac:InvoiceLine", "cbc:InvoicedQuantity")
+        };
+
+        w.start(line)?;
+        match self.position_number {
+            Some(pn) => w.text_element("cbc:ID", pn)?,
+            None => w.text_element("cbc:ID", index)?,
+        }
+
+        w.start(quantity)?;
+        w.attr("unitCode", &self.unit)?;
+        w.text(self.quantity.clone_with_scale(4))?;
+        w.end(quantity)?;
+
+        w.amount("cbc:LineExtensionAmount", self.line_item_amount(w.rounding()))?;
+
+        if let Some(delivery) = &self.delivery {
+            delivery.write_ubl_period(w)?;
+            w.element(delivery)?;
+        }
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            w.element(&reduction_and_surcharge.line_price(self.price()))?;
+        }
+
+        w.start("cac:Item")?;
+        for description in self.description.iter().skip(1) {
+            w.text_element("cbc:Description", description)?;
+        }
+        if let Some(name) = self.description.first() {
+            w.text_element("cbc:Name", name)?;
+        }
+        self.tax_item.write_ubl(w, "cac:ClassifiedTaxCategory")?;
+        w.end("cac:Item")?;
+
+        let currency = w.currency();
+        w.start("cac:Price")?;
+        w.start("cbc:PriceAmount")?;
+        w.attr("currencyID", currency)?;
+        w.text(self.unit_price.clone_with_scale(4))?;
+        w.end("cbc:PriceAmount")?;
+        if let Some(bq) = &self.base_quantity {
+            w.start("cbc:BaseQuantity")?;
+            w.attr("unitCode", &self.unit)?;
+            w.text(bq)?;
+            w.end("cbc:BaseQuantity")?;
+        }
+        w.end("cac:Price")?;
+
+        w.end(line)
+    }
+
+    /// Writes a CII `IncludedSupplyChainTradeLineItem`, identified like the UBL line. The first description becomes
+    /// the name of the product, the others its description. Except for the Extended profile, the delivery date is
+    /// written as billing period.
+    fn write_cii(&self, w: &mut CiiWriter, index: usize) -> fmt::Result {
+        let extended = w.profile() == CiiProfile::Extended;
+
+        w.start("ram:IncludedSupplyChainTradeLineItem")?;
+        w.start("ram:AssociatedDocumentLineDocument")?;
+        match self.position_number {
+            Some(pn) => w.text_element("ram:LineID", pn)?,
+            None => w.text_element("ram:LineID", index)?,
+        }
+        w.end("ram:AssociatedDocumentLineDocument")?;
+
+        w.start("ram:SpecifiedTradeProduct")?;
+        if let Some(name) = self.description.first() {
+            w.text_element("ram:Name", name)?;
+        }
+        if self.description.len() > 1 {
+            w.text_element("ram:Description", self.description[1..].join("\n"))?;
+        }
+        w.end("ram:SpecifiedTradeProduct")?;
+
+        w.start("ram:SpecifiedLineTradeAgreement")?;
+        w.start("ram:NetPriceProductTradePrice")?;
+        w.text_element("ram:ChargeAmount", self.unit_price.clone_with_scale(4))?;
+        if let Some(bq) = &self.base_quantity {
+            w.start("ram:BasisQuantity")?;
+            w.attr("unitCode", &self.unit)?;
+            w.text(bq)?;
+            w.end("ram:BasisQuantity")?;
+        }
+        w.end("ram:NetPriceProductTradePrice")?;
+        w.end("ram:SpecifiedLineTradeAgreement")?;
+
+        w.start("ram:SpecifiedLineTradeDelivery")?;
+        w.start("ram:BilledQuantity")?;
+        w.attr("unitCode", &self.unit)?;
+        w.text(self.quantity.clone_with_scale(4))?;
+        w.end("ram:BilledQuantity")?;
+        if extended && let Some(delivery) = &self.delivery {
+            delivery.write_cii(w)?;
+        }
+        w.end("ram:SpecifiedLineTradeDelivery")?;
+
+        w.start("ram:SpecifiedLineTradeSettlement")?;
+        self.tax_item.write_cii(w, "ram:ApplicableTradeTax")?;
+        if let Some(delivery) = &self.delivery {
+            delivery.write_cii_period(w, !extended)?;
+        }
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            w.element(&reduction_and_surcharge.line_price(self.price()))?;
+        }
+        w.start("ram:SpecifiedTradeSettlementLineMonetarySummation")?;
+        w.amount("ram:LineTotalAmount", self.line_item_amount(w.rounding()))?;
+        w.end("ram:SpecifiedTradeSettlementLineMonetarySummation")?;
+        w.end("ram:SpecifiedLineTradeSettlement")?;
+
+        w.end("ram:IncludedSupplyChainTradeLineItem")
     }
 }
 
 impl ToXml for DetailsItem<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("ListLineItem");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("ListLineItem")?;
 
         // PositionNumber.
         if let Some(pn) = self.position_number {
-            e = e.with_text_element("PositionNumber", pn.to_string());
+            w.text_element("PositionNumber", pn)?;
         }
 
         // Description(s).
         for description in &self.description {
-            e = e.with_text_element("Description", *description);
+            w.text_element("Description", description)?;
         }
 
         // Quantity.
-        e = e.with_element(
-            &XmlElement::new("Quantity")
-                .with_attr("Unit", self.unit)
-                .with_text(self.quantity.clone_with_scale(4).to_string()),
-        );
+        w.start("Quantity")?;
+        w.attr("Unit", &self.unit)?;
+        w.text(self.quantity.clone_with_scale(4))?;
+        w.end("Quantity")?;
 
         // UnitPrice and BaseQuantity.
-        let mut up =
-            XmlElement::new("UnitPrice").with_text(self.unit_price.clone_with_scale(4).to_string());
+        w.start("UnitPrice")?;
         if let Some(bq) = &self.base_quantity {
-            up = up.with_attr("BaseQuantity", bq.to_string())
+            w.attr("BaseQuantity", bq)?;
+        }
+        w.text(self.unit_price.clone_with_scale(4))?;
+        w.end("UnitPrice")?;
+
+        let amounts = self.amounts(w.rounding());
+        let tax_item = amounts.tax_item;
+
+        // VATRate (ebInterface 4.3) or TaxRate (5.0).
+        if !w.version().has_tax_item() {
+            w.element(&tax_item)?;
         }
-        e = e.with_element(&up);
 
         // ReductionListLineItem(s) and SurchargeListLineItem(s).
-        let mut reduction_and_surcharge_sum = Decimal::ZERO;
         if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
-            reduction_and_surcharge_sum = reduction_and_surcharge.sum();
-            e = e.with_element(reduction_and_surcharge);
+            w.element(&reduction_and_surcharge.line_price(self.price()))?;
+        }
+
+        // Delivery.
+        if let Some(delivery) = &self.delivery {
+            w.element(delivery)?;
         }
 
         // TaxItem.
-        let taxable_amount = self.quantity * self.unit_price + reduction_and_surcharge_sum;
-        e = e.with_element(&self.tax_item.taxable_amount(taxable_amount));
+        if w.version().has_tax_item() {
+            w.element(&tax_item)?;
+        }
 
         // LineItemAmount.
-        e = e.with_text_element("LineItemAmount", self.line_item_amount().to_string());
+        w.text_element("LineItemAmount", amounts.line_item_amount)?;
+
+        w.end("ListLineItem")
+    }
+}
+
+/// Reads a `ListLineItem` element. The taxable amount and the line item amount get derived again when writing.
+impl<'a> FromXml<'a> for DetailsItem<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let quantity = node.required_child("Quantity")?;
+        let unit_price = node.required_child("UnitPrice")?;
+
+        Ok(DetailsItem {
+            position_number: node.child("PositionNumber").map(XmlNode::value).transpose()?,
+            description: node.children("Description").map(XmlNode::text).collect(),
+            quantity: quantity.decimal()?,
+            unit: quantity.required_attr("Unit")?,
+            unit_price: unit_price.decimal()?,
+            base_quantity: unit_price.attr_value("BaseQuantity")?,
+            reduction_and_surcharge: node.child_from_xml("ReductionAndSurchargeListLineItemDetails")?,
+            delivery: node.child_from_xml("Delivery")?,
+            tax_item: TaxItem::from_xml(node.required_child("TaxItem")?)?,
+        })
+    }
+}
+
+/// The amounts calculated for a line item, see [`DetailsItem::amounts`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailsItemAmounts {
+    line_item_amount: Decimal,
+    total_gross_amount: Decimal,
+    tax_item: TaxItemWithTaxableAmount,
+}
+
+impl DetailsItemAmounts {
+    /// The net amount of the line item, after its reductions and surcharges.
+    pub fn line_item_amount(&self) -> Decimal {
+        self.line_item_amount
+    }
+
+    /// The line item amount plus the tax amount of the line item.
+    pub fn total_gross_amount(&self) -> Decimal {
+        self.total_gross_amount
+    }
 
-        e.to_xml()
+    /// The tax rate together with the taxable amount and the tax amount of the line item.
+    pub fn tax_item(&self) -> &TaxItemWithTaxableAmount {
+        &self.tax_item
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub(crate) struct Details<'a> {
     pub(crate) items: Vec<DetailsItem<'a>>,
 }
 
+impl Details<'_> {
+    pub(crate) fn into_owned(self) -> Details<'static> {
+        Details {
+            items: self.items.into_iter().map(DetailsItem::into_owned).collect(),
+        }
+    }
+}
+
 impl ToXml for Details<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("Details");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("Details")?;
 
-        let mut ie = XmlElement::new("ItemList");
+        w.start("ItemList")?;
         for item in &self.items {
-            ie = ie.with_element(item);
+            w.element(item)?;
+        }
+        w.end("ItemList")?;
+
+        w.end("Details")
+    }
+}
+
+impl ToUbl for Details<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            item.write_ubl(w, i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToCii for Details<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            item.write_cii(w, i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> FromXml<'a> for Details<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(Details {
+            items: node.required_child("ItemList")?.children_from_xml("ListLineItem")?,
+        })
+    }
+}
+
+impl Validate for DetailsItem<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("Quantity/@Unit", &self.unit);
+
+        if let Some(base_quantity) = self.base_quantity {
+            v.positive("UnitPrice/@BaseQuantity", base_quantity);
+        }
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            v.child("ReductionAndSurchargeListLineItemDetails", |v| reduction_and_surcharge.validate(v));
         }
-        e = e.with_element(&ie);
 
-        e.to_xml()
+        if let Some(delivery) = &self.delivery {
+            v.child("Delivery", |v| delivery.validate(v));
+        }
+
+        v.child("TaxItem", |v| self.tax_item.validate(v));
+    }
+}
+
+impl Validate for Details<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.child("ItemList", |v| {
+            if self.items.is_empty() {
+                v.error("ListLineItem", Error::Missing { field: "ListLineItem" });
+            }
+            v.children("ListLineItem", &self.items);
+        });
     }
 }
 
@@ -157,6 +479,7 @@ mod tests {
     use super::*;
 
     use crate::{
+        delivery::Delivery,
         reduction_and_surcharge::{ReductionAndSurchargeValue, SurchargeListLineItem},
         tax::TaxCategory,
         xml::ToXml,
@@ -178,7 +501,7 @@ mod tests {
 
         assert_eq!(
             result,
-            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">0.0050</Quantity><UnitPrice>0.0050</UnitPrice><TaxItem><TaxableAmount>0.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>0.00</TaxAmount></TaxItem><LineItemAmount>0.00</LineItemAmount></ListLineItem>"
+            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">0.0050</Quantity><UnitPrice>0.0050</UnitPrice><TaxItem><TaxableAmount>0.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>0.00</TaxAmount></TaxItem><LineItemAmount>0.00</LineItemAmount></ListLineItem>",
         );
     }
 
@@ -198,7 +521,7 @@ mod tests {
 
         assert_eq!(
             result,
-            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">100.1235</Quantity><UnitPrice>10.2001</UnitPrice><TaxItem><TaxableAmount>1021.26</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.25</TaxAmount></TaxItem><LineItemAmount>1021.26</LineItemAmount></ListLineItem>"
+            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">100.1235</Quantity><UnitPrice>10.2001</UnitPrice><TaxItem><TaxableAmount>1021.26</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.25</TaxAmount></TaxItem><LineItemAmount>1021.26</LineItemAmount></ListLineItem>",
         );
     }
 
@@ -219,10 +542,147 @@ mod tests {
 
         assert_eq!(
             result,
-            "<ListLineItem><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.34</Amount></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>2.66</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.27</TaxAmount></TaxItem><LineItemAmount>2.66</LineItemAmount></ListLineItem>"
+            "<ListLineItem><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.34</Amount></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>2.66</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.27</TaxAmount></TaxItem><LineItemAmount>2.66</LineItemAmount></ListLineItem>",
+        );
+    }
+
+    #[test]
+    fn returns_amounts_as_written() {
+        let amounts = DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(5),
+            TaxItem::new(Decimal::from(10), TaxCategory::AA),
+        )
+        .with_reduction(ReductionListLineItem::new(
+            Decimal::from(5),
+            ReductionAndSurchargeValue::Amount(Decimal::new(23399, 4)),
+        ))
+        .amounts(RoundingPolicy::default());
+
+        assert_eq!(amounts.line_item_amount(), Decimal::new(266, 2));
+        assert_eq!(amounts.total_gross_amount(), Decimal::new(293, 2));
+        assert_eq!(amounts.tax_item().tax_percent(), Decimal::from(10));
+        assert_eq!(amounts.tax_item().tax_category(), TaxCategory::AA);
+        assert_eq!(amounts.tax_item().taxable_amount().to_string(), "2.66");
+        assert_eq!(amounts.tax_item().tax_amount().to_string(), "0.27");
+    }
+
+    #[test]
+    fn writes_line_item_amounts_of_integer_prices_with_cents() {
+        let item = DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        );
+
+        assert_eq!(item.amounts(RoundingPolicy::default()).line_item_amount().to_string(), "10.00");
+        assert_eq!(
+            item.to_xml(),
+            "<ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>10.0000</UnitPrice><TaxItem><TaxableAmount>10.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>2.00</TaxAmount></TaxItem><LineItemAmount>10.00</LineItemAmount></ListLineItem>",
+        );
+    }
+
+    #[test]
+    fn honours_base_quantity() {
+        let result = DetailsItem::new(
+            Decimal::from(250),
+            "STK",
+            Decimal::new(125, 1),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        )
+        .with_base_quantity(Decimal::from(100))
+        .unwrap()
+        .to_xml();
+
+        assert_eq!(
+            result,
+            "<ListLineItem><Quantity Unit=\"STK\">250.0000</Quantity><UnitPrice BaseQuantity=\"100\">12.5000</UnitPrice><TaxItem><TaxableAmount>31.25</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>6.25</TaxAmount></TaxItem><LineItemAmount>31.25</LineItemAmount></ListLineItem>",
         );
     }
 
+    #[test]
+    fn honours_base_quantity_in_amounts() {
+        let amounts = DetailsItem::new(
+            Decimal::from(3),
+            "STK",
+            Decimal::new(999, 2),
+            TaxItem::new(Decimal::from(10), TaxCategory::AA),
+        )
+        .with_base_quantity(Decimal::from(10))
+        .unwrap()
+        .with_reduction(ReductionListLineItem::new(
+            Decimal::new(300, 2),
+            ReductionAndSurchargeValue::Amount(Decimal::new(5, 1)),
+        ))
+        .amounts(RoundingPolicy::default());
+
+        assert_eq!(amounts.line_item_amount(), Decimal::new(250, 2));
+        assert_eq!(amounts.tax_item().taxable_amount(), Decimal::new(250, 2));
+        assert_eq!(amounts.tax_item().tax_amount(), Decimal::new(25, 2));
+        assert_eq!(amounts.total_gross_amount(), Decimal::new(275, 2));
+    }
+
+    #[test]
+    fn derives_base_amount_of_percentage_reductions_and_surcharges() {
+        let result = DetailsItem::new(
+            Decimal::from(10),
+            "STK",
+            Decimal::new(2000, 2),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        )
+        .with_percentage_reduction(Decimal::from(10))
+        .with_cascading_percentage_reduction(Decimal::from(5))
+        .with_percentage_surcharge(Decimal::from(2))
+        .with_base_quantity(Decimal::from(2))
+        .unwrap()
+        .to_xml();
+
+        assert_eq!(
+            result,
+            "<ListLineItem><Quantity Unit=\"STK\">10.0000</Quantity><UnitPrice BaseQuantity=\"2\">20.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Percentage>10.00</Percentage></ReductionListLineItem><ReductionListLineItem><BaseAmount>90.00</BaseAmount><Percentage>5.00</Percentage></ReductionListLineItem><SurchargeListLineItem><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>87.50</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>17.50</TaxAmount></TaxItem><LineItemAmount>87.50</LineItemAmount></ListLineItem>",
+        );
+    }
+
+    #[test]
+    fn applies_percentages_to_the_rounded_base_amount() {
+        let item = DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::new(10125, 3),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        )
+        .with_percentage_reduction(Decimal::from(50));
+
+        // 50 % of the written base amount 10.13 is 5.065, not 5.0625.
+        assert_eq!(item.amounts(RoundingPolicy::default()).line_item_amount(), Decimal::new(506, 2));
+        assert_eq!(
+            item.to_xml(),
+            "<ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>10.1250</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>10.13</BaseAmount><Percentage>50.00</Percentage></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>5.06</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>1.01</TaxAmount></TaxItem><LineItemAmount>5.06</LineItemAmount></ListLineItem>",
+        );
+    }
+
+    #[test]
+    fn applies_cascading_reductions_in_order() {
+        let amounts = DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::new(3333, 2),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        )
+        .with_reduction(ReductionListLineItem::new(
+            Decimal::new(3333, 2),
+            ReductionAndSurchargeValue::Amount(Decimal::new(333, 2)),
+        ))
+        .with_cascading_percentage_reduction(Decimal::from(10))
+        .with_cascading_percentage_reduction(Decimal::from(5))
+        .amounts(RoundingPolicy::default());
+
+        // 33.33 - 3.33 = 30.00, - 3.00 = 27.00, - 1.35 = 25.65
+        assert_eq!(amounts.line_item_amount(), Decimal::new(2565, 2));
+    }
+
     #[test]
     fn calculates_surcharge_correctly() {
         let result = DetailsItem::new(
@@ -240,7 +700,7 @@ mod tests {
 
         assert_eq!(
             result,
-            "<ListLineItem><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><SurchargeListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.00</Amount></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>7.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.70</TaxAmount></TaxItem><LineItemAmount>7.00</LineItemAmount></ListLineItem>"
+            "<ListLineItem><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><SurchargeListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.00</Amount></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>7.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.70</TaxAmount></TaxItem><LineItemAmount>7.00</LineItemAmount></ListLineItem>",
         );
     }
 
@@ -259,8 +719,29 @@ mod tests {
         .to_xml();
 
         assert_eq!(
-            result.as_str(),
-            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">100.1234</Quantity><UnitPrice>10.2000</UnitPrice><TaxItem><TaxableAmount>1021.26</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.25</TaxAmount></TaxItem><LineItemAmount>1021.26</LineItemAmount></ListLineItem>"
+            result,
+            "<ListLineItem><Description>Sand</Description><Quantity Unit=\"KGM\">100.1234</Quantity><UnitPrice>10.2000</UnitPrice><TaxItem><TaxableAmount>1021.26</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.25</TaxAmount></TaxItem><LineItemAmount>1021.26</LineItemAmount></ListLineItem>",
+        );
+    }
+
+    #[test]
+    fn generates_delivery() {
+        let result = DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(5),
+            TaxItem::new(Decimal::from(10), TaxCategory::AA),
+        )
+        .with_surcharge(SurchargeListLineItem::new(
+            Decimal::from(5),
+            ReductionAndSurchargeValue::Amount(Decimal::from(2)),
+        ))
+        .with_delivery(Delivery::date("2020-01-01"))
+        .to_xml();
+
+        assert_eq!(
+            result,
+            "<ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><SurchargeListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.00</Amount></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails><Delivery><Date>2020-01-01</Date></Delivery><TaxItem><TaxableAmount>7.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.70</TaxAmount></TaxItem><LineItemAmount>7.00</LineItemAmount></ListLineItem>",
         );
     }
 }
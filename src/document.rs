@@ -1,6 +1,19 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use crate::{
+    cii::{CiiWriter, ToCii},
+    error::Error,
+    parse::{FromXml, ParseError, XmlNode},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
 pub enum DocumentType {
     CreditMemo,
     FinalSettlement,
+    #[default]
     Invoice,
     InvoiceForAdvancePayment,
     InvoiceForPartialDelivery,
@@ -9,6 +22,47 @@ pub enum DocumentType {
     SubsequentDebit,
 }
 
+impl DocumentType {
+    /// Credit memos and subsequent credits/debits correct an already issued document
+    /// and therefore have to reference it using a `RelatedDocument`.
+    pub(crate) fn requires_related_document(&self) -> bool {
+        matches!(
+            self,
+            DocumentType::CreditMemo | DocumentType::SubsequentCredit | DocumentType::SubsequentDebit
+        )
+    }
+
+    /// Credit memos and subsequent credits are written as UBL `CreditNote`, everything else as `Invoice`.
+    pub(crate) fn is_credit_note(&self) -> bool {
+        matches!(self, DocumentType::CreditMemo | DocumentType::SubsequentCredit)
+    }
+
+    /// The UNTDID 1001 code of the `InvoiceTypeCode` or `CreditNoteTypeCode`.
+    pub(crate) fn type_code(&self) -> &'static str {
+        match self {
+            DocumentType::CreditMemo | DocumentType::SubsequentCredit => "381",
+            DocumentType::FinalSettlement | DocumentType::Invoice => "380",
+            DocumentType::InvoiceForAdvancePayment => "386",
+            DocumentType::InvoiceForPartialDelivery => "326",
+            DocumentType::SelfBilling => "389",
+            DocumentType::SubsequentDebit => "383",
+        }
+    }
+
+    /// The document type of a UNTDID 1001 code, `None` for codes without equivalent.
+    pub(crate) fn from_type_code(code: &str) -> Option<DocumentType> {
+        match code {
+            "380" => Some(DocumentType::Invoice),
+            "381" => Some(DocumentType::CreditMemo),
+            "383" => Some(DocumentType::SubsequentDebit),
+            "386" => Some(DocumentType::InvoiceForAdvancePayment),
+            "326" => Some(DocumentType::InvoiceForPartialDelivery),
+            "389" => Some(DocumentType::SelfBilling),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for DocumentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -27,3 +81,163 @@ impl std::fmt::Display for DocumentType {
         )
     }
 }
+
+impl FromStr for DocumentType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "CreditMemo" => DocumentType::CreditMemo,
+            "FinalSettlement" => DocumentType::FinalSettlement,
+            "Invoice" => DocumentType::Invoice,
+            "InvoiceForAdvancePayment" => DocumentType::InvoiceForAdvancePayment,
+            "InvoiceForPartialDelivery" => DocumentType::InvoiceForPartialDelivery,
+            "SelfBilling" => DocumentType::SelfBilling,
+            "SubsequentCredit" => DocumentType::SubsequentCredit,
+            "SubsequentDebit" => DocumentType::SubsequentDebit,
+            _ => {
+                return Err(Error::UnknownValue {
+                    field: "DocumentType",
+                    value: s.to_string(),
+                });
+            }
+        })
+    }
+}
+
+/// A reference to another document, e.g. the original invoice of a credit memo.
+#[derive(Debug, Default, PartialEq)]
+pub struct RelatedDocument<'a> {
+    invoice_number: Cow<'a, str>,
+    invoice_date: Option<Cow<'a, str>>,
+    document_type: Option<DocumentType>,
+    comment: Option<Cow<'a, str>>,
+}
+
+impl<'a> RelatedDocument<'a> {
+    pub fn new(invoice_number: impl Into<Cow<'a, str>>) -> Self {
+        RelatedDocument {
+            invoice_number: invoice_number.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_invoice_date(mut self, invoice_date: impl Into<Cow<'a, str>>) -> Self {
+        self.invoice_date = Some(invoice_date.into());
+        self
+    }
+
+    pub fn with_document_type(mut self, document_type: DocumentType) -> Self {
+        self.document_type = Some(document_type);
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn into_owned(self) -> RelatedDocument<'static> {
+        RelatedDocument {
+            invoice_number: Cow::Owned(self.invoice_number.into_owned()),
+            invoice_date: self.invoice_date.map(|d| Cow::Owned(d.into_owned())),
+            document_type: self.document_type,
+            comment: self.comment.map(|c| Cow::Owned(c.into_owned())),
+        }
+    }
+}
+
+impl ToXml for RelatedDocument<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("RelatedDocument")?;
+        w.text_element("InvoiceNumber", &self.invoice_number)?;
+
+        if let Some(d) = &self.invoice_date {
+            w.text_element("InvoiceDate", d)?;
+        }
+
+        if let Some(dt) = self.document_type {
+            w.text_element("DocumentType", dt)?;
+        }
+
+        if let Some(c) = &self.comment {
+            w.text_element("Comment", c)?;
+        }
+
+        w.end("RelatedDocument")
+    }
+}
+
+/// Writes a UBL `BillingReference`. The document type and the comment get dropped.
+impl ToUbl for RelatedDocument<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:BillingReference")?;
+        w.start("cac:InvoiceDocumentReference")?;
+        w.text_element("cbc:ID", &self.invoice_number)?;
+
+        if let Some(d) = &self.invoice_date {
+            w.text_element("cbc:IssueDate", d)?;
+        }
+
+        w.end("cac:InvoiceDocumentReference")?;
+        w.end("cac:BillingReference")
+    }
+}
+
+/// Writes a CII `InvoiceReferencedDocument`. The document type and the comment get dropped.
+impl ToCii for RelatedDocument<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:InvoiceReferencedDocument")?;
+        w.text_element("ram:IssuerAssignedID", &self.invoice_number)?;
+
+        if let Some(d) = &self.invoice_date {
+            w.start("ram:FormattedIssueDateTime")?;
+            w.start("qdt:DateTimeString")?;
+            w.attr("format", "102")?;
+            w.text(d.replace('-', ""))?;
+            w.end("qdt:DateTimeString")?;
+            w.end("ram:FormattedIssueDateTime")?;
+        }
+
+        w.end("ram:InvoiceReferencedDocument")
+    }
+}
+
+impl<'a> FromXml<'a> for RelatedDocument<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(RelatedDocument {
+            invoice_number: node.required_child_text("InvoiceNumber")?,
+            invoice_date: node.child_text("InvoiceDate"),
+            document_type: node.child("DocumentType").map(XmlNode::value).transpose()?,
+            comment: node.child_text("Comment"),
+        })
+    }
+}
+
+impl Validate for RelatedDocument<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("InvoiceNumber", &self.invoice_number);
+        v.max_length("InvoiceNumber", &self.invoice_number, 35);
+
+        if let Some(d) = &self.invoice_date {
+            v.date("InvoiceDate", d);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_related_document() {
+        assert_eq!(
+            RelatedDocument::new("993433000298")
+                .with_invoice_date("2020-01-01")
+                .with_document_type(DocumentType::Invoice)
+                .with_comment("original invoice")
+                .to_xml(),
+            "<RelatedDocument><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><DocumentType>Invoice</DocumentType><Comment>original invoice</Comment></RelatedDocument>",
+        );
+    }
+}
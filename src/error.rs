@@ -0,0 +1,116 @@
+use rust_decimal::Decimal;
+use std::fmt;
+
+use crate::{document::DocumentType, version::EbInterfaceVersion};
+
+/// The errors returned by the validating builder methods and, wrapped in a `ValidationError`, by `Invoice::validate`.
+///
+/// `field` is the name of the affected ebInterface element or attribute, e.g. `"IBAN"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A required element or attribute is missing or empty.
+    Missing { field: &'static str },
+    /// The value is longer than the maximum length of the field.
+    TooLong {
+        field: &'static str,
+        max: usize,
+        value: String,
+    },
+    /// The value doesn't have the exact length required by the field.
+    InvalidLength {
+        field: &'static str,
+        length: usize,
+        value: String,
+    },
+    /// The value doesn't match the pattern of the field.
+    RegexMismatch {
+        field: &'static str,
+        pattern: &'static str,
+        value: String,
+    },
+    /// The value is outside of the allowed range of the field, e.g. a percentage above 100.
+    OutOfRange {
+        field: &'static str,
+        min: Decimal,
+        max: Decimal,
+        value: Decimal,
+    },
+    /// The value has to be greater than zero, e.g. a base quantity the unit price refers to.
+    NotPositive { field: &'static str, value: Decimal },
+    /// The check digits of the value are wrong.
+    InvalidChecksum { field: &'static str, value: String },
+    /// The given check digits don't match the ones calculated from the value they belong to, e.g. the `CheckSum` of
+    /// a payment reference.
+    ChecksumMismatch {
+        field: &'static str,
+        expected: String,
+        value: String,
+    },
+    /// The value isn't one of the codes allowed for the field.
+    UnknownValue { field: &'static str, value: String },
+    /// The document type requires a `RelatedDocument`, e.g. a credit memo referencing the original invoice.
+    MissingRelatedDocument { document_type: DocumentType },
+    /// Neither the invoice nor each of its line items have a `Delivery`.
+    MissingDelivery,
+    /// The invoice uses an element or code (e.g. `Contact` or `TaxCategoryCode AE`), which can't be expressed
+    /// in the requested ebInterface version.
+    UnsupportedByVersion {
+        version: EbInterfaceVersion,
+        construct: String,
+    },
+    /// The invoice uses an element or code (e.g. a `BelowTheLineItem` or `TaxCategoryCode D`), which can't be
+    /// expressed in another format, e.g. `"UBL"`.
+    UnsupportedByFormat { format: &'static str, construct: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Missing { field } => write!(f, "{field} is required!"),
+            Error::TooLong { field, max, value } => {
+                write!(f, "{field} {value} is too long (at most {max} characters)!")
+            }
+            Error::InvalidLength { field, length, value } => {
+                write!(f, "{field} {value} is not {length} characters long!")
+            }
+            Error::RegexMismatch { field, pattern, value } => {
+                write!(f, "{field} {value} doesn't match regex {pattern}!")
+            }
+            Error::OutOfRange { field, min, max, value } => {
+                write!(f, "{field} {value} is not between {min} and {max}!")
+            }
+            Error::NotPositive { field, value } => write!(f, "{field} {value} has to be greater than 0!"),
+            Error::InvalidChecksum { field, value } => write!(f, "{field} {value} has an invalid checksum!"),
+            Error::ChecksumMismatch { field, expected, value } => {
+                write!(f, "{field} {value} doesn't match the check digits {expected}!")
+            }
+            Error::UnknownValue { field, value } => write!(f, "Unknown {field} {value}!"),
+            Error::MissingRelatedDocument { document_type } => {
+                write!(f, "DocumentType {document_type} requires at least one RelatedDocument!")
+            }
+            Error::MissingDelivery => write!(f, "Delivery is required either on the invoice or on each item!"),
+            Error::UnsupportedByVersion { version, construct } => {
+                write!(f, "ebInterface {version} doesn't support {construct}!")
+            }
+            Error::UnsupportedByFormat { format, construct } => write!(f, "{format} doesn't support {construct}!"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An error found by `Invoice::validate`, with the path of the affected element or attribute,
+/// e.g. `Details/ItemList/ListLineItem[3]/Quantity/@Unit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub path: String,
+    pub error: Error,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)
+    }
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,192 @@
+use std::{borrow::Cow, fmt, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{codes::IBAN_LENGTHS, error::Error};
+
+const IBAN_REGEX_STR: &str = r"^[A-Z]{2}[0-9]{2}[0-9A-Z]{11,30}$";
+static IBAN_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(IBAN_REGEX_STR).unwrap());
+
+/// An ISO 13616 international bank account number, e.g. `AT611904300234573201`, with a country specific length
+/// and valid check digits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Iban<'a>(Cow<'a, str>);
+
+impl<'a> Iban<'a> {
+    /// Validates the IBAN, after removing spaces and converting it to uppercase, so both the electronic
+    /// (`AT611904300234573201`) and the formatted (`AT61 1904 3002 3457 3201`) form get accepted.
+    pub fn new(iban: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let iban = iban.into();
+        let iban = if iban.bytes().any(|b| b.is_ascii_whitespace() || b.is_ascii_lowercase()) {
+            Cow::Owned(
+                iban.chars()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect(),
+            )
+        } else {
+            iban
+        };
+        let iban = Iban(iban);
+        iban.check()?;
+        Ok(iban)
+    }
+
+    /// Wraps an IBAN as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(iban: Cow<'a, str>) -> Self {
+        Iban(iban)
+    }
+
+    /// Checks the structure, the length for the country and the check digits of the IBAN.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.0.len() > 34 {
+            return Err(Error::TooLong {
+                field: "IBAN",
+                max: 34,
+                value: self.0.to_string(),
+            });
+        }
+        if !IBAN_REGEX.is_match(&self.0) {
+            return Err(Error::RegexMismatch {
+                field: "IBAN",
+                pattern: IBAN_REGEX_STR,
+                value: self.0.to_string(),
+            });
+        }
+        let length = match IBAN_LENGTHS.binary_search_by_key(&self.country_code(), |(country_code, _)| country_code) {
+            Ok(i) => IBAN_LENGTHS[i].1,
+            Err(_) => {
+                return Err(Error::UnknownValue {
+                    field: "IBAN",
+                    value: self.0.to_string(),
+                });
+            }
+        };
+        if self.0.len() != length {
+            return Err(Error::InvalidLength {
+                field: "IBAN",
+                length,
+                value: self.0.to_string(),
+            });
+        }
+        if mod_97(&self.0[4..], &self.0[..4]) != 1 {
+            return Err(Error::InvalidChecksum {
+                field: "IBAN",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the IBAN in its electronic form, without spaces, as written into the documents.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the IBAN in its printed form, in groups of four characters, e.g. `AT61 1904 3002 3457 3201`.
+    pub fn formatted(&self) -> String {
+        let mut formatted = String::with_capacity(self.0.len() + self.0.len() / 4);
+        for (i, c) in self.0.chars().enumerate() {
+            if i > 0 && i % 4 == 0 {
+                formatted.push(' ');
+            }
+            formatted.push(c);
+        }
+        formatted
+    }
+
+    pub fn country_code(&self) -> &str {
+        self.0.get(0..2).unwrap_or_default()
+    }
+
+    pub fn check_digits(&self) -> &str {
+        self.0.get(2..4).unwrap_or_default()
+    }
+
+    /// Returns the country specific basic bank account number, e.g. `1904300234573201`.
+    pub fn bban(&self) -> &str {
+        self.0.get(4..).unwrap_or_default()
+    }
+
+    pub fn into_owned(self) -> Iban<'static> {
+        Iban(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+/// Writes the electronic form, see [`Iban::formatted`] for the printed form.
+impl fmt::Display for Iban<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Calculates the ISO 7064 MOD 97-10 remainder of the given parts, with letters counting as 10 to 35.
+pub(crate) fn mod_97(head: &str, tail: &str) -> u32 {
+    head.chars()
+        .chain(tail.chars())
+        .fold(0, |remainder, c| match c.to_digit(36) {
+            Some(value) if value < 10 => (remainder * 10 + value) % 97,
+            Some(value) => (remainder * 100 + value) % 97,
+            None => remainder,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_electronic_and_formatted_ibans() {
+        for iban in [
+            "AT611904300234573201",
+            "AT61 1904 3002 3457 3201",
+            "at61 1904 3002 3457 3201",
+            "DE89370400440532013000",
+            "GB29NWBK60161331926819",
+            "NO9386011117947",
+        ] {
+            assert!(Iban::new(iban).is_ok(), "{iban}");
+        }
+
+        let iban = Iban::new("AT61 1904 3002 3457 3201").unwrap();
+        assert_eq!(iban.to_string(), "AT611904300234573201");
+        assert_eq!(iban.formatted(), "AT61 1904 3002 3457 3201");
+        assert_eq!((iban.country_code(), iban.check_digits(), iban.bban()), ("AT", "61", "1904300234573201"));
+        assert_eq!(Iban::new("NO9386011117947").unwrap().formatted(), "NO93 8601 1117 947");
+    }
+
+    #[test]
+    fn rejects_invalid_ibans() {
+        assert_eq!(
+            Iban::new("AT611904300234573202"),
+            Err(Error::InvalidChecksum {
+                field: "IBAN",
+                value: "AT611904300234573202".to_string(),
+            })
+        );
+        assert_eq!(
+            Iban::new("AT6119043002345732011"),
+            Err(Error::InvalidLength {
+                field: "IBAN",
+                length: 20,
+                value: "AT6119043002345732011".to_string(),
+            })
+        );
+        assert_eq!(
+            Iban::new("US611904300234573201"),
+            Err(Error::UnknownValue {
+                field: "IBAN",
+                value: "US611904300234573201".to_string(),
+            })
+        );
+        assert!(matches!(Iban::new("AT61-1904-3002-3457-3201"), Err(Error::RegexMismatch { field: "IBAN", .. })));
+        assert!(matches!(
+            Iban::new("AT49120001111111111111111111111111111"),
+            Err(Error::TooLong {
+                field: "IBAN",
+                max: 34,
+                ..
+            })
+        ));
+    }
+}
@@ -1,5 +1,13 @@
-use crate::xml::{ToXml, XmlElement};
+use std::{borrow::Cow, fmt, str::FromStr};
 
+use crate::{
+    error::Error,
+    parse::{FromXml, ParseError, XmlNode},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, PartialEq)]
 pub enum FurtherIdentificationType {
     ARA,
     #[allow(non_camel_case_types)]
@@ -43,22 +51,78 @@ impl std::fmt::Display for FurtherIdentificationType {
     }
 }
 
+impl FromStr for FurtherIdentificationType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "ARA" => FurtherIdentificationType::ARA,
+            "BBG_GZ" => FurtherIdentificationType::BBG_GZ,
+            "Consolidator" => FurtherIdentificationType::Consolidator,
+            "Contract" => FurtherIdentificationType::Contract,
+            "DVR" => FurtherIdentificationType::DVR,
+            "EORI" => FurtherIdentificationType::EORI,
+            "ERSB" => FurtherIdentificationType::ERSB,
+            "FN" => FurtherIdentificationType::FN,
+            "FR" => FurtherIdentificationType::FR,
+            "HG" => FurtherIdentificationType::HG,
+            "Payer" => FurtherIdentificationType::Payer,
+            "FASTNR" => FurtherIdentificationType::FASTNR,
+            "VID" => FurtherIdentificationType::VID,
+            "VN" => FurtherIdentificationType::VN,
+            _ => {
+                return Err(Error::UnknownValue {
+                    field: "FurtherIdentificationType",
+                    value: s.to_string(),
+                });
+            }
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub struct FurtherIdentification<'a> {
-    id: &'a str,
+    id: Cow<'a, str>,
     id_type: FurtherIdentificationType,
 }
 
 impl<'a> FurtherIdentification<'a> {
-    pub fn new(id: &'a str, id_type: FurtherIdentificationType) -> Self {
-        FurtherIdentification { id, id_type }
+    pub fn new(id: impl Into<Cow<'a, str>>, id_type: FurtherIdentificationType) -> Self {
+        FurtherIdentification { id: id.into(), id_type }
+    }
+
+    pub fn into_owned(self) -> FurtherIdentification<'static> {
+        FurtherIdentification {
+            id: Cow::Owned(self.id.into_owned()),
+            id_type: self.id_type,
+        }
     }
 }
 
 impl ToXml for FurtherIdentification<'_> {
-    fn to_xml(&self) -> String {
-        XmlElement::new("FurtherIdentification")
-            .with_attr("IdentificationType", self.id_type.to_string())
-            .with_text(self.id)
-            .to_xml()
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("FurtherIdentification")?;
+        w.attr("IdentificationType", &self.id_type)?;
+        w.text(&self.id)?;
+        w.end("FurtherIdentification")
+    }
+}
+
+impl<'a> FromXml<'a> for FurtherIdentification<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(FurtherIdentification {
+            id: node.text(),
+            id_type: node.required_attr_value("IdentificationType")?,
+        })
+    }
+}
+
+impl Validate for FurtherIdentification<'_> {
+    fn validate(&self, v: &mut Validator) {
+        if self.id.trim().is_empty() {
+            v.error("", Error::Missing {
+                field: "FurtherIdentification",
+            });
+        }
     }
 }
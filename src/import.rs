@@ -0,0 +1,433 @@
+//! Reading UBL 2.1 and CII D16B invoices into the ebInterface model, see `Invoice::from_ubl` and `Invoice::from_cii`.
+//!
+//! Both formats can express more than ebInterface. Everything, which gets dropped or changed on the way, is reported
+//! as `ConversionLoss`:
+//!
+//! - elements without counterpart in ebInterface (e.g. the item identifiers), once per outermost element,
+//! - codes without counterpart (e.g. the tax category `L` or the payment means `10`, in cash),
+//! - a second payment method, as ebInterface has only one,
+//! - stated totals, which differ from the ones ebInterface derives from the line items.
+//!
+//! Attributes (e.g. `schemeID`) aren't reported. Values are read as given, without validating them like the `with_*`
+//! builders do, so the converted invoice should be checked with `Invoice::validate`.
+
+use rust_decimal::Decimal;
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use crate::{
+    address::Address,
+    biller::Biller,
+    contact::Contact,
+    decimal::CloneAndRescale,
+    details::DetailsItem,
+    invoice::{Invoice, Totals},
+    invoice_recipient::InvoiceRecipient,
+    parse::{ParseError, ParseErrorKind, XmlNode},
+    payment_method::{
+        PaymentMethod, PaymentMethodPaymentCard, PaymentMethodSEPADirectDebit, PaymentMethodUniversalBankTransaction,
+        PaymentMethodUniversalBankTransactionBeneficiaryAccount,
+    },
+    reduction_and_surcharge::{
+        Reduction, ReductionAndSurchargeValue, ReductionListLineItem, Surcharge, SurchargeListLineItem,
+    },
+    tax::{TaxCategory, TaxItem},
+    vat_id::VatId,
+};
+
+/// The `GeneratingSystem` of converted invoices.
+pub(crate) const GENERATING_SYSTEM: &str = concat!("eb_interface_rs ", env!("CARGO_PKG_VERSION"));
+
+/// A converted invoice, together with everything, which couldn't be represented in ebInterface.
+#[derive(Debug)]
+pub struct Conversion<'a> {
+    pub invoice: Invoice<'a>,
+    pub losses: Vec<ConversionLoss>,
+}
+
+/// A part of the converted document, which got dropped or changed, with the path of the affected element
+/// (e.g. `InvoiceLine[2]/Item/SellersItemIdentification`) and its (1-based) line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionLoss {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConversionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} at line {}, column {}", self.path, self.message, self.line, self.column)
+    }
+}
+
+/// The biller or invoice recipient of UBL or CII. Without VAT identification number, it is `00000000` (none).
+pub(crate) struct Party<'a> {
+    pub(crate) vat_identification_number: Option<Cow<'a, str>>,
+    pub(crate) address: Option<Address<'a>>,
+    pub(crate) contact: Option<Contact<'a>>,
+}
+
+impl<'a> Party<'a> {
+    pub(crate) fn biller(self) -> Biller<'a> {
+        let mut biller = Biller::new(
+            self.vat_identification_number
+                .map_or_else(VatId::none, VatId::unchecked),
+        );
+        if let Some(address) = self.address {
+            biller = biller.with_address(address);
+        }
+        if let Some(contact) = self.contact {
+            biller = biller.with_contact(contact);
+        }
+        biller
+    }
+
+    pub(crate) fn invoice_recipient(self) -> InvoiceRecipient<'a> {
+        let mut invoice_recipient = InvoiceRecipient::new(
+            self.vat_identification_number
+                .map_or_else(VatId::none, VatId::unchecked),
+        );
+        if let Some(address) = self.address {
+            invoice_recipient = invoice_recipient.with_address(address);
+        }
+        if let Some(contact) = self.contact {
+            invoice_recipient = invoice_recipient.with_contact(contact);
+        }
+        invoice_recipient
+    }
+}
+
+/// A reduction (allowance) or surcharge (charge) of UBL or CII.
+pub(crate) struct AllowanceCharge<'a> {
+    pub(crate) charge: bool,
+    pub(crate) base_amount: Decimal,
+    pub(crate) value: ReductionAndSurchargeValue,
+    pub(crate) comment: Option<Cow<'a, str>>,
+}
+
+impl<'a> AllowanceCharge<'a> {
+    pub(crate) fn add_to_item(self, item: DetailsItem<'a>) -> DetailsItem<'a> {
+        if self.charge {
+            let mut surcharge = SurchargeListLineItem::new(self.base_amount, self.value);
+            if let Some(comment) = self.comment {
+                surcharge = surcharge.with_comment(comment);
+            }
+            item.with_surcharge(surcharge)
+        } else {
+            let mut reduction = ReductionListLineItem::new(self.base_amount, self.value);
+            if let Some(comment) = self.comment {
+                reduction = reduction.with_comment(comment);
+            }
+            item.with_reduction(reduction)
+        }
+    }
+
+    pub(crate) fn add_to_invoice(self, invoice: Invoice<'a>, tax_item: TaxItem) -> Invoice<'a> {
+        if self.charge {
+            let mut surcharge = Surcharge::new(self.base_amount, self.value, tax_item);
+            if let Some(comment) = self.comment {
+                surcharge = surcharge.with_comment(comment);
+            }
+            invoice.with_surcharge(surcharge)
+        } else {
+            let mut reduction = Reduction::new(self.base_amount, self.value, tax_item);
+            if let Some(comment) = self.comment {
+                reduction = reduction.with_comment(comment);
+            }
+            invoice.with_reduction(reduction)
+        }
+    }
+}
+
+/// A payment means of UBL or CII. ebInterface has a single payment method, only multiple accounts can be merged.
+pub(crate) enum PaymentMeans<'a> {
+    CreditTransfer(PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a>),
+    DirectDebit { iban: Option<Cow<'a, str>> },
+    PaymentCard(PaymentMethodPaymentCard<'a>),
+    Other,
+}
+
+/// The payment details, which UBL and CII state apart from the payment means.
+#[derive(Default)]
+pub(crate) struct PaymentDetails<'n, 'a> {
+    pub(crate) payment_reference: Option<&'n XmlNode<'a>>,
+    pub(crate) creditor_id: Option<&'n XmlNode<'a>>,
+    pub(crate) mandate_reference: Option<&'n XmlNode<'a>>,
+    pub(crate) debit_collection_date: Option<Cow<'a, str>>,
+}
+
+/// Returns a total derived by ebInterface, to compare it with the one stated in the document.
+pub(crate) type DerivedTotal = fn(&Totals) -> Decimal;
+
+/// Returns the paths of all elements below the root by their index, numbering repeated elements like
+/// `InvoiceLine[2]`. The path of the root is empty.
+fn paths(root: &XmlNode) -> Vec<String> {
+    fn add(node: &XmlNode, path: &str, paths: &mut Vec<String>) {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for child in &node.children {
+            *counts.entry(child.name).or_default() += 1;
+        }
+
+        let mut numbers: HashMap<&str, usize> = HashMap::new();
+        for child in &node.children {
+            let number = numbers.entry(child.name).or_default();
+            *number += 1;
+            let segment = if counts[child.name] > 1 {
+                format!("{}[{number}]", child.name)
+            } else {
+                child.name.to_string()
+            };
+            let child_path = if path.is_empty() {
+                segment
+            } else {
+                format!("{path}/{segment}")
+            };
+            debug_assert_eq!(paths.len(), child.index);
+            paths.push(child_path.clone());
+            add(child, &child_path, paths);
+        }
+    }
+
+    let mut paths = vec![String::new()];
+    add(root, "", &mut paths);
+    paths
+}
+
+/// Keeps track of the elements read from the document by their index, to report the remaining ones as losses.
+pub(crate) struct Reader<'n, 'a> {
+    root: &'n XmlNode<'a>,
+    read: Vec<bool>,
+    /// The paths of the elements by their index, see `paths`.
+    paths: Vec<String>,
+    stated_totals: Vec<(&'n XmlNode<'a>, DerivedTotal)>,
+    losses: Vec<ConversionLoss>,
+}
+
+impl<'n, 'a> Reader<'n, 'a> {
+    pub(crate) fn new(root: &'n XmlNode<'a>) -> Self {
+        let paths = paths(root);
+        let mut read = vec![false; paths.len()];
+        read[root.index] = true;
+        Reader {
+            root,
+            read,
+            paths,
+            stated_totals: Vec::new(),
+            losses: Vec::new(),
+        }
+    }
+
+    /// Returns the first child element with the given name and marks it as read.
+    pub(crate) fn child(&mut self, node: &'n XmlNode<'a>, name: &str) -> Option<&'n XmlNode<'a>> {
+        let child = node.child(name)?;
+        self.read[child.index] = true;
+        Some(child)
+    }
+
+    /// Returns all child elements with the given name and marks them as read.
+    pub(crate) fn children(&mut self, node: &'n XmlNode<'a>, name: &str) -> Vec<&'n XmlNode<'a>> {
+        let children = node.children.iter().filter(|c| c.name == name).collect::<Vec<_>>();
+        for child in &children {
+            self.read[child.index] = true;
+        }
+        children
+    }
+
+    pub(crate) fn required_child(&mut self, node: &'n XmlNode<'a>, name: &str) -> Result<&'n XmlNode<'a>, ParseError> {
+        self.child(node, name)
+            .ok_or_else(|| node.error(ParseErrorKind::MissingElement(name.to_string())))
+    }
+
+    /// Follows the given path of child elements, e.g. `["Country", "IdentificationCode"]`.
+    pub(crate) fn descendant(&mut self, node: &'n XmlNode<'a>, path: &[&str]) -> Option<&'n XmlNode<'a>> {
+        path.iter().try_fold(node, |node, name| self.child(node, name))
+    }
+
+    pub(crate) fn text(&mut self, node: &'n XmlNode<'a>, path: &[&str]) -> Option<Cow<'a, str>> {
+        self.descendant(node, path).map(XmlNode::text)
+    }
+
+    pub(crate) fn required_text(&mut self, node: &'n XmlNode<'a>, name: &str) -> Result<Cow<'a, str>, ParseError> {
+        self.required_child(node, name).map(XmlNode::text)
+    }
+
+    pub(crate) fn decimal(&mut self, node: &'n XmlNode<'a>, path: &[&str]) -> Result<Option<Decimal>, ParseError> {
+        self.descendant(node, path).map(XmlNode::decimal).transpose()
+    }
+
+    /// Marks the child elements with the given name as read, including their descendants, e.g. amounts, which
+    /// ebInterface derives from the line items.
+    pub(crate) fn skip(&mut self, node: &'n XmlNode<'a>, name: &str) {
+        for child in node.children.iter().filter(|c| c.name == name) {
+            self.mark_subtree(child);
+        }
+    }
+
+    /// Reports the given element as dropped, without reporting its descendants again.
+    pub(crate) fn dropped(&mut self, node: &'n XmlNode<'a>, message: impl Into<String>) {
+        self.loss(node, message);
+        self.mark_subtree(node);
+    }
+
+    fn mark_subtree(&mut self, node: &'n XmlNode<'a>) {
+        self.read[node.index] = true;
+        for child in &node.children {
+            self.mark_subtree(child);
+        }
+    }
+
+    /// Compares the given total of the document with the one derived by ebInterface, after the conversion.
+    pub(crate) fn stated_total(&mut self, node: Option<&'n XmlNode<'a>>, derived: DerivedTotal) {
+        if let Some(node) = node {
+            self.stated_totals.push((node, derived));
+        }
+    }
+
+    pub(crate) fn loss(&mut self, node: &XmlNode, message: impl Into<String>) {
+        self.losses.push(ConversionLoss {
+            path: self.paths.get(node.index).cloned().unwrap_or_default(),
+            line: node.line,
+            column: node.column,
+            message: message.into(),
+        });
+    }
+
+    /// Returns the tax item of the given EN 16931 category code and percentage. Unknown codes are read as `S`.
+    pub(crate) fn tax_item(&mut self, node: &XmlNode, code: Option<&str>, percent: Option<Decimal>) -> TaxItem {
+        let code = code.unwrap_or_default();
+        let tax_category = TaxCategory::from_en16931_code(code).unwrap_or_else(|| {
+            self.loss(node, format!("unknown tax category code {code}, read as S"));
+            TaxCategory::S
+        });
+        TaxItem::new(percent.unwrap_or_default(), tax_category)
+    }
+
+    /// Returns the contact with the given name. ebInterface requires a name, so the phone number and email address
+    /// of a contact without one become part of the address.
+    pub(crate) fn contact(
+        &mut self,
+        node: &XmlNode,
+        name: Option<Cow<'a, str>>,
+        phone: Option<Cow<'a, str>>,
+        email: Option<Cow<'a, str>>,
+        address: &mut Option<Address<'a>>,
+    ) -> Option<Contact<'a>> {
+        if let Some(name) = name {
+            let mut contact = Contact::new(name);
+            if let Some(phone) = phone {
+                contact = contact.with_phone(phone);
+            }
+            if let Some(email) = email {
+                contact = contact.with_email(email);
+            }
+            return Some(contact);
+        }
+
+        match address.take() {
+            Some(mut a) => {
+                if let Some(phone) = phone {
+                    a = a.with_phone(phone);
+                }
+                if let Some(email) = email {
+                    a = a.with_email(email);
+                }
+                *address = Some(a);
+            }
+            None if phone.is_some() || email.is_some() => {
+                self.loss(node, "contact without name and address, dropped");
+            }
+            None => {}
+        }
+        None
+    }
+
+    /// Merges the payment means into the single payment method of ebInterface. The first payment means decides
+    /// the method, others are dropped, unless they are further accounts for a credit transfer.
+    pub(crate) fn payment_method(
+        &mut self,
+        payment_means: Vec<(&'n XmlNode<'a>, PaymentMeans<'a>)>,
+        details: PaymentDetails<'n, 'a>,
+    ) -> Option<PaymentMethod<'a>> {
+        let mut payment_means = payment_means.into_iter();
+        let (_, first) = payment_means.next()?;
+
+        if !matches!(first, PaymentMeans::CreditTransfer(_))
+            && let Some(node) = details.payment_reference
+        {
+            self.loss(node, "payment reference without credit transfer, dropped");
+        }
+        if !matches!(first, PaymentMeans::DirectDebit { .. }) {
+            for node in [details.creditor_id, details.mandate_reference].into_iter().flatten() {
+                self.loss(node, "direct debit details without direct debit, dropped");
+            }
+        }
+
+        let mut accounts = Vec::new();
+        let method = match first {
+            PaymentMeans::CreditTransfer(account) => {
+                accounts.push(account);
+                None
+            }
+            PaymentMeans::DirectDebit { iban } => {
+                Some(PaymentMethod::sepa_direct_debit(PaymentMethodSEPADirectDebit::imported(
+                    iban,
+                    details.creditor_id.map(XmlNode::text),
+                    details.mandate_reference.map(XmlNode::text),
+                    details.debit_collection_date,
+                )))
+            }
+            PaymentMeans::PaymentCard(payment_card) => Some(PaymentMethod::payment_card(payment_card)),
+            PaymentMeans::Other => Some(PaymentMethod::other_payment()),
+        };
+
+        for (node, means) in payment_means {
+            match means {
+                PaymentMeans::CreditTransfer(account) if method.is_none() => accounts.push(account),
+                _ => self.loss(node, "second payment method, dropped"),
+            }
+        }
+
+        method.or_else(|| {
+            Some(PaymentMethod::universal_bank_transaction(PaymentMethodUniversalBankTransaction::imported(
+                accounts,
+                details.payment_reference.map(XmlNode::text),
+            )))
+        })
+    }
+
+    fn unread(&self, node: &'n XmlNode<'a>, unread: &mut Vec<&'n XmlNode<'a>>) {
+        for child in &node.children {
+            if self.read[child.index] {
+                self.unread(child, unread);
+            } else {
+                unread.push(child);
+            }
+        }
+    }
+
+    /// Compares the stated totals and reports all elements, which haven't been read. The losses are sorted in
+    /// document order.
+    pub(crate) fn finish(mut self, invoice: Invoice<'a>) -> Conversion<'a> {
+        let totals = invoice.totals();
+        for (node, derived) in std::mem::take(&mut self.stated_totals) {
+            let derived = derived(&totals).clone_with_scale(2);
+            match node.decimal() {
+                Ok(stated) if stated == derived => {}
+                _ => self
+                    .loss(node, format!("{} {} differs from {derived} derived by ebInterface", node.name, node.text())),
+            }
+        }
+
+        let mut unread = Vec::new();
+        self.unread(self.root, &mut unread);
+        for node in unread {
+            self.loss(node, "unsupported element, dropped");
+        }
+        self.losses.sort_by_key(|loss| (loss.line, loss.column));
+
+        Conversion {
+            invoice,
+            losses: self.losses,
+        }
+    }
+}
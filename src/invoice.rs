@@ -1,58 +1,171 @@
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap, fmt, io};
 
 use crate::{
+    below_the_line_item::BelowTheLineItem,
     biller::Biller,
-    decimal::CloneAndRescale,
+    cii::{self, CiiProfile, CiiWriter, QDT_NAMESPACE, RAM_NAMESPACE, RSM_NAMESPACE, ToCii, UDT_NAMESPACE},
+    delivery::Delivery,
     details::{Details, DetailsItem},
-    document::DocumentType,
+    document::{DocumentType, RelatedDocument},
+    error::{Error, ValidationError},
+    import::{Conversion, Reader},
     invoice_recipient::InvoiceRecipient,
+    parse::{FromXml, ParseError, ParseErrorKind, parse_document},
+    payment_conditions::PaymentConditions,
     payment_method::PaymentMethod,
-    tax::{TaxCategory, TaxItem, TaxItemWithTaxableAmount},
-    xml::{ToXml, XmlElement},
+    reduction_and_surcharge::{Reduction, ReductionAndSurchargeDetails, Surcharge},
+    rounding::RoundingPolicy,
+    rules::{RuleViolation, Rules},
+    tax::{TaxCategory, TaxItemWithTaxableAmount},
+    ubl::{
+        self, CAC_NAMESPACE, CBC_NAMESPACE, CREDIT_NOTE_NAMESPACE, CUSTOMIZATION_ID, INVOICE_NAMESPACE, PROFILE_ID,
+        ToUbl, UblWriter,
+    },
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{IoWrite, XmlWriter},
 };
 
-#[derive(Default)]
+pub(crate) const NAMESPACE: &str = "http://www.ebinterface.at/schema/6p1/";
+
+#[derive(Debug, PartialEq)]
+enum RoundingAmount {
+    Amount(Decimal),
+    CashRounding(Decimal),
+}
+
+/// The amounts derived from the items, the reductions and surcharges and the below the line items, see
+/// [`Invoice::totals`]. The getters return the amounts rounded to cents with the rounding policy of the invoice, as
+/// they are written into the documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Totals {
+    pub(crate) line_total_amount: Decimal,
+    pub(crate) allowance_total_amount: Decimal,
+    pub(crate) charge_total_amount: Decimal,
+    pub(crate) tax_items: Vec<TaxItemWithTaxableAmount>,
+    pub(crate) tax_total_amount: Decimal,
+    pub(crate) total_gross_amount: Decimal,
+    pub(crate) prepaid_amount: Option<Decimal>,
+    pub(crate) rounding_amount: Option<Decimal>,
+    pub(crate) payable_amount: Decimal,
+    rounding: RoundingPolicy,
+}
+
+impl Totals {
+    /// The sum of the line item amounts.
+    pub fn line_total_amount(&self) -> Decimal {
+        self.rounding.round(self.line_total_amount)
+    }
+
+    /// The sum of the reductions on invoice level.
+    pub fn allowance_total_amount(&self) -> Decimal {
+        self.rounding.round(self.allowance_total_amount)
+    }
+
+    /// The sum of the surcharges on invoice level.
+    pub fn charge_total_amount(&self) -> Decimal {
+        self.rounding.round(self.charge_total_amount)
+    }
+
+    /// The net total: the line total minus the reductions plus the surcharges on invoice level.
+    pub fn tax_basis_total_amount(&self) -> Decimal {
+        self.rounding
+            .round(self.line_total_amount - self.allowance_total_amount + self.charge_total_amount)
+    }
+
+    /// The taxable amount and the tax amount per tax rate and category, as in the `Tax` element.
+    pub fn tax_items(&self) -> &[TaxItemWithTaxableAmount] {
+        &self.tax_items
+    }
+
+    /// The sum of the tax amounts.
+    pub fn tax_total_amount(&self) -> Decimal {
+        self.rounding.round(self.tax_total_amount)
+    }
+
+    pub fn total_gross_amount(&self) -> Decimal {
+        self.rounding.round(self.total_gross_amount)
+    }
+
+    pub fn prepaid_amount(&self) -> Option<Decimal> {
+        self.prepaid_amount.map(|a| self.rounding.round(a))
+    }
+
+    pub fn rounding_amount(&self) -> Option<Decimal> {
+        self.rounding_amount.map(|a| self.rounding.round(a))
+    }
+
+    pub fn payable_amount(&self) -> Decimal {
+        self.rounding.round(self.payable_amount)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct Invoice<'a> {
-    generating_system: &'a str,
-    invoice_currency: &'a str,
-    document_title: Option<&'a str>,
-    language: Option<&'a str>,
-    invoice_number: &'a str,
-    invoice_date: &'a str,
+    generating_system: Cow<'a, str>,
+    document_type: DocumentType,
+    invoice_currency: Cow<'a, str>,
+    document_title: Option<Cow<'a, str>>,
+    language: Option<Cow<'a, str>>,
+    invoice_number: Cow<'a, str>,
+    invoice_date: Cow<'a, str>,
+    related_documents: Vec<RelatedDocument<'a>>,
+    delivery: Option<Delivery<'a>>,
     biller: Biller<'a>,
     invoice_recipient: InvoiceRecipient<'a>,
     details: Details<'a>,
+    reduction_and_surcharge: Option<ReductionAndSurchargeDetails<'a>>,
+    prepaid_amount: Option<Decimal>,
+    rounding_amount: Option<RoundingAmount>,
+    below_the_line_items: Vec<BelowTheLineItem<'a>>,
     payment_method: Option<PaymentMethod<'a>>,
+    payment_conditions: Option<PaymentConditions<'a>>,
+    rounding: RoundingPolicy,
 }
 
 impl<'a> Invoice<'a> {
     pub fn new(
-        generating_system: &'a str,
-        invoice_currency: &'a str,
-        invoice_number: &'a str,
-        invoice_date: &'a str,
+        generating_system: impl Into<Cow<'a, str>>,
+        invoice_currency: impl Into<Cow<'a, str>>,
+        invoice_number: impl Into<Cow<'a, str>>,
+        invoice_date: impl Into<Cow<'a, str>>,
         biller: Biller<'a>,
         invoice_recipient: InvoiceRecipient<'a>,
     ) -> Self {
         Self {
-            generating_system,
-            invoice_currency,
-            invoice_number,
-            invoice_date,
+            generating_system: generating_system.into(),
+            invoice_currency: invoice_currency.into(),
+            invoice_number: invoice_number.into(),
+            invoice_date: invoice_date.into(),
             biller,
             invoice_recipient,
             ..Default::default()
         }
     }
 
-    pub fn with_document_title(mut self, document_title: &'a str) -> Self {
-        self.document_title = Some(document_title);
+    pub fn with_document_type(mut self, document_type: DocumentType) -> Self {
+        self.document_type = document_type;
+        self
+    }
+
+    pub fn with_related_document(mut self, related_document: RelatedDocument<'a>) -> Self {
+        self.related_documents.push(related_document);
+        self
+    }
+
+    pub fn with_delivery(mut self, delivery: Delivery<'a>) -> Self {
+        self.delivery = Some(delivery);
         self
     }
 
-    pub fn with_language(mut self, language: &'a str) -> Self {
-        self.language = Some(language);
+    pub fn with_document_title(mut self, document_title: impl Into<Cow<'a, str>>) -> Self {
+        self.document_title = Some(document_title.into());
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<Cow<'a, str>>) -> Self {
+        self.language = Some(language.into());
         self
     }
 
@@ -66,83 +179,722 @@ impl<'a> Invoice<'a> {
         self
     }
 
+    pub fn with_reduction(mut self, reduction: Reduction<'a>) -> Self {
+        self.reduction_and_surcharge = Some(self.reduction_and_surcharge.unwrap_or_default().with_reduction(reduction));
+        self
+    }
+
+    pub fn with_surcharge(mut self, surcharge: Surcharge<'a>) -> Self {
+        self.reduction_and_surcharge = Some(self.reduction_and_surcharge.unwrap_or_default().with_surcharge(surcharge));
+        self
+    }
+
+    /// Sets the already paid amount (e.g. by advance payments), which gets subtracted from the payable amount.
+    pub fn with_prepaid_amount(mut self, prepaid_amount: Decimal) -> Self {
+        self.prepaid_amount = Some(prepaid_amount);
+        self
+    }
+
+    /// Sets the rounding amount, which gets added to the payable amount.
+    pub fn with_rounding_amount(mut self, rounding_amount: Decimal) -> Self {
+        self.rounding_amount = Some(RoundingAmount::Amount(rounding_amount));
+        self
+    }
+
+    /// Rounds the payable amount to the nearest multiple of the given increment (e.g. `0.05` for 5-cent rounding)
+    /// and sets the difference as rounding amount. The increment has to be greater than zero.
+    pub fn with_cash_rounding(mut self, increment: Decimal) -> Result<Self, Error> {
+        if increment <= Decimal::ZERO {
+            return Err(Error::NotPositive {
+                field: "RoundingAmount",
+                value: increment,
+            });
+        }
+        self.rounding_amount = Some(RoundingAmount::CashRounding(increment));
+        Ok(self)
+    }
+
+    /// Sets how and where the amounts get rounded, by default as described in [`rounding`](crate::rounding).
+    pub fn with_rounding_policy(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_below_the_line_item(mut self, below_the_line_item: BelowTheLineItem<'a>) -> Self {
+        self.below_the_line_items.push(below_the_line_item);
+        self
+    }
+
     pub fn with_payment_method(mut self, payment_method: PaymentMethod<'a>) -> Self {
         self.payment_method = Some(payment_method);
         self
     }
 
-    fn invoice_tax_items(&self) -> Vec<((Decimal, TaxCategory), Decimal)> {
+    pub fn with_payment_conditions(mut self, payment_conditions: PaymentConditions<'a>) -> Self {
+        self.payment_conditions = Some(payment_conditions);
+        self
+    }
+
+    /// Converts the invoice into an invoice, which owns all of its data,
+    /// e.g. to store it or to send it to another thread.
+    pub fn into_owned(self) -> Invoice<'static> {
+        Invoice {
+            generating_system: Cow::Owned(self.generating_system.into_owned()),
+            document_type: self.document_type,
+            invoice_currency: Cow::Owned(self.invoice_currency.into_owned()),
+            document_title: self.document_title.map(|t| Cow::Owned(t.into_owned())),
+            language: self.language.map(|l| Cow::Owned(l.into_owned())),
+            invoice_number: Cow::Owned(self.invoice_number.into_owned()),
+            invoice_date: Cow::Owned(self.invoice_date.into_owned()),
+            related_documents: self.related_documents.into_iter().map(RelatedDocument::into_owned).collect(),
+            delivery: self.delivery.map(Delivery::into_owned),
+            biller: self.biller.into_owned(),
+            invoice_recipient: self.invoice_recipient.into_owned(),
+            details: self.details.into_owned(),
+            reduction_and_surcharge: self.reduction_and_surcharge.map(ReductionAndSurchargeDetails::into_owned),
+            prepaid_amount: self.prepaid_amount,
+            rounding_amount: self.rounding_amount,
+            below_the_line_items: self.below_the_line_items.into_iter().map(BelowTheLineItem::into_owned).collect(),
+            payment_method: self.payment_method.map(PaymentMethod::into_owned),
+            payment_conditions: self.payment_conditions.map(PaymentConditions::into_owned),
+            rounding: self.rounding,
+        }
+    }
+
+    /// Reads an ebInterface 6.1 document. Strings get borrowed from the given XML, where possible.
+    ///
+    /// Amounts, which are derived when writing the invoice (taxable amounts, line item amounts, the tax summary,
+    /// the total gross amount and the payable amount), get ignored. The rounding amount is read as a fixed amount.
+    pub fn from_xml(xml: &'a str) -> Result<Invoice<'a>, ParseError> {
+        let root = parse_document(xml)?;
+
+        if root.name != "Invoice" {
+            return Err(root.error(ParseErrorKind::UnexpectedElement(root.name.to_string())));
+        }
+        match root.namespace() {
+            Some(NAMESPACE) => {}
+            namespace => {
+                return Err(root.error(ParseErrorKind::UnsupportedNamespace(namespace.unwrap_or_default().to_string())));
+            }
+        }
+
+        Ok(Invoice {
+            generating_system: root.required_attr("GeneratingSystem")?,
+            document_type: root.required_attr_value("DocumentType")?,
+            invoice_currency: root.required_attr("InvoiceCurrency")?,
+            document_title: root.attr("DocumentTitle"),
+            language: root.attr("Language"),
+            invoice_number: root.required_child_text("InvoiceNumber")?,
+            invoice_date: root.required_child_text("InvoiceDate")?,
+            related_documents: root.children_from_xml("RelatedDocument")?,
+            delivery: root.child_from_xml("Delivery")?,
+            biller: Biller::from_xml(root.required_child("Biller")?)?,
+            invoice_recipient: InvoiceRecipient::from_xml(root.required_child("InvoiceRecipient")?)?,
+            details: Details::from_xml(root.required_child("Details")?)?,
+            reduction_and_surcharge: root.child_from_xml("ReductionAndSurchargeDetails")?,
+            prepaid_amount: root.child_decimal("PrepaidAmount")?,
+            rounding_amount: root.child_decimal("RoundingAmount")?.map(RoundingAmount::Amount),
+            below_the_line_items: root.children_from_xml("BelowTheLineItem")?,
+            payment_method: root.child_from_xml("PaymentMethod")?,
+            payment_conditions: root.child_from_xml("PaymentConditions")?,
+            rounding: RoundingPolicy::default(),
+        })
+    }
+
+    /// Reads a UBL 2.1 `Invoice` or `CreditNote`, e.g. a Peppol BIS Billing 3.0 document. Everything, which has no
+    /// counterpart in ebInterface, is reported in the `losses` of the returned conversion, see `import`.
+    ///
+    /// Like `from_xml`, derived amounts get ignored, but differing stated totals are reported.
+    pub fn from_ubl(xml: &'a str) -> Result<Conversion<'a>, ParseError> {
+        let root = parse_document(xml)?;
+        let mut r = Reader::new(&root);
+        let invoice = ubl::read_invoice(&mut r, &root)?;
+        Ok(r.finish(invoice))
+    }
+
+    /// Reads a UN/CEFACT Cross Industry Invoice (CII D16B) of any profile, e.g. ZUGFeRD/Factur-X or XRechnung, like
+    /// `from_ubl`. Dates have to be given in the format `102` (`YYYYMMDD`).
+    pub fn from_cii(xml: &'a str) -> Result<Conversion<'a>, ParseError> {
+        let root = parse_document(xml)?;
+        let mut r = Reader::new(&root);
+        let invoice = cii::read_invoice(&mut r, &root)?;
+        Ok(r.finish(invoice))
+    }
+
+    /// Checks the whole document against the constraints of the ebInterface 6.1 schema (required elements,
+    /// max lengths, patterns and code lists) and against the rules, which can't be enforced by the builder methods.
+    /// A credit memo, for example, has to reference the original invoice using a `RelatedDocument`.
+    ///
+    /// Returns all errors found, each with the path of the affected element, e.g. `Details/ItemList/ListLineItem[3]`.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut v = Validator::new();
+
+        v.required("@GeneratingSystem", &self.generating_system);
+        v.currency_code("@InvoiceCurrency", &self.invoice_currency);
+        v.required("InvoiceNumber", &self.invoice_number);
+        v.max_length("InvoiceNumber", &self.invoice_number, 35);
+        v.date("InvoiceDate", &self.invoice_date);
+
+        if self.document_type.requires_related_document() && self.related_documents.is_empty() {
+            v.error(
+                "RelatedDocument",
+                Error::MissingRelatedDocument {
+                    document_type: self.document_type,
+                },
+            );
+        }
+        v.children("RelatedDocument", &self.related_documents);
+
+        if self.delivery.is_none() && self.details.items.iter().any(|i| i.delivery.is_none()) {
+            v.error("Delivery", Error::MissingDelivery);
+        }
+        if let Some(delivery) = &self.delivery {
+            v.child("Delivery", |v| delivery.validate(v));
+        }
+
+        v.child("Biller", |v| self.biller.validate(v));
+        v.child("InvoiceRecipient", |v| self.invoice_recipient.validate(v));
+        v.child("Details", |v| self.details.validate(v));
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            v.child("ReductionAndSurchargeDetails", |v| reduction_and_surcharge.validate(v));
+        }
+
+        v.children("BelowTheLineItem", &self.below_the_line_items);
+
+        if let Some(payment_method) = &self.payment_method {
+            v.child("PaymentMethod", |v| payment_method.validate(v));
+        }
+
+        if let Some(payment_conditions) = &self.payment_conditions {
+            v.child("PaymentConditions", |v| payment_conditions.validate(v));
+        }
+
+        if v.errors.is_empty() { Ok(()) } else { Err(v.errors) }
+    }
+
+    /// Checks the business rules of the e-Rechnung portal (e.g. whether the tax amounts add up) against the
+    /// generated XML document. See `rules` for the list of rules.
+    pub fn check_rules(&self, rules: &Rules) -> Vec<RuleViolation> {
+        rules.check(&self.to_xml()).expect("the generated XML document is well-formed")
+    }
+
+    /// Returns the tax summary, the taxable amounts and the tax amounts per tuple of tax_percent and tax_category.
+    fn invoice_tax_items(&self) -> Vec<TaxItemWithTaxableAmount> {
         // Collect all taxes, grouped by tuples of tax_percent and tax_category.
-        let mut tax_items: HashMap<(Decimal, TaxCategory), Decimal> = HashMap::new();
+        let mut tax_items: HashMap<(Decimal, TaxCategory), TaxItemWithTaxableAmount> = HashMap::new();
+        let mut add = |item: TaxItemWithTaxableAmount| {
+            tax_items
+                .entry((item.tax_percent(), item.tax_category()))
+                .and_modify(|t| t.add(&item))
+                .or_insert(item);
+        };
         for i in &self.details.items {
-            let k = (i.tax_item.tax_percent, i.tax_item.tax_category);
-            let s = tax_items.get(&k).unwrap_or(&Decimal::ZERO);
-            tax_items.insert(k, s + i.line_item_amount());
+            add(i.amounts(self.rounding).tax_item().clone());
+        }
+        if let Some(rs) = &self.reduction_and_surcharge {
+            for (tax_item, amount) in rs.taxable_amounts(self.rounding) {
+                add(tax_item.taxable_amount(amount, self.rounding));
+            }
         }
 
         // To get consistent results, sort by keys (tax_percent and tax_category).
-        let mut sorted_tax_item_entries: Vec<((Decimal, TaxCategory), Decimal)> =
+        let mut sorted_tax_item_entries: Vec<((Decimal, TaxCategory), TaxItemWithTaxableAmount)> =
             tax_items.into_iter().collect();
-        sorted_tax_item_entries.sort_by_key(|k| (k.0 .0, k.0 .1));
+        sorted_tax_item_entries.sort_by_key(|k| k.0);
+
+        sorted_tax_item_entries.into_iter().map(|(_, t)| t).collect()
+    }
 
-        sorted_tax_item_entries
+    /// Streams the XML document into the given `io::Write`.
+    /// The output gets written in many small chunks, so wrap unbuffered writers (e.g. a `File`) in a `BufWriter`.
+    pub fn write_xml<W: io::Write>(&self, w: W) -> io::Result<()> {
+        let mut out = IoWrite::new(w);
+        self.write(&mut XmlWriter::new(&mut out).with_rounding(self.rounding))
+            .map_err(|_| out.error.take().unwrap_or_else(|| io::Error::other("formatter error")))
+    }
+
+    /// Streams the XML document into the given `fmt::Write`.
+    pub fn write_xml_fmt<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.write(&mut XmlWriter::new(w).with_rounding(self.rounding))
+    }
+
+    /// Validates the invoice and only returns the XML document, if it's valid.
+    pub fn try_to_xml(&self) -> Result<String, Vec<ValidationError>> {
+        self.validate()?;
+        Ok(self.to_xml())
     }
 
     pub fn to_xml(&self) -> String {
-        let tax_item_xmls = self
-            .invoice_tax_items()
+        let mut s = String::with_capacity(1024 + self.details.items.len() * 512);
+        self.write_xml_fmt(&mut s).expect("writing to a String doesn't fail");
+        s
+    }
+
+    /// Returns the XML document in the namespace and structure of the given ebInterface version.
+    /// Fails with `Error::UnsupportedByVersion`, if the invoice uses an element or code, which the version can't
+    /// express, e.g. a `Contact` in ebInterface 5.0.
+    pub fn to_xml_version(&self, version: EbInterfaceVersion) -> Result<String, Error> {
+        self.check_version(version)?;
+
+        let mut s = String::with_capacity(1024 + self.details.items.len() * 512);
+        self.write(&mut XmlWriter::new(&mut s).with_version(version).with_rounding(self.rounding))
+            .expect("writing to a String doesn't fail");
+        Ok(s)
+    }
+
+    fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        let unsupported = |construct: &str| {
+            Err(Error::UnsupportedByVersion {
+                version,
+                construct: construct.to_string(),
+            })
+        };
+
+        if let Some(delivery) = &self.delivery {
+            delivery.check_version(version)?;
+        }
+        self.biller.check_version(version)?;
+        self.invoice_recipient.check_version(version)?;
+        for item in &self.details.items {
+            item.check_version(version)?;
+        }
+        if let Some(rs) = &self.reduction_and_surcharge {
+            for (tax_item, _) in rs.taxable_amounts(self.rounding) {
+                tax_item.check_version(version)?;
+            }
+        }
+        if !version.has_prepaid_and_rounding_amount() {
+            if self.prepaid_amount.is_some() {
+                return unsupported("PrepaidAmount");
+            }
+            if self.rounding_amount.is_some() {
+                return unsupported("RoundingAmount");
+            }
+        }
+        if !version.has_below_the_line_items() && !self.below_the_line_items.is_empty() {
+            return unsupported("BelowTheLineItem");
+        }
+        if let Some(payment_method) = &self.payment_method {
+            payment_method.check_version(version)?;
+        }
+        Ok(())
+    }
+
+    /// Calculates the totals of the invoice. These are the amounts written into the ebInterface, UBL and CII
+    /// documents.
+    pub fn totals(&self) -> Totals {
+        let rounding = self.rounding;
+
+        let tax_items = self.invoice_tax_items();
+        let tax_total_amount = tax_items.iter().fold(Decimal::ZERO, |sum, t| sum + t.tax_amount());
+
+        let line_total_amount = self
+            .details
+            .items
             .iter()
-            .map(|e| TaxItem::new(e.0 .0, e.0 .1).taxable_amount(e.1))
-            .collect::<Vec<TaxItemWithTaxableAmount>>();
+            .fold(Decimal::ZERO, |sum, i| sum + i.line_item_amount(rounding));
+        let (allowance_total_amount, charge_total_amount) = match &self.reduction_and_surcharge {
+            Some(rs) => rs.allowance_and_charge_totals(rounding),
+            None => (Decimal::ZERO, Decimal::ZERO),
+        };
+
+        // Built from the rounded amounts as written, so the documents add up (EN 16931 BR-CO-15).
+        let total_gross_amount =
+            rounding.round(line_total_amount - allowance_total_amount + charge_total_amount + tax_total_amount); /* + sum of other_vat_able_taxes at root */
+
+        let unrounded_payable_amount = total_gross_amount - self.prepaid_amount.unwrap_or(Decimal::ZERO)
+            + self.below_the_line_items.iter().fold(Decimal::ZERO, |sum, i| sum + i.line_item_amount);
+
+        let rounding_amount = self.rounding_amount.as_ref().map(|r| match r {
+            RoundingAmount::Amount(amount) => *amount,
+            RoundingAmount::CashRounding(increment) => {
+                (unrounded_payable_amount / increment).round_dp_with_strategy(0, rounding.strategy())
+                    * increment
+                    - unrounded_payable_amount
+            }
+        });
 
-        let mut tax = XmlElement::new("Tax");
-        for tax_item_xml in tax_item_xmls {
-            tax = tax.with_element(&tax_item_xml);
+        let payable_amount = unrounded_payable_amount + rounding_amount.unwrap_or(Decimal::ZERO);
+
+        Totals {
+            line_total_amount,
+            allowance_total_amount,
+            charge_total_amount,
+            tax_items,
+            tax_total_amount,
+            total_gross_amount,
+            prepaid_amount: self.prepaid_amount,
+            rounding_amount,
+            payable_amount,
+            rounding,
         }
+    }
 
-        let total_gross_amount = self.details.items.iter().fold(Decimal::ZERO, |sum, i| sum + i.line_item_total_gross_amount()) /* + sum of surcharges at root + sum of other_vat_able_taxes at root - sum of reductions at root */;
-        let payable_amount = total_gross_amount /* - prepaid_amount + rounding_amount + sum of below_the_lines_items */;
+    fn write(&self, w: &mut XmlWriter) -> fmt::Result {
+        let Totals {
+            tax_items,
+            total_gross_amount,
+            prepaid_amount,
+            rounding_amount,
+            payable_amount,
+            ..
+        } = self.totals();
 
-        let mut invoice = XmlElement::new(&DocumentType::Invoice.to_string())
-            .with_attr("xmlns", "http://www.ebinterface.at/schema/6p1/")
-            .with_attr("GeneratingSystem", self.generating_system)
-            .with_attr("DocumentType", DocumentType::Invoice.to_string())
-            .with_attr("InvoiceCurrency", self.invoice_currency);
+        w.raw("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
 
-        if let Some(document_title) = self.document_title {
-            invoice = invoice.with_attr("DocumentTitle", document_title);
+        w.start("Invoice")?;
+        let xmlns = if w.version().has_qualified_names() { "xmlns:eb" } else { "xmlns" };
+        w.attr(xmlns, w.version().namespace())?;
+        w.attr("GeneratingSystem", &self.generating_system)?;
+        w.attr("DocumentType", self.document_type)?;
+        w.attr("InvoiceCurrency", &self.invoice_currency)?;
+
+        if let Some(document_title) = &self.document_title {
+            w.attr("DocumentTitle", document_title)?;
         }
 
-        if let Some(language) = self.language {
-            invoice = invoice.with_attr("Language", language);
+        if let Some(language) = &self.language {
+            w.attr("Language", language)?;
         }
 
-        invoice = invoice
-            .with_text_element("InvoiceNumber", self.invoice_number)
-            .with_text_element("InvoiceDate", self.invoice_date)
-            .with_element(&self.biller)
-            .with_element(&self.invoice_recipient)
-            .with_element(&self.details);
+        w.text_element("InvoiceNumber", &self.invoice_number)?;
+        w.text_element("InvoiceDate", &self.invoice_date)?;
 
-        invoice = invoice
-            .with_element(&tax)
-            .with_text_element(
-                "TotalGrossAmount",
-                total_gross_amount.clone_with_scale(2).to_string(),
-            )
-            .with_text_element(
-                "PayableAmount",
-                payable_amount.clone_with_scale(2).to_string(),
-            );
+        for related_document in &self.related_documents {
+            w.element(related_document)?;
+        }
+
+        if let Some(delivery) = &self.delivery {
+            w.element(delivery)?;
+        }
+
+        w.element(&self.biller)?;
+        w.element(&self.invoice_recipient)?;
+        w.element(&self.details)?;
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            w.element(reduction_and_surcharge)?;
+        }
+
+        w.start("Tax")?;
+        if w.version().has_tax_item() {
+            for tax_item in &tax_items {
+                w.element(tax_item)?;
+            }
+        } else {
+            w.start("VAT")?;
+            for tax_item in &tax_items {
+                tax_item.write_vat_item(w)?;
+            }
+            w.end("VAT")?;
+        }
+        w.end("Tax")?;
+
+        w.text_element("TotalGrossAmount", w.round(total_gross_amount))?;
+
+        if let Some(prepaid_amount) = prepaid_amount {
+            w.text_element("PrepaidAmount", w.round(prepaid_amount))?;
+        }
+
+        if let Some(rounding_amount) = rounding_amount {
+            w.text_element("RoundingAmount", w.round(rounding_amount))?;
+        }
+
+        for below_the_line_item in &self.below_the_line_items {
+            w.element(below_the_line_item)?;
+        }
+
+        w.text_element("PayableAmount", w.round(payable_amount))?;
 
         if let Some(payment_method) = &self.payment_method {
-            invoice = invoice.with_element(payment_method);
+            w.element(payment_method)?;
         }
 
-        format!(
-            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}",
-            invoice.to_xml()
-        )
+        if let Some(payment_conditions) = &self.payment_conditions {
+            w.element(&payment_conditions.payable_amount(payable_amount))?;
+        }
+
+        w.end("Invoice")
+    }
+
+    /// Returns the invoice as UBL 2.1 document following Peppol BIS Billing 3.0: a `CreditNote` for credit memos
+    /// and subsequent credits, an `Invoice` otherwise. The amounts are the same as in the ebInterface document.
+    ///
+    /// Tax categories get mapped to their EN 16931 code (`AA` becomes `S`), reductions and surcharges to
+    /// `AllowanceCharge` and the payment method to `PaymentMeans`. Fails with `Error::UnsupportedByFormat` for
+    /// below the line items and the tax categories `D`, `F`, `I` and `J`.
+    pub fn to_ubl(&self) -> Result<String, Error> {
+        self.check_format("UBL")?;
+
+        let mut s = String::with_capacity(2048 + self.details.items.len() * 1024);
+        let credit_note = self.document_type.is_credit_note();
+        self.write_ubl(&mut UblWriter::new(&mut s, &self.invoice_currency, credit_note).with_rounding(self.rounding))
+            .expect("writing to a String doesn't fail");
+        Ok(s)
+    }
+
+    /// Returns the invoice as UN/CEFACT Cross Industry Invoice (CII D16B), e.g. for ZUGFeRD/Factur-X or XRechnung,
+    /// in the given profile. The amounts are the same as in the ebInterface document.
+    ///
+    /// The mapping of tax categories, reductions and surcharges and payment methods follows `to_ubl`, with the same
+    /// `Error::UnsupportedByFormat` errors. The XRechnung profile requires an `OrderReference` of the invoice
+    /// recipient, whose `OrderID` becomes the buyer reference.
+    pub fn to_cii(&self, profile: CiiProfile) -> Result<String, Error> {
+        self.check_format("CII")?;
+        if profile == CiiProfile::XRechnung && self.invoice_recipient.order_reference().is_none() {
+            return Err(Error::Missing {
+                field: "OrderReference",
+            });
+        }
+
+        let mut s = String::with_capacity(2048 + self.details.items.len() * 1024);
+        self.write_cii(&mut CiiWriter::new(&mut s, &self.invoice_currency, profile).with_rounding(self.rounding))
+            .expect("writing to a String doesn't fail");
+        Ok(s)
+    }
+
+    fn check_format(&self, format: &'static str) -> Result<(), Error> {
+        for item in &self.details.items {
+            item.tax_item.check_format(format)?;
+        }
+        if let Some(rs) = &self.reduction_and_surcharge {
+            for (tax_item, _) in rs.taxable_amounts(self.rounding) {
+                tax_item.check_format(format)?;
+            }
+        }
+        if !self.below_the_line_items.is_empty() {
+            return Err(Error::UnsupportedByFormat {
+                format,
+                construct: "BelowTheLineItem".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ToUbl for Invoice<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        let totals = self.totals();
+        let (root, type_code, namespace) = if w.is_credit_note() {
+            ("CreditNote", "cbc:CreditNoteTypeCode", CREDIT_NOTE_NAMESPACE)
+        } else {
+            ("Invoice", "cbc:InvoiceTypeCode", INVOICE_NAMESPACE)
+        };
+
+        w.raw("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+
+        w.start(root)?;
+        w.attr("xmlns", namespace)?;
+        w.attr("xmlns:cac", CAC_NAMESPACE)?;
+        w.attr("xmlns:cbc", CBC_NAMESPACE)?;
+
+        w.text_element("cbc:CustomizationID", CUSTOMIZATION_ID)?;
+        w.text_element("cbc:ProfileID", PROFILE_ID)?;
+        w.text_element("cbc:ID", &self.invoice_number)?;
+        w.text_element("cbc:IssueDate", &self.invoice_date)?;
+
+        // A UBL 2.1 CreditNote has no DueDate.
+        if !w.is_credit_note()
+            && let Some(payment_conditions) = &self.payment_conditions
+        {
+            w.text_element("cbc:DueDate", payment_conditions.due_date())?;
+        }
+
+        w.text_element(type_code, self.document_type.type_code())?;
+
+        if let Some(document_title) = &self.document_title {
+            w.text_element("cbc:Note", document_title)?;
+        }
+
+        w.text_element("cbc:DocumentCurrencyCode", &self.invoice_currency)?;
+
+        if let Some(delivery) = &self.delivery {
+            delivery.write_ubl_period(w)?;
+        }
+
+        if let Some(order_reference) = self.invoice_recipient.order_reference() {
+            w.element(order_reference)?;
+        }
+
+        for related_document in &self.related_documents {
+            w.element(related_document)?;
+        }
+
+        w.element(&self.biller)?;
+        w.element(&self.invoice_recipient)?;
+
+        if let Some(delivery) = &self.delivery {
+            w.element(delivery)?;
+        }
+
+        if let Some(payment_method) = &self.payment_method {
+            w.element(payment_method)?;
+        }
+
+        if let Some(payment_conditions) = &self.payment_conditions {
+            w.element(payment_conditions)?;
+        }
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            w.element(reduction_and_surcharge)?;
+        }
+
+        w.start("cac:TaxTotal")?;
+        w.amount("cbc:TaxAmount", totals.tax_total_amount)?;
+        for tax_item in &totals.tax_items {
+            w.element(tax_item)?;
+        }
+        w.end("cac:TaxTotal")?;
+
+        w.start("cac:LegalMonetaryTotal")?;
+        w.amount("cbc:LineExtensionAmount", totals.line_total_amount)?;
+        w.amount("cbc:TaxExclusiveAmount", totals.tax_basis_total_amount())?;
+        w.amount("cbc:TaxInclusiveAmount", totals.total_gross_amount)?;
+        if self.reduction_and_surcharge.is_some() {
+            w.amount("cbc:AllowanceTotalAmount", totals.allowance_total_amount)?;
+            w.amount("cbc:ChargeTotalAmount", totals.charge_total_amount)?;
+        }
+        if let Some(prepaid_amount) = totals.prepaid_amount {
+            w.amount("cbc:PrepaidAmount", prepaid_amount)?;
+        }
+        if let Some(rounding_amount) = totals.rounding_amount {
+            w.amount("cbc:PayableRoundingAmount", rounding_amount)?;
+        }
+        w.amount("cbc:PayableAmount", totals.payable_amount)?;
+        w.end("cac:LegalMonetaryTotal")?;
+
+        w.element(&self.details)?;
+
+        w.end(root)
+    }
+}
+
+impl ToCii for Invoice<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        let totals = self.totals();
+
+        w.raw("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+
+        w.start("rsm:CrossIndustryInvoice")?;
+        w.attr("xmlns:rsm", RSM_NAMESPACE)?;
+        w.attr("xmlns:ram", RAM_NAMESPACE)?;
+        w.attr("xmlns:udt", UDT_NAMESPACE)?;
+        w.attr("xmlns:qdt", QDT_NAMESPACE)?;
+
+        let guideline_id = w.profile().guideline_id();
+        w.start("rsm:ExchangedDocumentContext")?;
+        w.start("ram:GuidelineSpecifiedDocumentContextParameter")?;
+        w.text_element("ram:ID", guideline_id)?;
+        w.end("ram:GuidelineSpecifiedDocumentContextParameter")?;
+        w.end("rsm:ExchangedDocumentContext")?;
+
+        w.start("rsm:ExchangedDocument")?;
+        w.text_element("ram:ID", &self.invoice_number)?;
+        w.text_element("ram:TypeCode", self.document_type.type_code())?;
+        w.date("ram:IssueDateTime", &self.invoice_date)?;
+        if let Some(document_title) = &self.document_title {
+            w.start("ram:IncludedNote")?;
+            w.text_element("ram:Content", document_title)?;
+            w.end("ram:IncludedNote")?;
+        }
+        w.end("rsm:ExchangedDocument")?;
+
+        w.start("rsm:SupplyChainTradeTransaction")?;
+        w.element(&self.details)?;
+
+        let order_reference = self.invoice_recipient.order_reference();
+        w.start("ram:ApplicableHeaderTradeAgreement")?;
+        if w.profile() == CiiProfile::XRechnung
+            && let Some(order_reference) = order_reference
+        {
+            w.text_element("ram:BuyerReference", order_reference.order_id())?;
+        }
+        w.element(&self.biller)?;
+        w.element(&self.invoice_recipient)?;
+        if let Some(order_reference) = order_reference {
+            w.element(order_reference)?;
+        }
+        w.end("ram:ApplicableHeaderTradeAgreement")?;
+
+        w.start("ram:ApplicableHeaderTradeDelivery")?;
+        if let Some(delivery) = &self.delivery {
+            delivery.write_cii(w)?;
+        }
+        w.end("ram:ApplicableHeaderTradeDelivery")?;
+
+        let payment_method = self.payment_method.as_ref();
+        let mandate_reference = payment_method.and_then(PaymentMethod::mandate_reference);
+
+        w.start("ram:ApplicableHeaderTradeSettlement")?;
+        if let Some(creditor_id) = payment_method.and_then(PaymentMethod::creditor_id) {
+            w.text_element("ram:CreditorReferenceID", creditor_id)?;
+        }
+        if let Some(payment_reference) = payment_method.and_then(PaymentMethod::payment_reference) {
+            w.text_element("ram:PaymentReference", payment_reference)?;
+        }
+        w.text_element("ram:InvoiceCurrencyCode", &self.invoice_currency)?;
+
+        if let Some(payment_method) = payment_method {
+            w.element(payment_method)?;
+        }
+
+        for tax_item in &totals.tax_items {
+            w.element(tax_item)?;
+        }
+
+        if let Some(delivery) = &self.delivery {
+            delivery.write_cii_period(w, false)?;
+        }
+
+        if let Some(reduction_and_surcharge) = &self.reduction_and_surcharge {
+            w.element(reduction_and_surcharge)?;
+        }
+
+        if self.payment_conditions.is_some() || mandate_reference.is_some() {
+            w.start("ram:SpecifiedTradePaymentTerms")?;
+            if let Some(payment_conditions) = &self.payment_conditions {
+                if let Some(note) = payment_conditions.note(w.currency()) {
+                    w.text_element("ram:Description", note)?;
+                }
+                w.date("ram:DueDateDateTime", payment_conditions.due_date())?;
+            }
+            if let Some(mandate_reference) = mandate_reference {
+                w.text_element("ram:DirectDebitMandateID", mandate_reference)?;
+            }
+            w.end("ram:SpecifiedTradePaymentTerms")?;
+        }
+
+        let currency = w.currency();
+        w.start("ram:SpecifiedTradeSettlementHeaderMonetarySummation")?;
+        w.amount("ram:LineTotalAmount", totals.line_total_amount)?;
+        if self.reduction_and_surcharge.is_some() {
+            w.amount("ram:ChargeTotalAmount", totals.charge_total_amount)?;
+            w.amount("ram:AllowanceTotalAmount", totals.allowance_total_amount)?;
+        }
+        w.amount("ram:TaxBasisTotalAmount", totals.tax_basis_total_amount())?;
+        w.start("ram:TaxTotalAmount")?;
+        w.attr("currencyID", currency)?;
+        w.text(totals.tax_total_amount())?;
+        w.end("ram:TaxTotalAmount")?;
+        if let Some(rounding_amount) = totals.rounding_amount {
+            w.amount("ram:RoundingAmount", rounding_amount)?;
+        }
+        w.amount("ram:GrandTotalAmount", totals.total_gross_amount)?;
+        if let Some(prepaid_amount) = totals.prepaid_amount {
+            w.amount("ram:TotalPrepaidAmount", prepaid_amount)?;
+        }
+        w.amount("ram:DuePayableAmount", totals.payable_amount)?;
+        w.end("ram:SpecifiedTradeSettlementHeaderMonetarySummation")?;
+
+        for related_document in &self.related_documents {
+            w.element(related_document)?;
+        }
+
+        w.end("ram:ApplicableHeaderTradeSettlement")?;
+        w.end("rsm:SupplyChainTradeTransaction")?;
+
+        w.end("rsm:CrossIndustryInvoice")
     }
 }
 
@@ -150,7 +902,28 @@ impl<'a> Invoice<'a> {
 mod tests {
     use super::*;
 
-    use crate::payment_method::PaymentMethodPaymentCard;
+    use rust_decimal::RoundingStrategy;
+
+    use crate::{
+        address::Address,
+        contact::Contact,
+        creditor_id::CreditorId,
+        iban::Iban,
+        order_reference::OrderReference,
+        payment_conditions::PaymentConditionsDiscount,
+        payment_method::{
+            PaymentMethodPaymentCard, PaymentMethodSEPADirectDebit, PaymentMethodUniversalBankTransaction,
+            PaymentMethodUniversalBankTransactionBeneficiaryAccount,
+        },
+        reduction_and_surcharge::{ReductionAndSurchargeValue, ReductionListLineItem},
+        rounding::TaxRounding,
+        tax::TaxItem,
+        vat_id::VatId,
+    };
+
+    fn assert_round_trip(invoice: &Invoice) {
+        assert_eq!(&Invoice::from_xml(&invoice.to_xml()).unwrap(), invoice);
+    }
 
     #[test]
     fn readme_example() {
@@ -159,8 +932,8 @@ mod tests {
             "EUR",
             "993433000298",
             "2020-01-01",
-            Biller::new("ATU51507409"),
-            InvoiceRecipient::new("ATU18708634"),
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
         )
         .with_item(
             DetailsItem::new(
@@ -180,13 +953,13 @@ mod tests {
                     .with_card_holder_name("Name"),
             )
             .with_comment("Comment"),
-        )
-        .to_xml();
+        );
 
         assert_eq!(
-            invoice,
-            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\" DocumentTitle=\"An invoice\" Language=\"de\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708634</VATIdentificationNumber></InvoiceRecipient><Details><ItemList><ListLineItem><Description>Schraubenzieher</Description><Quantity Unit=\"STK\">100.0000</Quantity><UnitPrice>10.2000</UnitPrice><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem><LineItemAmount>1020.00</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>1224.00</TotalGrossAmount><PayableAmount>1224.00</PayableAmount><PaymentMethod><Comment>Comment</Comment><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod></Invoice>",
-        )
+            invoice.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\" DocumentTitle=\"An invoice\" Language=\"de\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient><Details><ItemList><ListLineItem><Description>Schraubenzieher</Description><Quantity Unit=\"STK\">100.0000</Quantity><UnitPrice>10.2000</UnitPrice><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem><LineItemAmount>1020.00</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>1224.00</TotalGrossAmount><PayableAmount>1224.00</PayableAmount><PaymentMethod><Comment>Comment</Comment><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod></Invoice>",
+        );
+        assert_round_trip(&invoice);
     }
 
     #[test]
@@ -196,8 +969,8 @@ mod tests {
             "EUR",
             "0000",
             "2024-06-02",
-            Biller::new("ATU00000000"),
-            InvoiceRecipient::new("ATU000000000"),
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
         )
         .with_items(vec![
             DetailsItem::new(
@@ -235,8 +1008,1157 @@ mod tests {
         let tax_items = invoice.invoice_tax_items();
 
         assert_eq!(
-            tax_items.first().map_or(Decimal::from(0), |i| i.1),
+            tax_items.first().map_or(Decimal::from(0), |i| i.taxable_amount()),
             Decimal::new(285227, 2)
         )
     }
+
+    #[test]
+    fn generates_credit_memo() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000299",
+            "2020-01-02",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_document_type(DocumentType::CreditMemo)
+        .with_related_document(
+            RelatedDocument::new("993433000298")
+                .with_invoice_date("2020-01-01")
+                .with_document_type(DocumentType::Invoice),
+        )
+        .with_delivery(Delivery::date("2020-01-01"))
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+
+        assert_eq!(invoice.validate(), Ok(()));
+        assert_eq!(
+            invoice.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"CreditMemo\" InvoiceCurrency=\"EUR\"><InvoiceNumber>993433000299</InvoiceNumber><InvoiceDate>2020-01-02</InvoiceDate><RelatedDocument><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><DocumentType>Invoice</DocumentType></RelatedDocument><Delivery><Date>2020-01-01</Date></Delivery><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient><Details><ItemList><ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>10.0000</UnitPrice><TaxItem><TaxableAmount>10.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>2.00</TaxAmount></TaxItem><LineItemAmount>10.00</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>10.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>2.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>12.00</TotalGrossAmount><PayableAmount>12.00</PayableAmount></Invoice>",
+        );
+        assert_round_trip(&invoice);
+    }
+
+    #[test]
+    fn credit_memo_requires_related_document() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000299",
+            "2020-01-02",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_document_type(DocumentType::CreditMemo)
+        .with_delivery(Delivery::date("2020-01-01"))
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+
+        assert_eq!(
+            invoice.validate(),
+            Err(vec![ValidationError {
+                path: "RelatedDocument".to_string(),
+                error: Error::MissingRelatedDocument {
+                    document_type: DocumentType::CreditMemo
+                },
+            }])
+        );
+        assert_eq!(invoice.with_document_type(DocumentType::SelfBilling).validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_base_quantities_not_greater_than_zero() {
+        let item = || {
+            DetailsItem::new(
+                Decimal::from(1),
+                "STK",
+                Decimal::from(10),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+        };
+        for base_quantity in [Decimal::ZERO, Decimal::NEGATIVE_ONE] {
+            assert_eq!(
+                item().with_base_quantity(base_quantity),
+                Err(Error::NotPositive {
+                    field: "BaseQuantity",
+                    value: base_quantity,
+                })
+            );
+        }
+
+        let xml = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_delivery(Delivery::date("2020-01-01"))
+        .with_item(item().with_base_quantity(Decimal::ONE).unwrap())
+        .to_xml()
+        .replace("<UnitPrice BaseQuantity=\"1\">", "<UnitPrice BaseQuantity=\"0\">");
+        let invoice = Invoice::from_xml(&xml).unwrap();
+
+        // Written and calculated without panicking, but reported by the validation.
+        assert_eq!(invoice.totals().total_gross_amount(), Decimal::ZERO);
+        assert!(invoice.to_xml().contains("<LineItemAmount>0.00</LineItemAmount>"));
+        assert_eq!(
+            invoice.try_to_xml(),
+            Err(vec![ValidationError {
+                path: "Details/ItemList/ListLineItem[1]/UnitPrice/@BaseQuantity".to_string(),
+                error: Error::NotPositive {
+                    field: "BaseQuantity",
+                    value: Decimal::ZERO,
+                },
+            }])
+        );
+    }
+
+    #[test]
+    fn validates_whole_document() {
+        let invoice = Invoice::new(
+            "test",
+            "EURO",
+            "993433000298",
+            "2020-01-32",
+            Biller::new(VatId::unchecked("".into())),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap())
+                .with_address(Address::new("Mustermann GmbH", "Graz", "8010", "Österreich").with_country_code("XX")),
+        )
+        .with_delivery(Delivery::date("2020-01-01"))
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1),
+                "",
+                Decimal::from(10),
+                TaxItem::new(Decimal::from(120), TaxCategory::S),
+            )
+            .with_reduction(ReductionListLineItem::new(
+                Decimal::from(10),
+                ReductionAndSurchargeValue::Percentage(Decimal::from(-5)),
+            )),
+        )
+        .with_payment_conditions(PaymentConditions::new("31.01.2020"));
+
+        let errors = invoice.validate().unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            vec![
+                "@InvoiceCurrency",
+                "InvoiceDate",
+                "Biller/VATIdentificationNumber",
+                "InvoiceRecipient/Address/Country/@CountryCode",
+                "Details/ItemList/ListLineItem[2]/Quantity/@Unit",
+                "Details/ItemList/ListLineItem[2]/ReductionAndSurchargeListLineItemDetails/ReductionListLineItem[1]/Percentage",
+                "Details/ItemList/ListLineItem[2]/TaxItem/TaxPercent",
+                "PaymentConditions/DueDate",
+            ]
+        );
+        assert_eq!(
+            errors[0].error,
+            Error::UnknownValue {
+                field: "InvoiceCurrency",
+                value: "EURO".to_string()
+            }
+        );
+        assert_eq!(invoice.try_to_xml(), Err(errors));
+        assert!(
+            Invoice::new(
+                "test",
+                "EUR",
+                "993433000298",
+                "2020-01-01",
+                Biller::new(VatId::new("ATU51507409").unwrap()),
+                InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+            )
+            .with_delivery(Delivery::date("2020-01-01"))
+            .with_item(DetailsItem::new(
+                Decimal::from(1),
+                "STK",
+                Decimal::from(10),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            ))
+            .try_to_xml()
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn includes_root_reductions_and_surcharges_in_totals() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(100),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(5),
+            TaxItem::new(Decimal::from(10), TaxCategory::AA),
+        ))
+        .with_reduction(
+            Reduction::new(
+                Decimal::from(1000),
+                ReductionAndSurchargeValue::Percentage(Decimal::from(3)),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_comment("early-order discount"),
+        )
+        .with_surcharge(Surcharge::new(
+            Decimal::from(1005),
+            ReductionAndSurchargeValue::Amount(Decimal::from(15)),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+
+        assert_eq!(
+            invoice.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient><Details><ItemList><ListLineItem><Quantity Unit=\"STK\">100.0000</Quantity><UnitPrice>10.0000</UnitPrice><TaxItem><TaxableAmount>1000.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>200.00</TaxAmount></TaxItem><LineItemAmount>1000.00</LineItemAmount></ListLineItem><ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>5.0000</UnitPrice><TaxItem><TaxableAmount>5.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.50</TaxAmount></TaxItem><LineItemAmount>5.00</LineItemAmount></ListLineItem></ItemList></Details><ReductionAndSurchargeDetails><Reduction><BaseAmount>1000.00</BaseAmount><Percentage>3.00</Percentage><TaxItem><TaxableAmount>30.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>6.00</TaxAmount></TaxItem><Comment>early-order discount</Comment></Reduction><Surcharge><BaseAmount>1005.00</BaseAmount><Amount>15.00</Amount><TaxItem><TaxableAmount>15.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>3.00</TaxAmount></TaxItem></Surcharge></ReductionAndSurchargeDetails><Tax><TaxItem><TaxableAmount>5.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.50</TaxAmount></TaxItem><TaxItem><TaxableAmount>985.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>197.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>1187.50</TotalGrossAmount><PayableAmount>1187.50</PayableAmount></Invoice>",
+        );
+        assert_round_trip(&invoice);
+        assert_eq!(invoice.check_rules(&Rules::new()), vec![]);
+        assert_eq!(
+            invoice
+                .check_rules(&Rules::new().with_federal_recipient(true))
+                .iter()
+                .map(|v| v.rule)
+                .collect::<Vec<_>>(),
+            vec!["federal-order-reference"]
+        );
+    }
+
+    #[test]
+    fn calculates_payable_amount() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_document_type(DocumentType::FinalSettlement)
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::new(1023, 2),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_prepaid_amount(Decimal::from(5))
+        .with_below_the_line_item(BelowTheLineItem::new("Deposit", Decimal::new(40, 2)))
+        .with_cash_rounding(Decimal::new(5, 2))
+        .unwrap();
+
+        assert_eq!(
+            invoice.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"FinalSettlement\" InvoiceCurrency=\"EUR\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient><Details><ItemList><ListLineItem><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice>10.2300</UnitPrice><TaxItem><TaxableAmount>10.23</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>2.05</TaxAmount></TaxItem><LineItemAmount>10.23</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>10.23</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>2.05</TaxAmount></TaxItem></Tax><TotalGrossAmount>12.28</TotalGrossAmount><PrepaidAmount>5.00</PrepaidAmount><RoundingAmount>0.02</RoundingAmount><BelowTheLineItem><Description>Deposit</Description><LineItemAmount>0.40</LineItemAmount></BelowTheLineItem><PayableAmount>7.70</PayableAmount></Invoice>",
+        );
+
+        let invoice = invoice.with_rounding_amount(Decimal::new(-3, 2));
+        assert!(invoice.to_xml().contains("<RoundingAmount>-0.03</RoundingAmount>"));
+        assert!(invoice.to_xml().contains("<PayableAmount>7.65</PayableAmount>"));
+        assert_round_trip(&invoice);
+
+        for increment in [Decimal::ZERO, Decimal::new(-5, 2)] {
+            assert_eq!(
+                Invoice::default().with_cash_rounding(increment).err(),
+                Some(Error::NotPositive {
+                    field: "RoundingAmount",
+                    value: increment,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn writes_older_versions() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1),
+                "STK",
+                Decimal::from(100),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_reduction(ReductionListLineItem::new(
+                Decimal::from(100),
+                ReductionAndSurchargeValue::Amount(Decimal::from(10)),
+            )),
+        )
+        .with_reduction(Reduction::new(
+            Decimal::from(90),
+            ReductionAndSurchargeValue::Amount(Decimal::from(5)),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V6p0).unwrap(),
+            invoice.to_xml().replace("/6p1/", "/6p0/")
+        );
+        assert_eq!(invoice.to_xml_version(EbInterfaceVersion::V6p1).unwrap(), invoice.to_xml());
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V5p0).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><eb:Invoice xmlns:eb=\"http://www.ebinterface.at/schema/5p0/\" eb:GeneratingSystem=\"test\" eb:DocumentType=\"Invoice\" eb:InvoiceCurrency=\"EUR\"><eb:InvoiceNumber>993433000298</eb:InvoiceNumber><eb:InvoiceDate>2020-01-01</eb:InvoiceDate><eb:Biller><eb:VATIdentificationNumber>ATU51507409</eb:VATIdentificationNumber></eb:Biller><eb:InvoiceRecipient><eb:VATIdentificationNumber>ATU18708637</eb:VATIdentificationNumber></eb:InvoiceRecipient><eb:Details><eb:ItemList><eb:ListLineItem><eb:Quantity eb:Unit=\"STK\">1.0000</eb:Quantity><eb:UnitPrice>100.0000</eb:UnitPrice><eb:TaxRate>20</eb:TaxRate><eb:ReductionAndSurchargeListLineItemDetails><eb:ReductionListLineItem><eb:BaseAmount>100.00</eb:BaseAmount><eb:Amount>10.00</eb:Amount></eb:ReductionListLineItem></eb:ReductionAndSurchargeListLineItemDetails><eb:LineItemAmount>90.00</eb:LineItemAmount></eb:ListLineItem></eb:ItemList></eb:Details><eb:ReductionAndSurchargeDetails><eb:Reduction><eb:BaseAmount>90.00</eb:BaseAmount><eb:Amount>5.00</eb:Amount><eb:TaxRate>20</eb:TaxRate></eb:Reduction></eb:ReductionAndSurchargeDetails><eb:Tax><eb:VAT><eb:VATItem><eb:TaxedAmount>85.00</eb:TaxedAmount><eb:TaxRate>20</eb:TaxRate><eb:Amount>17.00</eb:Amount></eb:VATItem></eb:VAT></eb:Tax><eb:TotalGrossAmount>102.00</eb:TotalGrossAmount><eb:PayableAmount>102.00</eb:PayableAmount></eb:Invoice>"
+        );
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V4p3).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><eb:Invoice xmlns:eb=\"http://www.ebinterface.at/schema/4p3/\" eb:GeneratingSystem=\"test\" eb:DocumentType=\"Invoice\" eb:InvoiceCurrency=\"EUR\"><eb:InvoiceNumber>993433000298</eb:InvoiceNumber><eb:InvoiceDate>2020-01-01</eb:InvoiceDate><eb:Biller><eb:VATIdentificationNumber>ATU51507409</eb:VATIdentificationNumber></eb:Biller><eb:InvoiceRecipient><eb:VATIdentificationNumber>ATU18708637</eb:VATIdentificationNumber></eb:InvoiceRecipient><eb:Details><eb:ItemList><eb:ListLineItem><eb:Quantity eb:Unit=\"STK\">1.0000</eb:Quantity><eb:UnitPrice>100.0000</eb:UnitPrice><eb:VATRate>20</eb:VATRate><eb:ReductionAndSurchargeListLineItemDetails><eb:ReductionListLineItem><eb:BaseAmount>100.00</eb:BaseAmount><eb:Amount>10.00</eb:Amount></eb:ReductionListLineItem></eb:ReductionAndSurchargeListLineItemDetails><eb:LineItemAmount>90.00</eb:LineItemAmount></eb:ListLineItem></eb:ItemList></eb:Details><eb:ReductionAndSurchargeDetails><eb:Reduction><eb:BaseAmount>90.00</eb:BaseAmount><eb:Amount>5.00</eb:Amount><eb:VATRate>20</eb:VATRate></eb:Reduction></eb:ReductionAndSurchargeDetails><eb:Tax><eb:VAT><eb:VATItem><eb:TaxedAmount>85.00</eb:TaxedAmount><eb:VATRate>20</eb:VATRate><eb:Amount>17.00</eb:Amount></eb:VATItem></eb:VAT></eb:Tax><eb:TotalGrossAmount>102.00</eb:TotalGrossAmount><eb:PayableAmount>102.00</eb:PayableAmount></eb:Invoice>"
+        );
+    }
+
+    #[test]
+    fn rejects_constructs_unsupported_by_version() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()).with_contact(Contact::new("Max Mustermann")),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(100),
+            TaxItem::new(Decimal::ZERO, TaxCategory::AE),
+        ))
+        .with_payment_method(PaymentMethod::other_payment());
+
+        assert!(invoice.to_xml_version(EbInterfaceVersion::V6p0).is_ok());
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V5p0).unwrap_err().to_string(),
+            "ebInterface 5.0 doesn't support Contact!"
+        );
+
+        let invoice = Invoice {
+            biller: Biller::new(VatId::new("ATU51507409").unwrap()),
+            ..invoice
+        };
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V5p0),
+            Err(Error::UnsupportedByVersion {
+                version: EbInterfaceVersion::V5p0,
+                construct: "TaxCategoryCode AE".to_string()
+            })
+        );
+
+        let invoice = Invoice {
+            details: Details {
+                items: vec![DetailsItem::new(
+                    Decimal::from(1),
+                    "STK",
+                    Decimal::from(100),
+                    TaxItem::new(Decimal::from(20), TaxCategory::S),
+                )],
+            },
+            ..invoice
+        };
+        assert!(invoice.to_xml_version(EbInterfaceVersion::V5p0).is_ok());
+        assert_eq!(
+            invoice.to_xml_version(EbInterfaceVersion::V4p3).unwrap_err().to_string(),
+            "ebInterface 4.3 doesn't support OtherPayment!"
+        );
+    }
+
+    fn ubl_invoice() -> Invoice<'static> {
+        Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()).with_address(
+                Address::new("Schrauben Mustermann", "Wien", "1020", "Österreich")
+                    .with_street("Lassallenstraße 5")
+                    .with_country_code("AT")
+                    .with_email("schrauben@mustermann.at"),
+            ),
+            InvoiceRecipient::new(VatId::none())
+                .with_order_reference(OrderReference::new("4711"))
+                .with_address(Address::new("Max Mustermann", "Graz", "8010", "Österreich").with_country_code("AT"))
+                .with_contact(Contact::new("Max Mustermann").with_phone("+43 316 123456")),
+        )
+        .with_delivery(Delivery::period("2019-12-01", "2019-12-31"))
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(100),
+                "C62",
+                Decimal::new(1020, 2),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_position_number(1)
+            .with_description("Schraubenzieher")
+            .with_base_quantity(Decimal::from(1))
+            .unwrap()
+            .with_reduction(
+                ReductionListLineItem::new(
+                    Decimal::from(1020),
+                    ReductionAndSurchargeValue::Percentage(Decimal::from(10)),
+                )
+                .with_comment("Mengenrabatt"),
+            ),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "C62",
+            Decimal::from(5),
+            TaxItem::new(Decimal::from(10), TaxCategory::AA),
+        ))
+        .with_surcharge(Surcharge::new(
+            Decimal::from(923),
+            ReductionAndSurchargeValue::Amount(Decimal::from(15)),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_payment_method(PaymentMethod::universal_bank_transaction(
+            PaymentMethodUniversalBankTransaction::new()
+                .with_beneficiary_account(
+                    PaymentMethodUniversalBankTransactionBeneficiaryAccount::new()
+                        .with_iban(Iban::new("AT611904300234573201").unwrap())
+                        .with_bank_account_owner("Schrauben Mustermann")
+                        .unwrap(),
+                )
+                .with_payment_reference("993433000298")
+                .unwrap(),
+        ))
+        .with_payment_conditions(
+            PaymentConditions::new("2020-01-31")
+                .with_discount(PaymentConditionsDiscount::new("2020-01-15").with_percentage(Decimal::from(2))),
+        )
+    }
+
+    #[test]
+    fn writes_ubl() {
+        let invoice = ubl_invoice();
+
+        assert_eq!(
+            invoice.to_ubl().unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:Invoice-2\" xmlns:cac=\"urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2\" xmlns:cbc=\"urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2\"><cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID><cbc:ProfileID>urn:fdc:peppol.eu:2017:poacc:billing:01:1.0</cbc:ProfileID><cbc:ID>993433000298</cbc:ID><cbc:IssueDate>2020-01-01</cbc:IssueDate><cbc:DueDate>2020-01-31</cbc:DueDate><cbc:InvoiceTypeCode>380</cbc:InvoiceTypeCode><cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode><cac:InvoicePeriod><cbc:StartDate>2019-12-01</cbc:StartDate><cbc:EndDate>2019-12-31</cbc:EndDate></cac:InvoicePeriod><cac:OrderReference><cbc:ID>4711</cbc:ID></cac:OrderReference><cac:AccountingSupplierParty><cac:Party><cac:PartyName><cbc:Name>Schrauben Mustermann</cbc:Name></cac:PartyName><cac:PostalAddress><cbc:StreetName>Lassallenstraße 5</cbc:StreetName><cbc:CityName>Wien</cbc:CityName><cbc:PostalZone>1020</cbc:PostalZone><cac:Country><cbc:IdentificationCode>AT</cbc:IdentificationCode></cac:Country></cac:PostalAddress><cac:PartyTaxScheme><cbc:CompanyID>ATU51507409</cbc:CompanyID><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:PartyTaxScheme><cac:PartyLegalEntity><cbc:RegistrationName>Schrauben Mustermann</cbc:RegistrationName></cac:PartyLegalEntity><cac:Contact><cbc:ElectronicMail>schrauben@mustermann.at</cbc:ElectronicMail></cac:Contact></cac:Party></cac:AccountingSupplierParty><cac:AccountingCustomerParty><cac:Party><cac:PartyName><cbc:Name>Max Mustermann</cbc:Name></cac:PartyName><cac:PostalAddress><cbc:CityName>Graz</cbc:CityName><cbc:PostalZone>8010</cbc:PostalZone><cac:Country><cbc:IdentificationCode>AT</cbc:IdentificationCode></cac:Country></cac:PostalAddress><cac:PartyLegalEntity><cbc:RegistrationName>Max Mustermann</cbc:RegistrationName></cac:PartyLegalEntity><cac:Contact><cbc:Name>Max Mustermann</cbc:Name><cbc:Telephone>+43 316 123456</cbc:Telephone></cac:Contact></cac:Party></cac:AccountingCustomerParty><cac:PaymentMeans><cbc:PaymentMeansCode>58</cbc:PaymentMeansCode><cbc:PaymentID>993433000298</cbc:PaymentID><cac:PayeeFinancialAccount><cbc:ID>AT611904300234573201</cbc:ID><cbc:Name>Schrauben Mustermann</cbc:Name></cac:PayeeFinancialAccount></cac:PaymentMeans><cac:PaymentTerms><cbc:Note>2.00 % discount if paid by 2020-01-15</cbc:Note></cac:PaymentTerms><cac:AllowanceCharge><cbc:ChargeIndicator>true</cbc:ChargeIndicator><cbc:AllowanceChargeReasonCode>ZZZ</cbc:AllowanceChargeReasonCode><cbc:Amount currencyID=\"EUR\">15.00</cbc:Amount><cbc:BaseAmount currencyID=\"EUR\">923.00</cbc:BaseAmount><cac:TaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>20</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:TaxCategory></cac:AllowanceCharge><cac:TaxTotal><cbc:TaxAmount currencyID=\"EUR\">187.10</cbc:TaxAmount><cac:TaxSubtotal><cbc:TaxableAmount currencyID=\"EUR\">5.00</cbc:TaxableAmount><cbc:TaxAmount currencyID=\"EUR\">0.50</cbc:TaxAmount><cac:TaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>10</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:TaxCategory></cac:TaxSubtotal><cac:TaxSubtotal><cbc:TaxableAmount currencyID=\"EUR\">933.00</cbc:TaxableAmount><cbc:TaxAmount currencyID=\"EUR\">186.60</cbc:TaxAmount><cac:TaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>20</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:TaxCategory></cac:TaxSubtotal></cac:TaxTotal><cac:LegalMonetaryTotal><cbc:LineExtensionAmount currencyID=\"EUR\">923.00</cbc:LineExtensionAmount><cbc:TaxExclusiveAmount currencyID=\"EUR\">938.00</cbc:TaxExclusiveAmount><cbc:TaxInclusiveAmount currencyID=\"EUR\">1125.10</cbc:TaxInclusiveAmount><cbc:AllowanceTotalAmount currencyID=\"EUR\">0.00</cbc:AllowanceTotalAmount><cbc:ChargeTotalAmount currencyID=\"EUR\">15.00</cbc:ChargeTotalAmount><cbc:PayableAmount currencyID=\"EUR\">1125.10</cbc:PayableAmount></cac:LegalMonetaryTotal><cac:InvoiceLine><cbc:ID>1</cbc:ID><cbc:InvoicedQuantity unitCode=\"C62\">100.0000</cbc:InvoicedQuantity><cbc:LineExtensionAmount currencyID=\"EUR\">918.00</cbc:LineExtensionAmount><cac:AllowanceCharge><cbc:ChargeIndicator>false</cbc:ChargeIndicator><cbc:AllowanceChargeReason>Mengenrabatt</cbc:AllowanceChargeReason><cbc:MultiplierFactorNumeric>10.00</cbc:MultiplierFactorNumeric><cbc:Amount currencyID=\"EUR\">102.00</cbc:Amount><cbc:BaseAmount currencyID=\"EUR\">1020.00</cbc:BaseAmount></cac:AllowanceCharge><cac:Item><cbc:Name>Schraubenzieher</cbc:Name><cac:ClassifiedTaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>20</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:ClassifiedTaxCategory></cac:Item><cac:Price><cbc:PriceAmount currencyID=\"EUR\">10.2000</cbc:PriceAmount><cbc:BaseQuantity unitCode=\"C62\">1</cbc:BaseQuantity></cac:Price></cac:InvoiceLine><cac:InvoiceLine><cbc:ID>2</cbc:ID><cbc:InvoicedQuantity unitCode=\"C62\">1.0000</cbc:InvoicedQuantity><cbc:LineExtensionAmount currencyID=\"EUR\">5.00</cbc:LineExtensionAmount><cac:Item><cac:ClassifiedTaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>10</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:ClassifiedTaxCategory></cac:Item><cac:Price><cbc:PriceAmount currencyID=\"EUR\">5.0000</cbc:PriceAmount></cac:Price></cac:InvoiceLine></Invoice>"
+        );
+    }
+
+    #[test]
+    fn writes_ubl_credit_note() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000299",
+            "2020-01-02",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_document_type(DocumentType::CreditMemo)
+        .with_related_document(RelatedDocument::new("993433000298").with_invoice_date("2020-01-01"))
+        .with_delivery(Delivery::date("2020-01-01"))
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "C62",
+            Decimal::from(10),
+            TaxItem::new(Decimal::ZERO, TaxCategory::AE),
+        ))
+        .with_payment_method(PaymentMethod::other_payment())
+        .with_payment_conditions(PaymentConditions::new("2020-01-31"));
+
+        assert_eq!(
+            invoice.to_ubl().unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><CreditNote xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2\" xmlns:cac=\"urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2\" xmlns:cbc=\"urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2\"><cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID><cbc:ProfileID>urn:fdc:peppol.eu:2017:poacc:billing:01:1.0</cbc:ProfileID><cbc:ID>993433000299</cbc:ID><cbc:IssueDate>2020-01-02</cbc:IssueDate><cbc:CreditNoteTypeCode>381</cbc:CreditNoteTypeCode><cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode><cac:BillingReference><cac:InvoiceDocumentReference><cbc:ID>993433000298</cbc:ID><cbc:IssueDate>2020-01-01</cbc:IssueDate></cac:InvoiceDocumentReference></cac:BillingReference><cac:AccountingSupplierParty><cac:Party><cac:PartyTaxScheme><cbc:CompanyID>ATU51507409</cbc:CompanyID><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:PartyTaxScheme><cac:PartyLegalEntity><cbc:RegistrationName>ATU51507409</cbc:RegistrationName></cac:PartyLegalEntity></cac:Party></cac:AccountingSupplierParty><cac:AccountingCustomerParty><cac:Party><cac:PartyTaxScheme><cbc:CompanyID>ATU18708637</cbc:CompanyID><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:PartyTaxScheme><cac:PartyLegalEntity><cbc:RegistrationName>ATU18708637</cbc:RegistrationName></cac:PartyLegalEntity></cac:Party></cac:AccountingCustomerParty><cac:Delivery><cbc:ActualDeliveryDate>2020-01-01</cbc:ActualDeliveryDate></cac:Delivery><cac:PaymentMeans><cbc:PaymentMeansCode>ZZZ</cbc:PaymentMeansCode></cac:PaymentMeans><cac:TaxTotal><cbc:TaxAmount currencyID=\"EUR\">0.00</cbc:TaxAmount><cac:TaxSubtotal><cbc:TaxableAmount currencyID=\"EUR\">10.00</cbc:TaxableAmount><cbc:TaxAmount currencyID=\"EUR\">0.00</cbc:TaxAmount><cac:TaxCategory><cbc:ID>AE</cbc:ID><cbc:Percent>0</cbc:Percent><cbc:TaxExemptionReasonCode>VATEX-EU-AE</cbc:TaxExemptionReasonCode><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:TaxCategory></cac:TaxSubtotal></cac:TaxTotal><cac:LegalMonetaryTotal><cbc:LineExtensionAmount currencyID=\"EUR\">10.00</cbc:LineExtensionAmount><cbc:TaxExclusiveAmount currencyID=\"EUR\">10.00</cbc:TaxExclusiveAmount><cbc:TaxInclusiveAmount currencyID=\"EUR\">10.00</cbc:TaxInclusiveAmount><cbc:PayableAmount currencyID=\"EUR\">10.00</cbc:PayableAmount></cac:LegalMonetaryTotal><cac:CreditNoteLine><cbc:ID>1</cbc:ID><cbc:CreditedQuantity unitCode=\"C62\">1.0000</cbc:CreditedQuantity><cbc:LineExtensionAmount currencyID=\"EUR\">10.00</cbc:LineExtensionAmount><cac:Item><cac:ClassifiedTaxCategory><cbc:ID>AE</cbc:ID><cbc:Percent>0</cbc:Percent><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:ClassifiedTaxCategory></cac:Item><cac:Price><cbc:PriceAmount currencyID=\"EUR\">10.0000</cbc:PriceAmount></cac:Price></cac:CreditNoteLine></CreditNote>"
+        );
+    }
+
+    #[test]
+    fn rejects_constructs_unsupported_by_ubl() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "C62",
+            Decimal::from(100),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_reduction(Reduction::new(
+            Decimal::from(100),
+            ReductionAndSurchargeValue::Amount(Decimal::from(5)),
+            TaxItem::new(Decimal::from(20), TaxCategory::D),
+        ));
+
+        assert_eq!(
+            invoice.to_ubl(),
+            Err(Error::UnsupportedByFormat {
+                format: "UBL",
+                construct: "TaxCategoryCode D".to_string()
+            })
+        );
+
+        let invoice = Invoice {
+            reduction_and_surcharge: None,
+            ..invoice
+        }
+        .with_below_the_line_item(BelowTheLineItem::new("Deposit", Decimal::from(10)));
+        assert_eq!(invoice.to_ubl().unwrap_err().to_string(), "UBL doesn't support BelowTheLineItem!");
+    }
+
+    fn cii_invoice() -> Invoice<'static> {
+        Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()).with_address(
+                Address::new("Schrauben Mustermann", "Wien", "1020", "Österreich")
+                    .with_street("Lassallenstraße 5")
+                    .with_country_code("AT")
+                    .with_email("schrauben@mustermann.at"),
+            ),
+            InvoiceRecipient::new(VatId::new("DE123456788").unwrap())
+                .with_order_reference(OrderReference::new("04011000-12345-34"))
+                .with_address(
+                    Address::new("Mustermann GmbH", "Berlin", "10115", "Deutschland")
+                        .with_street("Hauptstraße 10")
+                        .with_country_code("DE"),
+                )
+                .with_contact(Contact::new("Max Mustermann").with_email("max@mustermann.de")),
+        )
+        .with_document_title("An invoice")
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(100),
+                "C62",
+                Decimal::new(1020, 2),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_position_number(1)
+            .with_description("Schraubenzieher")
+            .with_description("Kreuzschlitz")
+            .with_base_quantity(Decimal::from(1))
+            .unwrap()
+            .with_delivery(Delivery::date("2019-12-15"))
+            .with_reduction(
+                ReductionListLineItem::new(
+                    Decimal::from(1020),
+                    ReductionAndSurchargeValue::Percentage(Decimal::from(10)),
+                )
+                .with_comment("Mengenrabatt"),
+            ),
+        )
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1),
+                "C62",
+                Decimal::from(5),
+                TaxItem::new(Decimal::from(10), TaxCategory::AA),
+            )
+            .with_description("Handbuch zur Schraube")
+            .with_delivery(Delivery::period("2019-12-01", "2019-12-31")),
+        )
+        .with_reduction(Reduction::new(
+            Decimal::from(923),
+            ReductionAndSurchargeValue::Amount(Decimal::from(3)),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_payment_method(PaymentMethod::sepa_direct_debit(
+            PaymentMethodSEPADirectDebit::new()
+                .with_iban(Iban::new("DE02120300000000202051").unwrap())
+                .with_creditor_id(CreditorId::new("AT61ZZZ01234567890").unwrap())
+                .with_mandate_reference("MANDATE-1")
+                .unwrap(),
+        ))
+        .with_payment_conditions(PaymentConditions::new("2020-01-31").with_comment("Zahlbar ohne Abzug"))
+    }
+
+    #[test]
+    fn writes_cii() {
+        let invoice = cii_invoice();
+
+        assert_eq!(
+            invoice.to_cii(CiiProfile::En16931).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><rsm:CrossIndustryInvoice xmlns:rsm=\"urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100\" xmlns:ram=\"urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100\" xmlns:udt=\"urn:un:unece:uncefact:data:standard:UnqualifiedDataType:100\" xmlns:qdt=\"urn:un:unece:uncefact:data:standard:QualifiedDataType:100\"><rsm:ExchangedDocumentContext><ram:GuidelineSpecifiedDocumentContextParameter><ram:ID>urn:cen.eu:en16931:2017</ram:ID></ram:GuidelineSpecifiedDocumentContextParameter></rsm:ExchangedDocumentContext><rsm:ExchangedDocument><ram:ID>993433000298</ram:ID><ram:TypeCode>380</ram:TypeCode><ram:IssueDateTime><udt:DateTimeString format=\"102\">20200101</udt:DateTimeString></ram:IssueDateTime><ram:IncludedNote><ram:Content>An invoice</ram:Content></ram:IncludedNote></rsm:ExchangedDocument><rsm:SupplyChainTradeTransaction><ram:IncludedSupplyChainTradeLineItem><ram:AssociatedDocumentLineDocument><ram:LineID>1</ram:LineID></ram:AssociatedDocumentLineDocument><ram:SpecifiedTradeProduct><ram:Name>Schraubenzieher</ram:Name><ram:Description>Kreuzschlitz</ram:Description></ram:SpecifiedTradeProduct><ram:SpecifiedLineTradeAgreement><ram:NetPriceProductTradePrice><ram:ChargeAmount>10.2000</ram:ChargeAmount><ram:BasisQuantity unitCode=\"C62\">1</ram:BasisQuantity></ram:NetPriceProductTradePrice></ram:SpecifiedLineTradeAgreement><ram:SpecifiedLineTradeDelivery><ram:BilledQuantity unitCode=\"C62\">100.0000</ram:BilledQuantity></ram:SpecifiedLineTradeDelivery><ram:SpecifiedLineTradeSettlement><ram:ApplicableTradeTax><ram:TypeCode>VAT</ram:TypeCode><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>20</ram:RateApplicablePercent></ram:ApplicableTradeTax><ram:BillingSpecifiedPeriod><ram:StartDateTime><udt:DateTimeString format=\"102\">20191215</udt:DateTimeString></ram:StartDateTime><ram:EndDateTime><udt:DateTimeString format=\"102\">20191215</udt:DateTimeString></ram:EndDateTime></ram:BillingSpecifiedPeriod><ram:SpecifiedTradeAllowanceCharge><ram:ChargeIndicator><udt:Indicator>false</udt:Indicator></ram:ChargeIndicator><ram:CalculationPercent>10.00</ram:CalculationPercent><ram:BasisAmount>1020.00</ram:BasisAmount><ram:ActualAmount>102.00</ram:ActualAmount><ram:Reason>Mengenrabatt</ram:Reason></ram:SpecifiedTradeAllowanceCharge><ram:SpecifiedTradeSettlementLineMonetarySummation><ram:LineTotalAmount>918.00</ram:LineTotalAmount></ram:SpecifiedTradeSettlementLineMonetarySummation></ram:SpecifiedLineTradeSettlement></ram:IncludedSupplyChainTradeLineItem><ram:IncludedSupplyChainTradeLineItem><ram:AssociatedDocumentLineDocument><ram:LineID>2</ram:LineID></ram:AssociatedDocumentLineDocument><ram:SpecifiedTradeProduct><ram:Name>Handbuch zur Schraube</ram:Name></ram:SpecifiedTradeProduct><ram:SpecifiedLineTradeAgreement><ram:NetPriceProductTradePrice><ram:ChargeAmount>5.0000</ram:ChargeAmount></ram:NetPriceProductTradePrice></ram:SpecifiedLineTradeAgreement><ram:SpecifiedLineTradeDelivery><ram:BilledQuantity unitCode=\"C62\">1.0000</ram:BilledQuantity></ram:SpecifiedLineTradeDelivery><ram:SpecifiedLineTradeSettlement><ram:ApplicableTradeTax><ram:TypeCode>VAT</ram:TypeCode><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>10</ram:RateApplicablePercent></ram:ApplicableTradeTax><ram:BillingSpecifiedPeriod><ram:StartDateTime><udt:DateTimeString format=\"102\">20191201</udt:DateTimeString></ram:StartDateTime><ram:EndDateTime><udt:DateTimeString format=\"102\">20191231</udt:DateTimeString></ram:EndDateTime></ram:BillingSpecifiedPeriod><ram:SpecifiedTradeSettlementLineMonetarySummation><ram:LineTotalAmount>5.00</ram:LineTotalAmount></ram:SpecifiedTradeSettlementLineMonetarySummation></ram:SpecifiedLineTradeSettlement></ram:IncludedSupplyChainTradeLineItem><ram:ApplicableHeaderTradeAgreement><ram:SellerTradeParty><ram:Name>Schrauben Mustermann</ram:Name><ram:DefinedTradeContact><ram:EmailURIUniversalCommunication><ram:URIID>schrauben@mustermann.at</ram:URIID></ram:EmailURIUniversalCommunication></ram:DefinedTradeContact><ram:PostalTradeAddress><ram:PostcodeCode>1020</ram:PostcodeCode><ram:LineOne>Lassallenstraße 5</ram:LineOne><ram:CityName>Wien</ram:CityName><ram:CountryID>AT</ram:CountryID></ram:PostalTradeAddress><ram:SpecifiedTaxRegistration><ram:ID schemeID=\"VA\">ATU51507409</ram:ID></ram:SpecifiedTaxRegistration></ram:SellerTradeParty><ram:BuyerTradeParty><ram:Name>Mustermann GmbH</ram:Name><ram:DefinedTradeContact><ram:PersonName>Max Mustermann</ram:PersonName><ram:EmailURIUniversalCommunication><ram:URIID>max@mustermann.de</ram:URIID></ram:EmailURIUniversalCommunication></ram:DefinedTradeContact><ram:PostalTradeAddress><ram:PostcodeCode>10115</ram:PostcodeCode><ram:LineOne>Hauptstraße 10</ram:LineOne><ram:CityName>Berlin</ram:CityName><ram:CountryID>DE</ram:CountryID></ram:PostalTradeAddress><ram:SpecifiedTaxRegistration><ram:ID schemeID=\"VA\">DE123456788</ram:ID></ram:SpecifiedTaxRegistration></ram:BuyerTradeParty><ram:BuyerOrderReferencedDocument><ram:IssuerAssignedID>04011000-12345-34</ram:IssuerAssignedID></ram:BuyerOrderReferencedDocument></ram:ApplicableHeaderTradeAgreement><ram:ApplicableHeaderTradeDelivery></ram:ApplicableHeaderTradeDelivery><ram:ApplicableHeaderTradeSettlement><ram:CreditorReferenceID>AT61ZZZ01234567890</ram:CreditorReferenceID><ram:InvoiceCurrencyCode>EUR</ram:InvoiceCurrencyCode><ram:SpecifiedTradeSettlementPaymentMeans><ram:TypeCode>59</ram:TypeCode><ram:PayerPartyDebtorFinancialAccount><ram:IBANID>DE02120300000000202051</ram:IBANID></ram:PayerPartyDebtorFinancialAccount></ram:SpecifiedTradeSettlementPaymentMeans><ram:ApplicableTradeTax><ram:CalculatedAmount>0.50</ram:CalculatedAmount><ram:TypeCode>VAT</ram:TypeCode><ram:BasisAmount>5.00</ram:BasisAmount><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>10</ram:RateApplicablePercent></ram:ApplicableTradeTax><ram:ApplicableTradeTax><ram:CalculatedAmount>183.00</ram:CalculatedAmount><ram:TypeCode>VAT</ram:TypeCode><ram:BasisAmount>915.00</ram:BasisAmount><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>20</ram:RateApplicablePercent></ram:ApplicableTradeTax><ram:SpecifiedTradeAllowanceCharge><ram:ChargeIndicator><udt:Indicator>false</udt:Indicator></ram:ChargeIndicator><ram:BasisAmount>923.00</ram:BasisAmount><ram:ActualAmount>3.00</ram:ActualAmount><ram:ReasonCode>95</ram:ReasonCode><ram:CategoryTradeTax><ram:TypeCode>VAT</ram:TypeCode><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>20</ram:RateApplicablePercent></ram:CategoryTradeTax></ram:SpecifiedTradeAllowanceCharge><ram:SpecifiedTradePaymentTerms><ram:Description>Zahlbar ohne Abzug</ram:Description><ram:DueDateDateTime><udt:DateTimeString format=\"102\">20200131</udt:DateTimeString></ram:DueDateDateTime><ram:DirectDebitMandateID>MANDATE-1</ram:DirectDebitMandateID></ram:SpecifiedTradePaymentTerms><ram:SpecifiedTradeSettlementHeaderMonetarySummation><ram:LineTotalAmount>923.00</ram:LineTotalAmount><ram:ChargeTotalAmount>0.00</ram:ChargeTotalAmount><ram:AllowanceTotalAmount>3.00</ram:AllowanceTotalAmount><ram:TaxBasisTotalAmount>920.00</ram:TaxBasisTotalAmount><ram:TaxTotalAmount currencyID=\"EUR\">183.50</ram:TaxTotalAmount><ram:GrandTotalAmount>1103.50</ram:GrandTotalAmount><ram:DuePayableAmount>1103.50</ram:DuePayableAmount></ram:SpecifiedTradeSettlementHeaderMonetarySummation></ram:ApplicableHeaderTradeSettlement></rsm:SupplyChainTradeTransaction></rsm:CrossIndustryInvoice>"
+        );
+    }
+
+    #[test]
+    fn writes_cii_profiles() {
+        let invoice = cii_invoice();
+
+        let xrechnung = invoice.to_cii(CiiProfile::XRechnung).unwrap();
+        assert!(
+            xrechnung
+                .contains("<ram:ID>urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0</ram:ID>")
+        );
+        assert!(xrechnung.contains("<ram:BuyerReference>04011000-12345-34</ram:BuyerReference>"));
+        assert!(!xrechnung.contains("ram:ActualDeliverySupplyChainEvent"));
+
+        let extended = invoice.to_cii(CiiProfile::Extended).unwrap();
+        assert!(!extended.contains("ram:BuyerReference"));
+        assert!(extended.contains("<ram:BilledQuantity unitCode=\"C62\">100.0000</ram:BilledQuantity><ram:ActualDeliverySupplyChainEvent><ram:OccurrenceDateTime><udt:DateTimeString format=\"102\">20191215</udt:DateTimeString></ram:OccurrenceDateTime></ram:ActualDeliverySupplyChainEvent>"));
+        assert!(extended.contains("<ram:GrandTotalAmount>1103.50</ram:GrandTotalAmount>"));
+        assert!(
+            invoice
+                .to_xml()
+                .contains("<TotalGrossAmount>1103.50</TotalGrossAmount>")
+        );
+
+        let invoice = Invoice {
+            invoice_recipient: InvoiceRecipient::new(VatId::new("DE123456788").unwrap()),
+            ..invoice
+        };
+        assert_eq!(
+            invoice.to_cii(CiiProfile::XRechnung),
+            Err(Error::Missing {
+                field: "OrderReference"
+            })
+        );
+        assert!(invoice.to_cii(CiiProfile::En16931).is_ok());
+    }
+
+    #[test]
+    fn calculates_totals() {
+        let invoice = ubl_invoice().with_prepaid_amount(Decimal::from(100));
+        let totals = invoice.totals();
+
+        assert_eq!(totals.line_total_amount(), Decimal::new(92300, 2));
+        assert_eq!(totals.allowance_total_amount(), Decimal::ZERO);
+        assert_eq!(totals.charge_total_amount(), Decimal::new(1500, 2));
+        assert_eq!(totals.tax_basis_total_amount(), Decimal::new(93800, 2));
+        assert_eq!(
+            totals
+                .tax_items()
+                .iter()
+                .map(|t| (t.tax_percent(), t.tax_category(), t.taxable_amount(), t.tax_amount()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Decimal::from(10), TaxCategory::AA, Decimal::new(500, 2), Decimal::new(50, 2)),
+                (Decimal::from(20), TaxCategory::S, Decimal::new(93300, 2), Decimal::new(18660, 2)),
+            ]
+        );
+        assert_eq!(totals.tax_total_amount(), Decimal::new(18710, 2));
+        assert_eq!(totals.total_gross_amount(), Decimal::new(112510, 2));
+        assert_eq!(totals.prepaid_amount(), Some(Decimal::new(10000, 2)));
+        assert_eq!(totals.rounding_amount(), None);
+        assert_eq!(totals.payable_amount(), Decimal::new(102510, 2));
+        assert!(
+            invoice
+                .to_xml()
+                .contains("<PrepaidAmount>100.00</PrepaidAmount><PayableAmount>1025.10</PayableAmount>")
+        );
+    }
+
+    #[test]
+    fn rounds_with_rounding_policy() {
+        let item = |unit_price, tax_item| DetailsItem::new(Decimal::ONE, "STK", unit_price, tax_item);
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_items(vec![
+            item(Decimal::new(3, 2), TaxItem::new(Decimal::from(20), TaxCategory::S)),
+            item(Decimal::new(3, 2), TaxItem::new(Decimal::from(20), TaxCategory::S)),
+            item(Decimal::new(125, 3), TaxItem::new(Decimal::from(10), TaxCategory::AA)),
+        ]);
+        let tax_amounts = |totals: &Totals| totals.tax_items().iter().map(|t| t.tax_amount()).collect::<Vec<_>>();
+
+        let totals = invoice.totals();
+        assert_eq!(totals.line_total_amount(), Decimal::new(19, 2));
+        assert_eq!(tax_amounts(&totals), vec![Decimal::new(1, 2), Decimal::new(1, 2)]);
+        assert_eq!(totals.total_gross_amount(), Decimal::new(21, 2));
+
+        let invoice = invoice.with_rounding_policy(RoundingPolicy::new(RoundingStrategy::MidpointNearestEven));
+        let totals = invoice.totals();
+        assert_eq!(totals.line_total_amount(), Decimal::new(18, 2));
+        assert_eq!(tax_amounts(&totals), vec![Decimal::new(1, 2), Decimal::new(1, 2)]);
+        assert_eq!(totals.total_gross_amount(), Decimal::new(20, 2));
+        assert!(invoice.to_xml().contains("<LineItemAmount>0.12</LineItemAmount>"));
+
+        let invoice = invoice.with_rounding_policy(
+            RoundingPolicy::new(RoundingStrategy::MidpointNearestEven).with_tax_rounding(TaxRounding::PerLine),
+        );
+        let totals = invoice.totals();
+        assert_eq!(tax_amounts(&totals), vec![Decimal::new(1, 2), Decimal::new(2, 2)]);
+        assert_eq!(totals.tax_total_amount(), Decimal::new(3, 2));
+        assert_eq!(totals.total_gross_amount(), Decimal::new(21, 2));
+        assert!(
+            invoice
+                .to_ubl()
+                .unwrap()
+                .contains("<cbc:TaxInclusiveAmount currencyID=\"EUR\">0.21</cbc:TaxInclusiveAmount>")
+        );
+    }
+
+    #[test]
+    fn adds_up_the_rounded_tax_of_multiple_rates() {
+        let item = |tax_category| {
+            DetailsItem::new(Decimal::ONE, "STK", Decimal::new(5, 2), TaxItem::new(Decimal::from(10), tax_category))
+        };
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_items(vec![item(TaxCategory::S), item(TaxCategory::AA), item(TaxCategory::Z)]);
+        let totals = invoice.totals();
+
+        assert_eq!(totals.line_total_amount(), Decimal::new(15, 2));
+        assert_eq!(totals.tax_items().iter().map(|t| t.tax_amount()).collect::<Vec<_>>(), vec![Decimal::new(1, 2); 3]);
+        assert_eq!(totals.tax_total_amount(), Decimal::new(3, 2));
+        assert_eq!(totals.total_gross_amount(), Decimal::new(18, 2));
+        assert!(invoice.to_xml().contains("<TotalGrossAmount>0.18</TotalGrossAmount>"));
+        assert!(
+            invoice
+                .to_ubl()
+                .unwrap()
+                .contains("<cbc:TaxInclusiveAmount currencyID=\"EUR\">0.18</cbc:TaxInclusiveAmount>")
+        );
+        assert!(
+            invoice
+                .to_cii(CiiProfile::En16931)
+                .unwrap()
+                .contains("<ram:GrandTotalAmount>0.18</ram:GrandTotalAmount>")
+        );
+    }
+
+    #[test]
+    fn honours_base_quantity_in_totals() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1500),
+                "STK",
+                Decimal::new(42, 1),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_base_quantity(Decimal::from(1000))
+            .unwrap(),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(2),
+            "STK",
+            Decimal::new(75, 1),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+        let totals = invoice.totals();
+
+        assert_eq!(totals.line_total_amount(), Decimal::new(2130, 2));
+        assert_eq!(totals.tax_items()[0].taxable_amount(), Decimal::new(2130, 2));
+        assert_eq!(totals.tax_total_amount(), Decimal::new(426, 2));
+        assert_eq!(totals.total_gross_amount(), Decimal::new(2556, 2));
+        assert!(invoice.to_xml().contains(
+            "<UnitPrice BaseQuantity=\"1000\">4.2000</UnitPrice><TaxItem><TaxableAmount>6.30</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>1.26</TaxAmount></TaxItem>"
+        ));
+    }
+
+    #[test]
+    fn reads_ubl_and_cii() {
+        let ubl = ubl_invoice().to_ubl().unwrap();
+        let conversion = Invoice::from_ubl(&ubl).unwrap();
+        assert_eq!(conversion.losses, []);
+        assert_eq!(conversion.invoice.to_ubl().unwrap(), ubl);
+
+        for profile in [CiiProfile::En16931, CiiProfile::XRechnung, CiiProfile::Extended] {
+            let cii = cii_invoice().to_cii(profile).unwrap();
+            let conversion = Invoice::from_cii(&cii).unwrap();
+            assert_eq!(conversion.losses, [], "{profile}");
+            assert_eq!(conversion.invoice.to_cii(profile).unwrap(), cii, "{profile}");
+        }
+    }
+
+    #[test]
+    fn reports_conversion_losses() {
+        let ubl = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Invoice xmlns="urn:oasis:names:specification:ubl:schema:xsd:Invoice-2"
+    xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2"
+    xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2">
+  <cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID>
+  <cbc:ID>R-42</cbc:ID>
+  <cbc:IssueDate>2024-03-01</cbc:IssueDate>
+  <cbc:DueDate>2024-03-31</cbc:DueDate>
+  <cbc:InvoiceTypeCode>380</cbc:InvoiceTypeCode>
+  <cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode>
+  <cbc:BuyerReference>991-12345-67</cbc:BuyerReference>
+  <cac:AccountingSupplierParty>
+    <cac:Party>
+      <cbc:EndpointID schemeID="9914">ATU51507409</cbc:EndpointID>
+      <cac:PartyName><cbc:Name>Schrauben Mustermann</cbc:Name></cac:PartyName>
+      <cac:PostalAddress>
+        <cbc:CityName>Wien</cbc:CityName>
+        <cbc:PostalZone>1020</cbc:PostalZone>
+        <cac:Country><cbc:IdentificationCode>AT</cbc:IdentificationCode></cac:Country>
+      </cac:PostalAddress>
+      <cac:PartyTaxScheme>
+        <cbc:CompanyID>ATU51507409</cbc:CompanyID>
+        <cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme>
+      </cac:PartyTaxScheme>
+    </cac:Party>
+  </cac:AccountingSupplierParty>
+  <cac:AccountingCustomerParty>
+    <cac:Party>
+      <cac:PartyName><cbc:Name>Mustermann GmbH</cbc:Name></cac:PartyName>
+      <cac:PostalAddress>
+        <cbc:CityName>Graz</cbc:CityName>
+        <cbc:PostalZone>8010</cbc:PostalZone>
+        <cac:Country><cbc:IdentificationCode>AT</cbc:IdentificationCode></cac:Country>
+      </cac:PostalAddress>
+    </cac:Party>
+  </cac:AccountingCustomerParty>
+  <cac:PaymentMeans>
+    <cbc:PaymentMeansCode>10</cbc:PaymentMeansCode>
+  </cac:PaymentMeans>
+  <cac:PaymentMeans>
+    <cbc:PaymentMeansCode>58</cbc:PaymentMeansCode>
+    <cac:PayeeFinancialAccount><cbc:ID>AT611904300234573201</cbc:ID></cac:PayeeFinancialAccount>
+  </cac:PaymentMeans>
+  <cac:LegalMonetaryTotal>
+    <cbc:PayableAmount currencyID="EUR">120.00</cbc:PayableAmount>
+  </cac:LegalMonetaryTotal>
+  <cac:InvoiceLine>
+    <cbc:ID>A1</cbc:ID>
+    <cbc:InvoicedQuantity unitCode="C62">2</cbc:InvoicedQuantity>
+    <cac:Item>
+      <cbc:Name>Schraubenzieher</cbc:Name>
+      <cac:SellersItemIdentification><cbc:ID>SZ-1</cbc:ID></cac:SellersItemIdentification>
+      <cac:ClassifiedTaxCategory>
+        <cbc:ID>L</cbc:ID>
+        <cbc:Percent>7</cbc:Percent>
+        <cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme>
+      </cac:ClassifiedTaxCategory>
+    </cac:Item>
+    <cac:Price><cbc:PriceAmount currencyID="EUR">50</cbc:PriceAmount></cac:Price>
+  </cac:InvoiceLine>
+</Invoice>"#;
+
+        let conversion = Invoice::from_ubl(ubl).unwrap();
+        let losses = conversion.losses.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            losses,
+            [
+                "AccountingSupplierParty/Party/EndpointID: unsupported element, dropped at line 14, column 7",
+                "PaymentMeans[1]/PaymentMeansCode: payment means code 10 has no counterpart, read as OtherPayment at \
+                 line 38, column 5",
+                "PaymentMeans[2]: second payment method, dropped at line 40, column 3",
+                "LegalMonetaryTotal/PayableAmount: PayableAmount 120.00 differs from 107.00 derived by ebInterface at \
+                 line 45, column 5",
+                "InvoiceLine/ID: line ID A1 isn't a position number, dropped at line 48, column 5",
+                "InvoiceLine/Item/SellersItemIdentification: unsupported element, dropped at line 52, column 7",
+                "InvoiceLine/Item/ClassifiedTaxCategory: unknown tax category code L, read as S at line 53, column 7",
+            ]
+        );
+
+        let invoice = conversion.invoice;
+        assert_eq!(invoice.generating_system, crate::import::GENERATING_SYSTEM);
+        let order_reference = invoice.invoice_recipient.order_reference();
+        assert_eq!(order_reference.map(OrderReference::order_id), Some("991-12345-67"));
+        assert_eq!(invoice.payment_method, Some(PaymentMethod::other_payment()));
+        assert_eq!(invoice.details.items[0].tax_item, TaxItem::new(Decimal::from(7), TaxCategory::S));
+    }
+
+    #[test]
+    fn rejects_documents_of_other_formats() {
+        let ubl = ubl_invoice().to_ubl().unwrap();
+        let cii = cii_invoice().to_cii(CiiProfile::En16931).unwrap();
+
+        assert_eq!(Invoice::from_cii(&ubl).unwrap_err().kind, ParseErrorKind::UnexpectedElement("Invoice".to_string()));
+        assert_eq!(
+            Invoice::from_ubl(&cii).unwrap_err().kind,
+            ParseErrorKind::UnexpectedElement("CrossIndustryInvoice".to_string())
+        );
+        assert_eq!(
+            Invoice::from_ubl(&ubl.replace("xsd:Invoice-2", "xsd:CreditNote-2"))
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::UnsupportedNamespace(CREDIT_NOTE_NAMESPACE.to_string())
+        );
+        assert_eq!(
+            Invoice::from_cii(&cii.replace("format=\"102\">20200101", "format=\"610\">202001"))
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::InvalidValue {
+                name: "format".to_string(),
+                value: "610".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_imported_base_quantities_not_greater_than_zero() {
+        let ubl = ubl_invoice().to_ubl().unwrap();
+        let cii = cii_invoice().to_cii(CiiProfile::En16931).unwrap();
+
+        assert_eq!(
+            Invoice::from_ubl(&ubl.replace("\"C62\">1</cbc:BaseQuantity>", "\"C62\">0</cbc:BaseQuantity>"))
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::InvalidValue {
+                name: "BaseQuantity".to_string(),
+                value: "0".to_string()
+            }
+        );
+        assert_eq!(
+            Invoice::from_cii(&cii.replace("\"C62\">1</ram:BasisQuantity>", "\"C62\">-1</ram:BasisQuantity>"))
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::InvalidValue {
+                name: "BasisQuantity".to_string(),
+                value: "-1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn calculates_payment_conditions_discount_from_payable_amount() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(100),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ))
+        .with_prepaid_amount(Decimal::from(20))
+        .with_payment_conditions(
+            PaymentConditions::new("2020-01-31")
+                .with_discount(PaymentConditionsDiscount::new("2020-01-15").with_percentage(Decimal::from(2))),
+        )
+        .to_xml();
+
+        assert!(invoice.ends_with("<PayableAmount>100.00</PayableAmount><PaymentConditions><DueDate>2020-01-31</DueDate><Discount><PaymentDate>2020-01-15</PaymentDate><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage><Amount>2.00</Amount></Discount></PaymentConditions></Invoice>"));
+    }
+
+    #[test]
+    fn requires_delivery_on_invoice_or_each_item() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1),
+                "STK",
+                Decimal::from(10),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_delivery(Delivery::date("2020-01-01")),
+        );
+        assert_eq!(invoice.validate(), Ok(()));
+
+        let invoice = invoice.with_item(DetailsItem::new(
+            Decimal::from(1),
+            "STK",
+            Decimal::from(10),
+            TaxItem::new(Decimal::from(20), TaxCategory::S),
+        ));
+        assert_eq!(
+            invoice.validate(),
+            Err(vec![ValidationError {
+                path: "Delivery".to_string(),
+                error: Error::MissingDelivery,
+            }])
+        );
+
+        let invoice = invoice.with_delivery(Delivery::period("2020-01-01", "2020-01-31"));
+        assert_eq!(invoice.validate(), Ok(()));
+        assert_round_trip(&invoice);
+    }
+
+    #[test]
+    fn creates_owned_invoice() {
+        fn build_invoice(invoice_number: String, descriptions: Vec<String>) -> Invoice<'static> {
+            let mut invoice = Invoice::new(
+                "test",
+                "EUR",
+                invoice_number,
+                "2020-01-01".to_string(),
+                Biller::new(VatId::new("ATU51507409".to_string()).unwrap()),
+                InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+            );
+            for description in descriptions {
+                invoice = invoice.with_item(
+                    DetailsItem::new(
+                        Decimal::from(1),
+                        "STK",
+                        Decimal::from(10),
+                        TaxItem::new(Decimal::from(20), TaxCategory::S),
+                    )
+                    .with_description(description),
+                );
+            }
+            invoice
+        }
+
+        let invoice = build_invoice("993433000298".to_string(), vec!["a".to_string(), "b".to_string()]);
+        let xml = std::thread::spawn(move || invoice.to_xml()).join().unwrap();
+        assert!(xml.contains("<InvoiceNumber>993433000298</InvoiceNumber>"));
+        assert!(xml.contains("<Description>b</Description>"));
+
+        let number = String::from("993433000299");
+        let borrowed = Invoice::new(
+            "test",
+            "EUR",
+            number.as_str(),
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        );
+        let owned: Invoice<'static> = borrowed.into_owned();
+        drop(number);
+        assert!(owned.to_xml().contains("<InvoiceNumber>993433000299</InvoiceNumber>"));
+    }
+
+    #[test]
+    fn parses_invoice_with_line_and_column_of_errors() {
+        let xml = "<?xml version=\"1.0\"?>
+<Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\">
+  <InvoiceNumber>993433000298</InvoiceNumber>
+  <InvoiceDate>2020-01-01</InvoiceDate>
+  <Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller>
+  <InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient>
+  <Details>
+    <ItemList>
+      <ListLineItem>
+        <Description>Schrauben &amp; Muttern</Description>
+        <Quantity Unit=\"STK\">1.0000</Quantity>
+        <UnitPrice>10.0000</UnitPrice>
+        <TaxItem><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent></TaxItem>
+      </ListLineItem>
+    </ItemList>
+  </Details>
+</Invoice>";
+
+        let invoice = Invoice::from_xml(xml).unwrap();
+        assert!(matches!(invoice.invoice_number, Cow::Borrowed("993433000298")));
+        assert_eq!(
+            invoice,
+            Invoice::new(
+                "test",
+                "EUR",
+                "993433000298",
+                "2020-01-01",
+                Biller::new(VatId::new("ATU51507409").unwrap()),
+                InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+            )
+            .with_item(
+                DetailsItem::new(
+                    Decimal::from(1),
+                    "STK",
+                    Decimal::from(10),
+                    TaxItem::new(Decimal::from(20), TaxCategory::S),
+                )
+                .with_description("Schrauben & Muttern")
+            )
+        );
+
+        assert_eq!(
+            Invoice::from_xml(&xml.replace("1.0000</Quantity>", "1,0</Quantity>"))
+                .unwrap_err()
+                .to_string(),
+            "invalid value \"1,0\" of Quantity at line 11, column 9"
+        );
+        assert_eq!(
+            Invoice::from_xml(&xml.replace(" TaxCategoryCode=\"S\"", ""))
+                .unwrap_err()
+                .to_string(),
+            "missing attribute TaxCategoryCode at line 13, column 18"
+        );
+        assert_eq!(
+            Invoice::from_xml(&xml.replace("<InvoiceDate>2020-01-01</InvoiceDate>", ""))
+                .unwrap_err()
+                .to_string(),
+            "missing element <InvoiceDate> at line 2, column 1"
+        );
+        assert_eq!(
+            Invoice::from_xml(&xml.replace("</ItemList>", "</Item>")).unwrap_err().to_string(),
+            "expected end tag </ItemList>, found </Item> at line 15, column 5"
+        );
+        assert_eq!(
+            Invoice::from_xml(&xml.replace("6p1", "6p0")).unwrap_err().kind,
+            ParseErrorKind::UnsupportedNamespace("http://www.ebinterface.at/schema/6p0/".to_string())
+        );
+    }
+
+    #[test]
+    fn streams_xml() {
+        let invoice = Invoice::new(
+            "test",
+            "EUR",
+            "993433000298",
+            "2020-01-01",
+            Biller::new(VatId::new("ATU51507409").unwrap()),
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap()),
+        )
+        .with_item(
+            DetailsItem::new(
+                Decimal::from(1),
+                "STK",
+                Decimal::from(10),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            )
+            .with_description("<&>"),
+        );
+
+        let mut io_out = Vec::new();
+        invoice.write_xml(&mut io_out).unwrap();
+        let mut fmt_out = String::new();
+        invoice.write_xml_fmt(&mut fmt_out).unwrap();
+
+        assert_eq!(String::from_utf8(io_out).unwrap(), invoice.to_xml());
+        assert_eq!(fmt_out, invoice.to_xml());
+        assert_round_trip(&invoice);
+    }
 }
@@ -1,16 +1,34 @@
 pub mod address;
+pub mod below_the_line_item;
+pub mod bic;
 pub mod biller;
+pub mod cii;
 pub mod contact;
+pub mod creditor_id;
 pub mod decimal;
+pub mod delivery;
 pub mod details;
 pub mod document;
+pub mod error;
+pub mod iban;
 pub mod identification;
+pub mod import;
 pub mod invoice;
 pub mod invoice_recipient;
 pub mod order_reference;
+pub mod parse;
+pub mod payment_conditions;
 pub mod payment_method;
+pub mod payment_reference;
 pub mod reduction_and_surcharge;
+pub mod rounding;
+pub mod rules;
 pub mod tax;
+pub mod vat_id;
+pub mod version;
+pub(crate) mod codes;
+pub(crate) mod ubl;
+pub(crate) mod validation;
 pub(crate) mod xml;
 
 #[cfg(test)]
@@ -30,15 +48,16 @@ mod tests {
     use payment_method::{PaymentMethod, PaymentMethodPaymentCard};
     use reduction_and_surcharge::{ReductionAndSurchargeValue, ReductionListLineItem};
     use tax::{TaxCategory, TaxItem};
+    use vat_id::VatId;
 
     #[test]
     fn it_works() {
-        let result = Invoice::new(
+        let invoice = Invoice::new(
             "test",
             "EUR",
             "993433000298",
             "2020-01-01",
-            Biller::new("ATU51507409")
+            Biller::new(VatId::new("ATU51507409").unwrap())
                 .with_further_identification(FurtherIdentification::new(
                     "0012345",
                     FurtherIdentificationType::DVR,
@@ -50,7 +69,7 @@ mod tests {
                         .with_phone("+43 / 1 / 78 56 789")
                         .with_email("schrauben@mustermann.at"),
                 ),
-            InvoiceRecipient::new("ATU18708634")
+            InvoiceRecipient::new(VatId::new("ATU18708637").unwrap())
                 .with_order_reference(OrderReference::new("test"))
                 .with_address(
                     Address::new("Mustermann GmbH", "Graz", "8010", "Österreich")
@@ -68,7 +87,8 @@ mod tests {
             )
             .with_position_number(1)
             .with_description("Schraubenzieher")
-            .with_base_quantity(Decimal::from(1)),
+            .with_base_quantity(Decimal::from(1))
+            .unwrap(),
         )
         .with_item(
             DetailsItem::new(
@@ -80,6 +100,7 @@ mod tests {
             .with_position_number(2)
             .with_description("Handbuch zur Schraube")
             .with_base_quantity(Decimal::from(1))
+            .unwrap()
             .with_reduction(
                 ReductionListLineItem::new(
                     Decimal::from(5),
@@ -93,16 +114,16 @@ mod tests {
         .with_payment_method(
             PaymentMethod::payment_card(
                 PaymentMethodPaymentCard::new("123456*4321")
-                    .and_then(|s| Ok(s.with_card_holder_name("Name")))
+                    .map(|s| s.with_card_holder_name("Name"))
                     .unwrap_or_else(|e| panic!("{e}")),
             )
             .with_comment("Comment"),
-        )
-        .to_xml();
+        );
 
         assert_eq!(
-            result,
-            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\" DocumentTitle=\"An invoice\" Language=\"de\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber><FurtherIdentification IdentificationType=\"DVR\">0012345</FurtherIdentification><Address><Name>Schrauben Mustermann</Name><Street>Lassallenstraße 5</Street><Town>Wien</Town><ZIP>1020</ZIP><Country CountryCode=\"AT\">Österreich</Country><Phone>+43 / 1 / 78 56 789</Phone><Email>schrauben@mustermann.at</Email></Address></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708634</VATIdentificationNumber><OrderReference><OrderID>test</OrderID></OrderReference><Address><Name>Mustermann GmbH</Name><Street>Hauptstraße 10</Street><Town>Graz</Town><ZIP>8010</ZIP><Country CountryCode=\"AT\">Österreich</Country></Address><Contact><Name>Max Mustermann</Name><Email>schrauben@mustermann.at</Email></Contact></InvoiceRecipient><Details><ItemList><ListLineItem><PositionNumber>1</PositionNumber><Description>Schraubenzieher</Description><Quantity Unit=\"STK\">100.0000</Quantity><UnitPrice BaseQuantity=\"1\">10.2000</UnitPrice><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem><LineItemAmount>1020.00</LineItemAmount></ListLineItem><ListLineItem><PositionNumber>2</PositionNumber><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice BaseQuantity=\"1\">5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.00</Amount><Comment>reduction</Comment></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>3.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.30</TaxAmount></TaxItem><LineItemAmount>3.00</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>3.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.30</TaxAmount></TaxItem><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>1227.30</TotalGrossAmount><PayableAmount>1227.30</PayableAmount><PaymentMethod><Comment>Comment</Comment><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod></Invoice>"
+            invoice.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Invoice xmlns=\"http://www.ebinterface.at/schema/6p1/\" GeneratingSystem=\"test\" DocumentType=\"Invoice\" InvoiceCurrency=\"EUR\" DocumentTitle=\"An invoice\" Language=\"de\"><InvoiceNumber>993433000298</InvoiceNumber><InvoiceDate>2020-01-01</InvoiceDate><Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber><FurtherIdentification IdentificationType=\"DVR\">0012345</FurtherIdentification><Address><Name>Schrauben Mustermann</Name><Street>Lassallenstraße 5</Street><Town>Wien</Town><ZIP>1020</ZIP><Country CountryCode=\"AT\">Österreich</Country><Phone>+43 / 1 / 78 56 789</Phone><Email>schrauben@mustermann.at</Email></Address></Biller><InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber><OrderReference><OrderID>test</OrderID></OrderReference><Address><Name>Mustermann GmbH</Name><Street>Hauptstraße 10</Street><Town>Graz</Town><ZIP>8010</ZIP><Country CountryCode=\"AT\">Österreich</Country></Address><Contact><Name>Max Mustermann</Name><Email>schrauben@mustermann.at</Email></Contact></InvoiceRecipient><Details><ItemList><ListLineItem><PositionNumber>1</PositionNumber><Description>Schraubenzieher</Description><Quantity Unit=\"STK\">100.0000</Quantity><UnitPrice BaseQuantity=\"1\">10.2000</UnitPrice><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem><LineItemAmount>1020.00</LineItemAmount></ListLineItem><ListLineItem><PositionNumber>2</PositionNumber><Description>Handbuch zur Schraube</Description><Quantity Unit=\"STK\">1.0000</Quantity><UnitPrice BaseQuantity=\"1\">5.0000</UnitPrice><ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>5.00</BaseAmount><Amount>2.00</Amount><Comment>reduction</Comment></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails><TaxItem><TaxableAmount>3.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.30</TaxAmount></TaxItem><LineItemAmount>3.00</LineItemAmount></ListLineItem></ItemList></Details><Tax><TaxItem><TaxableAmount>3.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>0.30</TaxAmount></TaxItem><TaxItem><TaxableAmount>1020.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>204.00</TaxAmount></TaxItem></Tax><TotalGrossAmount>1227.30</TotalGrossAmount><PayableAmount>1227.30</PayableAmount><PaymentMethod><Comment>Comment</Comment><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod></Invoice>",
         );
+        assert_eq!(Invoice::from_xml(&invoice.to_xml()).unwrap(), invoice);
     }
 }
@@ -1,43 +1,106 @@
-use crate::xml::{ToXml, XmlElement};
+use std::{borrow::Cow, fmt};
 
-#[derive(Default)]
+use crate::{
+    cii::{CiiWriter, ToCii},
+    parse::{FromXml, ParseError, XmlNode},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, Default, PartialEq)]
 pub struct OrderReference<'a> {
-    order_id: &'a str,
-    reference_date: Option<&'a str>,
-    description: Option<&'a str>,
+    order_id: Cow<'a, str>,
+    reference_date: Option<Cow<'a, str>>,
+    description: Option<Cow<'a, str>>,
 }
 
 impl<'a> OrderReference<'a> {
-    pub fn new(order_id: &'a str) -> Self {
+    pub fn new(order_id: impl Into<Cow<'a, str>>) -> Self {
         OrderReference {
-            order_id,
+            order_id: order_id.into(),
             ..Default::default()
         }
     }
 
-    pub fn with_reference_date(mut self, reference_date: &'a str) -> Self {
-        self.reference_date = Some(reference_date);
+    pub fn with_reference_date(mut self, reference_date: impl Into<Cow<'a, str>>) -> Self {
+        self.reference_date = Some(reference_date.into());
         self
     }
 
-    pub fn with_description(mut self, description: &'a str) -> Self {
-        self.description = Some(description);
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
         self
     }
+
+    pub(crate) fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    pub fn into_owned(self) -> OrderReference<'static> {
+        OrderReference {
+            order_id: Cow::Owned(self.order_id.into_owned()),
+            reference_date: self.reference_date.map(|d| Cow::Owned(d.into_owned())),
+            description: self.description.map(|d| Cow::Owned(d.into_owned())),
+        }
+    }
 }
 
 impl ToXml for OrderReference<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("OrderReference").with_text_element("OrderID", self.order_id);
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("OrderReference")?;
+        w.text_element("OrderID", &self.order_id)?;
+
+        if let Some(d) = &self.reference_date {
+            w.text_element("ReferenceDate", d)?;
+        }
 
-        if let Some(d) = self.reference_date {
-            e = e.with_text_element("ReferenceDate", d);
+        if let Some(d) = &self.description {
+            w.text_element("Description", d)?;
         }
 
-        if let Some(d) = self.description {
-            e = e.with_text_element("Description", d);
+        w.end("OrderReference")
+    }
+}
+
+impl ToUbl for OrderReference<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:OrderReference")?;
+        w.text_element("cbc:ID", &self.order_id)?;
+
+        if let Some(d) = &self.reference_date {
+            w.text_element("cbc:IssueDate", d)?;
         }
 
-        e.to_xml()
+        w.end("cac:OrderReference")
+    }
+}
+
+/// Writes a CII `BuyerOrderReferencedDocument`.
+impl ToCii for OrderReference<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:BuyerOrderReferencedDocument")?;
+        w.text_element("ram:IssuerAssignedID", &self.order_id)?;
+        w.end("ram:BuyerOrderReferencedDocument")
+    }
+}
+
+impl<'a> FromXml<'a> for OrderReference<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(OrderReference {
+            order_id: node.required_child_text("OrderID")?,
+            reference_date: node.child_text("ReferenceDate"),
+            description: node.child_text("Description"),
+        })
+    }
+}
+
+impl Validate for OrderReference<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.required("OrderID", &self.order_id);
+
+        if let Some(d) = &self.reference_date {
+            v.date("ReferenceDate", d);
+        }
     }
 }
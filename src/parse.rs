@@ -0,0 +1,501 @@
+use rust_decimal::Decimal;
+use std::{borrow::Cow, fmt, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// The document isn't well-formed XML.
+    Syntax(&'static str),
+    UnexpectedEof,
+    MismatchedEndTag {
+        expected: String,
+        found: String,
+    },
+    UnknownEntity(String),
+    UnsupportedNamespace(String),
+    UnexpectedElement(String),
+    MissingElement(String),
+    MissingAttribute(String),
+    InvalidValue {
+        name: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Syntax(message) => write!(f, "{message}"),
+            ParseErrorKind::UnexpectedEof => write!(f, "unexpected end of document"),
+            ParseErrorKind::MismatchedEndTag { expected, found } => {
+                write!(f, "expected end tag </{expected}>, found </{found}>")
+            }
+            ParseErrorKind::UnknownEntity(entity) => write!(f, "unknown entity &{entity};"),
+            ParseErrorKind::UnsupportedNamespace(namespace) => write!(f, "unsupported namespace \"{namespace}\""),
+            ParseErrorKind::UnexpectedElement(name) => write!(f, "unexpected element <{name}>"),
+            ParseErrorKind::MissingElement(name) => write!(f, "missing element <{name}>"),
+            ParseErrorKind::MissingAttribute(name) => write!(f, "missing attribute {name}"),
+            ParseErrorKind::InvalidValue { name, value } => write!(f, "invalid value \"{value}\" of {name}"),
+        }
+    }
+}
+
+/// An error, which occurred while reading an XML document, with the (1-based) line and column,
+/// where it occurred.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.kind, self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An element of a parsed XML document.
+/// Texts of an element get concatenated, comments and processing instructions are skipped.
+#[derive(Debug)]
+pub(crate) struct XmlNode<'a> {
+    /// The local name, without the namespace prefix.
+    pub(crate) name: &'a str,
+    prefix: Option<&'a str>,
+    attrs: Vec<(&'a str, Cow<'a, str>)>,
+    pub(crate) children: Vec<XmlNode<'a>>,
+    text: Cow<'a, str>,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    /// The number of the element in document order, 0 for the root, to keep track of elements read from the tree.
+    pub(crate) index: usize,
+}
+
+impl<'a> XmlNode<'a> {
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+
+    /// Returns the namespace of this element, if it's declared on this element.
+    pub(crate) fn namespace(&self) -> Option<&str> {
+        match self.prefix {
+            Some(prefix) => self
+                .attrs
+                .iter()
+                .find(|(n, _)| n.strip_prefix("xmlns:") == Some(prefix))
+                .map(|(_, v)| v.as_ref()),
+            None => self.attrs.iter().find(|(n, _)| *n == "xmlns").map(|(_, v)| v.as_ref()),
+        }
+    }
+
+    pub(crate) fn attr(&self, name: &str) -> Option<Cow<'a, str>> {
+        self.attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| v.clone())
+    }
+
+    pub(crate) fn required_attr(&self, name: &str) -> Result<Cow<'a, str>, ParseError> {
+        self.attr(name)
+            .ok_or_else(|| self.error(ParseErrorKind::MissingAttribute(name.to_string())))
+    }
+
+    pub(crate) fn text(&self) -> Cow<'a, str> {
+        self.text.clone()
+    }
+
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlNode<'a>> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub(crate) fn children<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s XmlNode<'a>> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    pub(crate) fn required_child(&self, name: &str) -> Result<&XmlNode<'a>, ParseError> {
+        self.child(name)
+            .ok_or_else(|| self.error(ParseErrorKind::MissingElement(name.to_string())))
+    }
+
+    pub(crate) fn child_text(&self, name: &str) -> Option<Cow<'a, str>> {
+        self.child(name).map(XmlNode::text)
+    }
+
+    pub(crate) fn required_child_text(&self, name: &str) -> Result<Cow<'a, str>, ParseError> {
+        self.required_child(name).map(XmlNode::text)
+    }
+
+    /// Parses the (trimmed) text of this element.
+    pub(crate) fn value<T: FromStr>(&self) -> Result<T, ParseError> {
+        parse_value(self, self.name, &self.text)
+    }
+
+    /// Parses the (trimmed) value of the given attribute.
+    pub(crate) fn attr_value<T: FromStr>(&self, name: &str) -> Result<Option<T>, ParseError> {
+        self.attr(name).map(|v| parse_value(self, name, &v)).transpose()
+    }
+
+    pub(crate) fn required_attr_value<T: FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        parse_value(self, name, &self.required_attr(name)?)
+    }
+
+    pub(crate) fn decimal(&self) -> Result<Decimal, ParseError> {
+        self.value()
+    }
+
+    pub(crate) fn child_decimal(&self, name: &str) -> Result<Option<Decimal>, ParseError> {
+        self.child(name).map(XmlNode::decimal).transpose()
+    }
+
+    pub(crate) fn required_child_decimal(&self, name: &str) -> Result<Decimal, ParseError> {
+        self.required_child(name)?.decimal()
+    }
+
+    pub(crate) fn child_from_xml<T: FromXml<'a>>(&self, name: &str) -> Result<Option<T>, ParseError> {
+        self.child(name).map(T::from_xml).transpose()
+    }
+
+    pub(crate) fn children_from_xml<T: FromXml<'a>>(&self, name: &str) -> Result<Vec<T>, ParseError> {
+        self.children(name).map(T::from_xml).collect()
+    }
+}
+
+fn parse_value<T: FromStr>(node: &XmlNode, name: &str, value: &str) -> Result<T, ParseError> {
+    value.trim().parse().map_err(|_| {
+        node.error(ParseErrorKind::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    })
+}
+
+/// Maps an empty list to `None`, like the `with_*` builders, which only create a list when pushing to it.
+pub(crate) fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+    if v.is_empty() { None } else { Some(v) }
+}
+
+pub(crate) trait FromXml<'a>: Sized {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError>;
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    line_starts: Vec<usize>,
+    elements: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Parser {
+            src,
+            pos: 0,
+            line_starts,
+            elements: 0,
+        }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&s| s <= offset);
+        let column = self.src[self.line_starts[line - 1]..offset].chars().count() + 1;
+        (line, column)
+    }
+
+    fn error_at(&self, offset: usize, kind: ParseErrorKind) -> ParseError {
+        let (line, column) = self.position(offset);
+        ParseError { line, column, kind }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\r', '\n']).len();
+    }
+
+    /// Skips everything up to and including the given delimiter.
+    fn skip_past(&mut self, delimiter: &str) -> Result<&'a str, ParseError> {
+        match self.rest().find(delimiter) {
+            Some(i) => {
+                let skipped = &self.rest()[..i];
+                self.pos += i + delimiter.len();
+                Ok(skipped)
+            }
+            None => Err(self.error_at(self.src.len(), ParseErrorKind::UnexpectedEof)),
+        }
+    }
+
+    fn expect(&mut self, s: &str, message: &'static str) -> Result<(), ParseError> {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else if self.rest().is_empty() {
+            Err(self.error_at(self.pos, ParseErrorKind::UnexpectedEof))
+        } else {
+            Err(self.error_at(self.pos, ParseErrorKind::Syntax(message)))
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<' | '"' | '\''))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error_at(self.pos, ParseErrorKind::Syntax("expected a name")));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Skips whitespace, comments and processing instructions.
+    fn skip_misc(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                return Err(self.error_at(self.pos, ParseErrorKind::Syntax("document type declarations aren't supported")));
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn unescape(&self, s: &'a str, offset: usize) -> Result<Cow<'a, str>, ParseError> {
+        if !s.contains('&') {
+            return Ok(Cow::Borrowed(s));
+        }
+
+        let mut o = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('&') {
+            o.push_str(&rest[..i]);
+            let entity_offset = offset + (s.len() - rest.len()) + i;
+            let end = rest[i..]
+                .find(';')
+                .ok_or_else(|| self.error_at(entity_offset, ParseErrorKind::Syntax("unterminated entity")))?;
+            let entity = &rest[i + 1..i + end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|dec| dec.parse().ok())
+                        .and_then(char::from_u32),
+                },
+            };
+            match c {
+                Some(c) => o.push(c),
+                None => return Err(self.error_at(entity_offset, ParseErrorKind::UnknownEntity(entity.to_string()))),
+            }
+            rest = &rest[i + end + 1..];
+        }
+        o.push_str(rest);
+
+        Ok(Cow::Owned(o))
+    }
+
+    fn element(&mut self) -> Result<XmlNode<'a>, ParseError> {
+        let (line, column) = self.position(self.pos);
+        self.expect("<", "expected an element")?;
+
+        let qualified_name = self.name()?;
+        let (prefix, name) = match qualified_name.split_once(':') {
+            Some((prefix, name)) => (Some(prefix), name),
+            None => (None, qualified_name),
+        };
+
+        let mut node = XmlNode {
+            name,
+            prefix,
+            attrs: Vec::new(),
+            children: Vec::new(),
+            text: Cow::Borrowed(""),
+            line,
+            column,
+            index: self.elements,
+        };
+        self.elements += 1;
+
+        // Attributes.
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(node);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let attr_name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=", "expected \"=\" after the attribute name")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q @ ('"' | '\'')) => q,
+                Some(_) => return Err(self.error_at(self.pos, ParseErrorKind::Syntax("expected a quoted value"))),
+                None => return Err(self.error_at(self.pos, ParseErrorKind::UnexpectedEof)),
+            };
+            self.pos += 1;
+            let value_offset = self.pos;
+            let value = self.skip_past(if quote == '"' { "\"" } else { "'" })?;
+            if node.attrs.iter().any(|(n, _)| *n == attr_name) {
+                return Err(self.error_at(value_offset, ParseErrorKind::Syntax("duplicate attribute")));
+            }
+            node.attrs.push((attr_name, self.unescape(value, value_offset)?));
+        }
+
+        // Content.
+        loop {
+            let text_offset = self.pos;
+            let text_len = self.rest().find('<').unwrap_or(self.rest().len());
+            if text_len > 0 {
+                let text = self.unescape(&self.rest()[..text_len], text_offset)?;
+                append(&mut node.text, text);
+                self.pos += text_len;
+            }
+
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error_at(self.pos, ParseErrorKind::UnexpectedEof));
+            } else if rest.starts_with("</") {
+                let end_offset = self.pos;
+                self.pos += 2;
+                let end_name = self.name()?;
+                if end_name != qualified_name {
+                    return Err(self.error_at(
+                        end_offset,
+                        ParseErrorKind::MismatchedEndTag {
+                            expected: qualified_name.to_string(),
+                            found: end_name.to_string(),
+                        },
+                    ));
+                }
+                self.skip_whitespace();
+                self.expect(">", "expected \">\"")?;
+                return Ok(node);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let cdata = self.skip_past("]]>")?;
+                append(&mut node.text, Cow::Borrowed(cdata));
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else {
+                node.children.push(self.element()?);
+            }
+        }
+    }
+}
+
+fn append<'a>(text: &mut Cow<'a, str>, t: Cow<'a, str>) {
+    if text.is_empty() {
+        *text = t;
+    } else {
+        text.to_mut().push_str(&t);
+    }
+}
+
+/// Parses the given XML document and returns its root element.
+pub(crate) fn parse_document(xml: &str) -> Result<XmlNode<'_>, ParseError> {
+    let mut parser = Parser::new(xml);
+
+    if parser.rest().starts_with('\u{feff}') {
+        parser.pos += '\u{feff}'.len_utf8();
+    }
+
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+
+    if !parser.rest().is_empty() {
+        return Err(parser.error_at(parser.pos, ParseErrorKind::Syntax("content after the root element")));
+    }
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_document() {
+        let root = parse_document(
+            "<?xml version=\"1.0\"?>\n<!-- comment -->\n<eb:a xmlns:eb=\"urn:x\" b='1 &amp; 2'><c>x&lt;y</c><c><![CDATA[<z>]]></c><d/></eb:a>\n",
+        )
+        .unwrap();
+
+        assert_eq!(root.name, "a");
+        assert_eq!(root.namespace(), Some("urn:x"));
+        assert_eq!(root.attr("b").as_deref(), Some("1 & 2"));
+        assert_eq!(root.children("c").map(|c| c.text()).collect::<Vec<_>>(), vec!["x<y", "<z>"]);
+        assert_eq!(root.required_child("d").unwrap().text(), "");
+        assert_eq!((root.line, root.column), (3, 1));
+        assert_eq!((root.children[2].line, root.children[2].column), (3, 73));
+    }
+
+    #[test]
+    fn unescapes_character_references() {
+        let root = parse_document("<a>&#228;&#xFC;</a>").unwrap();
+        assert_eq!(root.text(), "äü");
+    }
+
+    #[test]
+    fn reports_position_of_errors() {
+        assert_eq!(
+            parse_document("<a>\n  <b></c>\n</a>").unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 6,
+                kind: ParseErrorKind::MismatchedEndTag {
+                    expected: "b".to_string(),
+                    found: "c".to_string()
+                }
+            }
+        );
+        assert_eq!(
+            parse_document("<a>ü &foo;</a>").unwrap_err(),
+            ParseError {
+                line: 1,
+                column: 6,
+                kind: ParseErrorKind::UnknownEntity("foo".to_string())
+            }
+        );
+        assert_eq!(parse_document("<a>").unwrap_err().kind, ParseErrorKind::UnexpectedEof);
+        assert_eq!(
+            parse_document("<a></a><b/>").unwrap_err().kind,
+            ParseErrorKind::Syntax("content after the root element")
+        );
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let root = parse_document("<a>\n<b>1.2.3</b></a>").unwrap();
+        assert_eq!(
+            root.required_child_decimal("b").unwrap_err().to_string(),
+            "invalid value \"1.2.3\" of b at line 2, column 1"
+        );
+        assert_eq!(
+            root.required_child_decimal("c").unwrap_err().to_string(),
+            "missing element <c> at line 1, column 1"
+        );
+    }
+}
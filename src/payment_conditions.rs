@@ -0,0 +1,279 @@
+use rust_decimal::Decimal;
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    decimal::CloneAndRescale,
+    parse::{FromXml, ParseError, XmlNode},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
+};
+
+/// A discount for early payment (Skonto), e.g. 2% if paid within 14 days.
+///
+/// If only a percentage is given, the base amount defaults to the payable amount of the invoice
+/// and the discount amount gets calculated from it.
+#[derive(Debug, Default, PartialEq)]
+pub struct PaymentConditionsDiscount<'a> {
+    payment_date: Cow<'a, str>,
+    base_amount: Option<Decimal>,
+    percentage: Option<Decimal>,
+    amount: Option<Decimal>,
+}
+
+impl<'a> PaymentConditionsDiscount<'a> {
+    pub fn new(payment_date: impl Into<Cow<'a, str>>) -> Self {
+        PaymentConditionsDiscount {
+            payment_date: payment_date.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_base_amount(mut self, base_amount: Decimal) -> Self {
+        self.base_amount = Some(base_amount);
+        self
+    }
+
+    pub fn with_percentage(mut self, percentage: Decimal) -> Self {
+        self.percentage = Some(percentage);
+        self
+    }
+
+    pub fn with_amount(mut self, amount: Decimal) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn into_owned(self) -> PaymentConditionsDiscount<'static> {
+        PaymentConditionsDiscount {
+            payment_date: Cow::Owned(self.payment_date.into_owned()),
+            base_amount: self.base_amount,
+            percentage: self.percentage,
+            amount: self.amount,
+        }
+    }
+
+    fn write_xml(&self, w: &mut XmlWriter, payable_amount: Decimal) -> fmt::Result {
+        w.start("Discount")?;
+        w.text_element("PaymentDate", &self.payment_date)?;
+
+        let base_amount = match (self.base_amount, self.percentage, self.amount) {
+            (Some(base_amount), _, _) => Some(base_amount),
+            (None, Some(_), None) => Some(payable_amount),
+            _ => None,
+        };
+
+        if let Some(base_amount) = base_amount {
+            w.text_element("BaseAmount", w.round(base_amount))?;
+        }
+
+        if let Some(percentage) = self.percentage {
+            w.text_element("Percentage", percentage.clone_with_scale(2))?;
+        }
+
+        let amount = match (self.amount, base_amount, self.percentage) {
+            (Some(amount), _, _) => Some(amount),
+            (None, Some(base_amount), Some(percentage)) => Some(base_amount * percentage / Decimal::ONE_HUNDRED),
+            _ => None,
+        };
+
+        if let Some(amount) = amount {
+            w.text_element("Amount", w.round(amount))?;
+        }
+
+        w.end("Discount")
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PaymentConditions<'a> {
+    due_date: Cow<'a, str>,
+    discounts: Vec<PaymentConditionsDiscount<'a>>,
+    minimum_payment: Option<Decimal>,
+    comment: Option<Cow<'a, str>>,
+}
+
+impl<'a> PaymentConditions<'a> {
+    pub fn new(due_date: impl Into<Cow<'a, str>>) -> Self {
+        PaymentConditions {
+            due_date: due_date.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_discount(mut self, discount: PaymentConditionsDiscount<'a>) -> Self {
+        self.discounts.push(discount);
+        self
+    }
+
+    pub fn with_minimum_payment(mut self, minimum_payment: Decimal) -> Self {
+        self.minimum_payment = Some(minimum_payment);
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn into_owned(self) -> PaymentConditions<'static> {
+        PaymentConditions {
+            due_date: Cow::Owned(self.due_date.into_owned()),
+            discounts: self.discounts.into_iter().map(PaymentConditionsDiscount::into_owned).collect(),
+            minimum_payment: self.minimum_payment,
+            comment: self.comment.map(|c| Cow::Owned(c.into_owned())),
+        }
+    }
+
+    pub(crate) fn due_date(&self) -> &str {
+        &self.due_date
+    }
+
+    /// The comment and the discounts as text for UBL and CII, e.g. "2.00 % discount if paid by 2020-01-15".
+    pub(crate) fn note(&self, currency: &str) -> Option<String> {
+        let mut notes = Vec::new();
+        if let Some(comment) = &self.comment {
+            notes.push(comment.to_string());
+        }
+        for discount in &self.discounts {
+            match (discount.percentage, discount.amount) {
+                (Some(percentage), _) => notes.push(format!(
+                    "{} % discount if paid by {}",
+                    percentage.clone_with_scale(2),
+                    discount.payment_date
+                )),
+                (None, Some(amount)) => notes.push(format!(
+                    "{} {} discount if paid by {}",
+                    amount.clone_with_scale(2),
+                    currency,
+                    discount.payment_date
+                )),
+                (None, None) => {}
+            }
+        }
+        (!notes.is_empty()).then(|| notes.join("; "))
+    }
+
+    pub(crate) fn payable_amount(&self, payable_amount: Decimal) -> PaymentConditionsWithPayableAmount<'_> {
+        PaymentConditionsWithPayableAmount {
+            payment_conditions: self,
+            payable_amount,
+        }
+    }
+}
+
+/// Reads a `Discount` element. A base amount and amount derived from the payable amount are read as given.
+impl<'a> FromXml<'a> for PaymentConditionsDiscount<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(PaymentConditionsDiscount {
+            payment_date: node.required_child_text("PaymentDate")?,
+            base_amount: node.child_decimal("BaseAmount")?,
+            percentage: node.child_decimal("Percentage")?,
+            amount: node.child_decimal("Amount")?,
+        })
+    }
+}
+
+impl<'a> FromXml<'a> for PaymentConditions<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(PaymentConditions {
+            due_date: node.required_child_text("DueDate")?,
+            discounts: node.children_from_xml("Discount")?,
+            minimum_payment: node.child_decimal("MinimumPayment")?,
+            comment: node.child_text("Comment"),
+        })
+    }
+}
+
+pub(crate) struct PaymentConditionsWithPayableAmount<'a> {
+    payment_conditions: &'a PaymentConditions<'a>,
+    payable_amount: Decimal,
+}
+
+impl ToXml for PaymentConditionsWithPayableAmount<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("PaymentConditions")?;
+        w.text_element("DueDate", &self.payment_conditions.due_date)?;
+
+        for discount in &self.payment_conditions.discounts {
+            discount.write_xml(w, self.payable_amount)?;
+        }
+
+        if let Some(minimum_payment) = self.payment_conditions.minimum_payment {
+            w.text_element("MinimumPayment", w.round(minimum_payment))?;
+        }
+
+        if let Some(comment) = &self.payment_conditions.comment {
+            w.text_element("Comment", comment)?;
+        }
+
+        w.end("PaymentConditions")
+    }
+}
+
+/// Writes the note as UBL `PaymentTerms`. The due date is part of the invoice, the minimum payment gets dropped.
+impl ToUbl for PaymentConditions<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        if let Some(note) = self.note(w.currency()) {
+            w.start("cac:PaymentTerms")?;
+            w.text_element("cbc:Note", note)?;
+            w.end("cac:PaymentTerms")?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for PaymentConditionsDiscount<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.date("PaymentDate", &self.payment_date);
+
+        if let Some(percentage) = self.percentage {
+            v.percentage("Percentage", percentage);
+        }
+    }
+}
+
+impl Validate for PaymentConditions<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.date("DueDate", &self.due_date);
+        v.children("Discount", &self.discounts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculates_discount_amount_from_payable_amount() {
+        let result = PaymentConditions::new("2020-01-31")
+            .with_discount(PaymentConditionsDiscount::new("2020-01-15").with_percentage(Decimal::from(2)))
+            .with_comment("2% Skonto within 14 days")
+            .payable_amount(Decimal::new(122730, 2))
+            .to_xml();
+
+        assert_eq!(
+            result,
+            "<PaymentConditions><DueDate>2020-01-31</DueDate><Discount><PaymentDate>2020-01-15</PaymentDate><BaseAmount>1227.30</BaseAmount><Percentage>2.00</Percentage><Amount>24.55</Amount></Discount><Comment>2% Skonto within 14 days</Comment></PaymentConditions>",
+        );
+    }
+
+    #[test]
+    fn generates_multiple_discounts_and_minimum_payment() {
+        let result = PaymentConditions::new("2020-01-31")
+            .with_discount(
+                PaymentConditionsDiscount::new("2020-01-08")
+                    .with_base_amount(Decimal::from(1000))
+                    .with_percentage(Decimal::from(3)),
+            )
+            .with_discount(PaymentConditionsDiscount::new("2020-01-15").with_amount(Decimal::from(10)))
+            .with_minimum_payment(Decimal::from(100))
+            .payable_amount(Decimal::new(122730, 2))
+            .to_xml();
+
+        assert_eq!(
+            result,
+            "<PaymentConditions><DueDate>2020-01-31</DueDate><Discount><PaymentDate>2020-01-08</PaymentDate><BaseAmount>1000.00</BaseAmount><Percentage>3.00</Percentage><Amount>30.00</Amount></Discount><Discount><PaymentDate>2020-01-15</PaymentDate><Amount>10.00</Amount></Discount><MinimumPayment>100.00</MinimumPayment></PaymentConditions>",
+        );
+    }
+}
@@ -1,10 +1,22 @@
-use std::sync::LazyLock;
+use std::{borrow::Cow, fmt, str::FromStr, sync::LazyLock};
 
 use regex::Regex;
 
-use crate::xml::{ToXml, XmlElement};
-
-#[derive(Default)]
+use crate::{
+    bic::Bic,
+    cii::{CiiWriter, ToCii},
+    creditor_id::CreditorId,
+    error::Error,
+    iban::Iban,
+    parse::{FromXml, ParseError, ParseErrorKind, XmlNode, non_empty},
+    payment_reference::{CreditorReference, StuzzaPaymentReference},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
+};
+
+#[derive(Debug, Default, PartialEq)]
 enum PaymentMethodType<'a> {
     #[default]
     NoPayment,
@@ -17,40 +29,121 @@ enum PaymentMethodType<'a> {
     OtherPayment,
 }
 
+impl PaymentMethodType<'_> {
+    fn into_owned(self) -> PaymentMethodType<'static> {
+        match self {
+            PaymentMethodType::NoPayment => PaymentMethodType::NoPayment,
+            PaymentMethodType::SEPADirectDebit(p) => PaymentMethodType::SEPADirectDebit(p.into_owned()),
+            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => {
+                PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p.into_owned())
+            }
+            PaymentMethodType::UniversalBankTransaction(p) => {
+                PaymentMethodType::UniversalBankTransaction(p.into_owned())
+            }
+            PaymentMethodType::PaymentCard(p) => PaymentMethodType::PaymentCard(p.into_owned()),
+            PaymentMethodType::OtherPayment => PaymentMethodType::OtherPayment,
+        }
+    }
+}
+
 impl ToXml for PaymentMethodType<'_> {
-    fn to_xml(&self) -> String {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
         match self {
-            PaymentMethodType::NoPayment => XmlElement::new("NoPayment").to_xml(),
-            PaymentMethodType::SEPADirectDebit(p) => p.to_xml(),
-            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => p.to_xml(),
-            PaymentMethodType::UniversalBankTransaction(p) => p.to_xml(),
-            PaymentMethodType::PaymentCard(p) => p.to_xml(),
-            PaymentMethodType::OtherPayment => XmlElement::new("OtherPayment").to_xml(),
+            PaymentMethodType::NoPayment => {
+                w.start("NoPayment")?;
+                w.end("NoPayment")
+            }
+            PaymentMethodType::SEPADirectDebit(p) => p.write_xml(w),
+            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => p.write_xml(w),
+            PaymentMethodType::UniversalBankTransaction(p) => p.write_xml(w),
+            PaymentMethodType::PaymentCard(p) => p.write_xml(w),
+            PaymentMethodType::OtherPayment => {
+                w.start("OtherPayment")?;
+                w.end("OtherPayment")
+            }
         }
     }
 }
 
-#[derive(Default)]
-pub struct PaymentMethodSEPADirectDebit<'a> {
-    direct_debit_type: Option<&'a str>,
-    bic: Option<&'a str>,
-    iban: Option<&'a str>,
-    bank_account_owner: Option<&'a str>,
-    creditor_id: Option<&'a str>,
-    mandate_reference: Option<&'a str>,
-    debit_collection_date: Option<&'a str>,
+impl<'a> FromXml<'a> for PaymentMethodType<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(match node.name {
+            "NoPayment" => PaymentMethodType::NoPayment,
+            "SEPADirectDebit" => PaymentMethodType::SEPADirectDebit(PaymentMethodSEPADirectDebit::from_xml(node)?),
+            "BeneficiaryAccount" => PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(
+                PaymentMethodUniversalBankTransactionBeneficiaryAccount::from_xml(node)?,
+            ),
+            "UniversalBankTransaction" => {
+                PaymentMethodType::UniversalBankTransaction(PaymentMethodUniversalBankTransaction::from_xml(node)?)
+            }
+            "PaymentCard" => PaymentMethodType::PaymentCard(PaymentMethodPaymentCard::from_xml(node)?),
+            "OtherPayment" => PaymentMethodType::OtherPayment,
+            name => return Err(node.error(ParseErrorKind::UnexpectedElement(name.to_string()))),
+        })
+    }
+}
+
+impl Validate for PaymentMethodType<'_> {
+    fn validate(&self, v: &mut Validator) {
+        match self {
+            PaymentMethodType::SEPADirectDebit(p) => v.child("SEPADirectDebit", |v| p.validate(v)),
+            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => {
+                v.child("BeneficiaryAccount", |v| p.validate(v))
+            }
+            PaymentMethodType::UniversalBankTransaction(p) => v.child("UniversalBankTransaction", |v| p.validate(v)),
+            PaymentMethodType::PaymentCard(p) => v.child("PaymentCard", |v| p.validate(v)),
+            PaymentMethodType::NoPayment | PaymentMethodType::OtherPayment => {}
+        }
+    }
 }
 
-const BIC_REGEX_STR: &str = r"^[0-9A-Za-z]{8}([0-9A-Za-z]{3})?$";
+/// The SEPA direct debit scheme, `B2C` (core) if not given.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum SepaDirectDebitType {
+    #[default]
+    B2C,
+    B2B,
+}
 
-trait MatchBicRegex {
-    fn match_bic_regex(bic: &str) -> bool {
-        static BIC_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(BIC_REGEX_STR).unwrap());
-        !BIC_REGEX.is_match(bic)
+impl fmt::Display for SepaDirectDebitType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SepaDirectDebitType::B2C => "B2C",
+            SepaDirectDebitType::B2B => "B2B",
+        })
     }
 }
 
-impl MatchBicRegex for PaymentMethodSEPADirectDebit<'_> {}
+impl FromStr for SepaDirectDebitType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "B2C" => Ok(SepaDirectDebitType::B2C),
+            "B2B" => Ok(SepaDirectDebitType::B2B),
+            _ => Err(Error::UnknownValue {
+                field: "Type",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// The characters allowed in SEPA identifiers like the mandate reference: the Latin letters and digits and
+/// `/ - ? : ( ) . , ' +`, but no space. A `/` may neither start nor end the identifier nor follow another `/`.
+const MANDATE_REFERENCE_REGEX_STR: &str = r"^[0-9A-Za-z?:().,'+-]+(/[0-9A-Za-z?:().,'+-]+)*$";
+static MANDATE_REFERENCE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(MANDATE_REFERENCE_REGEX_STR).unwrap());
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PaymentMethodSEPADirectDebit<'a> {
+    direct_debit_type: Option<SepaDirectDebitType>,
+    bic: Option<Bic<'a>>,
+    iban: Option<Iban<'a>>,
+    bank_account_owner: Option<Cow<'a, str>>,
+    creditor_id: Option<CreditorId<'a>>,
+    mandate_reference: Option<Cow<'a, str>>,
+    debit_collection_date: Option<Cow<'a, str>>,
+}
 
 impl<'a> PaymentMethodSEPADirectDebit<'a> {
     pub fn new() -> Self {
@@ -59,48 +152,56 @@ impl<'a> PaymentMethodSEPADirectDebit<'a> {
         }
     }
 
-    pub fn with_direct_debit_type(mut self, direct_debit_type: &'a str) -> Self {
+    pub fn with_direct_debit_type(mut self, direct_debit_type: SepaDirectDebitType) -> Self {
         self.direct_debit_type = Some(direct_debit_type);
         self
     }
 
-    pub fn with_bic(mut self, bic: &'a str) -> Result<Self, String> {
-        if !PaymentMethodSEPADirectDebit::match_bic_regex(bic) {
-            return Err(format!("BIC {bic} doesn't match regex {BIC_REGEX_STR}!"));
-        }
+    pub fn with_bic(mut self, bic: Bic<'a>) -> Self {
         self.bic = Some(bic);
-        Ok(self)
+        self
     }
 
-    pub fn with_iban(mut self, iban: &'a str) -> Result<Self, String> {
-        if iban.len() > 34 {
-            return Err(format!("IBAN {iban} is too long!"));
-        }
+    pub fn with_iban(mut self, iban: Iban<'a>) -> Self {
         self.iban = Some(iban);
-        Ok(self)
+        self
     }
 
-    pub fn with_bank_account_owner(mut self, bank_account_owner: &'a str) -> Result<Self, String> {
-        if bank_account_owner.len() > 70 {
-            return Err(format!(
-                "BankAccountOwner {bank_account_owner} is too long!"
-            ));
+    pub fn with_bank_account_owner(mut self, bank_account_owner: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let bank_account_owner = bank_account_owner.into();
+        if bank_account_owner.chars().count() > 70 {
+            return Err(Error::TooLong {
+                field: "BankAccountOwner",
+                max: 70,
+                value: bank_account_owner.into_owned(),
+            });
         }
         self.bank_account_owner = Some(bank_account_owner);
         Ok(self)
     }
 
-    pub fn with_creditor_id(mut self, creditor_id: &'a str) -> Result<Self, String> {
-        if creditor_id.len() > 35 {
-            return Err(format!("CreditorID {creditor_id} is too long!"));
-        }
+    pub fn with_creditor_id(mut self, creditor_id: CreditorId<'a>) -> Self {
         self.creditor_id = Some(creditor_id);
-        Ok(self)
+        self
     }
 
-    pub fn with_mandate_reference(mut self, mandate_reference: &'a str) -> Result<Self, String> {
-        if mandate_reference.len() > 35 {
-            return Err(format!("MandateReference {mandate_reference} is too long!"));
+    /// Sets the reference of the mandate, up to 35 of the characters allowed by SEPA: the Latin letters and digits
+    /// and `/ - ? : ( ) . , ' +`, without a leading or trailing `/` and without `//`.
+    pub fn with_mandate_reference(mut self, mandate_reference: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let mandate_reference = mandate_reference.into();
+        if mandate_reference.chars().count() > 35 {
+            return Err(Error::TooLong {
+                field: "MandateReference",
+                max: 35,
+                value: mandate_reference.into_owned(),
+            });
+        }
+        if !MANDATE_REFERENCE_REGEX.is_match(&mandate_reference) {
+            return Err(Error::RegexMismatch {
+                field: "MandateReference",
+                pattern: MANDATE_REFERENCE_REGEX_STR,
+                value: mandate_reference.into_owned(),
+            });
         }
         self.mandate_reference = Some(mandate_reference);
         Ok(self)
@@ -108,81 +209,217 @@ impl<'a> PaymentMethodSEPADirectDebit<'a> {
 
     pub fn with_debit_collection_date(
         mut self,
-        debit_collection_date: &'a str,
-    ) -> Result<Self, String> {
+        debit_collection_date: impl Into<Cow<'a, str>>,
+    ) -> Result<Self, Error> {
+        let debit_collection_date = debit_collection_date.into();
         static DATE_REGEX_STR: &str = r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$";
         static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(DATE_REGEX_STR).unwrap());
-        if !DATE_REGEX.is_match(debit_collection_date) {
-            return Err(format!(
-                "DebitCollectionDate {debit_collection_date} doesn't match regex {DATE_REGEX_STR}!"
-            ));
+        if !DATE_REGEX.is_match(&debit_collection_date) {
+            return Err(Error::RegexMismatch {
+                field: "DebitCollectionDate",
+                pattern: DATE_REGEX_STR,
+                value: debit_collection_date.into_owned(),
+            });
         }
         self.debit_collection_date = Some(debit_collection_date);
         Ok(self)
     }
+
+    /// Creates a direct debit as read from UBL or CII, without validating it like the `with_*` builders do.
+    pub(crate) fn imported(
+        iban: Option<Cow<'a, str>>,
+        creditor_id: Option<Cow<'a, str>>,
+        mandate_reference: Option<Cow<'a, str>>,
+        debit_collection_date: Option<Cow<'a, str>>,
+    ) -> Self {
+        PaymentMethodSEPADirectDebit {
+            iban: iban.map(Iban::unchecked),
+            creditor_id: creditor_id.map(CreditorId::unchecked),
+            mandate_reference,
+            debit_collection_date,
+            ..Default::default()
+        }
+    }
+
+    pub fn into_owned(self) -> PaymentMethodSEPADirectDebit<'static> {
+        PaymentMethodSEPADirectDebit {
+            direct_debit_type: self.direct_debit_type,
+            bic: self.bic.map(Bic::into_owned),
+            iban: self.iban.map(Iban::into_owned),
+            bank_account_owner: self.bank_account_owner.map(|v| Cow::Owned(v.into_owned())),
+            creditor_id: self.creditor_id.map(CreditorId::into_owned),
+            mandate_reference: self.mandate_reference.map(|v| Cow::Owned(v.into_owned())),
+            debit_collection_date: self.debit_collection_date.map(|v| Cow::Owned(v.into_owned())),
+        }
+    }
 }
 
 impl ToXml for PaymentMethodSEPADirectDebit<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("SEPADirectDebit");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("SEPADirectDebit")?;
 
-        e = e.with_text_element("Type", self.direct_debit_type.unwrap_or("B2C"));
+        w.text_element("Type", self.direct_debit_type.unwrap_or_default())?;
+
+        if let Some(bic) = &self.bic {
+            w.text_element("BIC", bic)?;
+        }
 
-        if let Some(bic) = self.bic {
-            e = e.with_text_element("BIC", bic);
+        if let Some(iban) = &self.iban {
+            w.text_element("IBAN", iban)?;
         }
 
-        if let Some(iban) = self.iban {
-            e = e.with_text_element("IBAN", iban);
+        if let Some(bank_account_owner) = &self.bank_account_owner {
+            w.text_element("BankAccountOwner", bank_account_owner)?;
         }
 
-        if let Some(bank_account_owner) = self.bank_account_owner {
-            e = e.with_text_element("BankAccountOwner", bank_account_owner);
+        if let Some(creditor_id) = &self.creditor_id {
+            w.text_element("CreditorID", creditor_id)?;
         }
 
-        if let Some(creditor_id) = self.creditor_id {
-            e = e.with_text_element("CreditorID", creditor_id);
+        if let Some(mandate_reference) = &self.mandate_reference {
+            w.text_element("MandateReference", mandate_reference)?;
         }
 
-        if let Some(mandate_reference) = self.mandate_reference {
-            e = e.with_text_element("MandateReference", mandate_reference);
+        if let Some(debit_collection_date) = &self.debit_collection_date {
+            w.text_element("DebitCollectionDate", debit_collection_date)?;
+        }
+
+        w.end("SEPADirectDebit")
+    }
+}
+
+/// Writes a UBL `PaymentMeans` with code `59` (SEPA direct debit). The debit collection date becomes the
+/// `PaymentDueDate`, the mandate reference and the IBAN of the debtor the `PaymentMandate`.
+impl ToUbl for PaymentMethodSEPADirectDebit<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:PaymentMeans")?;
+        w.text_element("cbc:PaymentMeansCode", "59")?;
+
+        if let Some(debit_collection_date) = &self.debit_collection_date {
+            w.text_element("cbc:PaymentDueDate", debit_collection_date)?;
+        }
+
+        if self.mandate_reference.is_some() || self.iban.is_some() {
+            w.start("cac:PaymentMandate")?;
+
+            if let Some(mandate_reference) = &self.mandate_reference {
+                w.text_element("cbc:ID", mandate_reference)?;
+            }
+
+            if let Some(iban) = &self.iban {
+                w.start("cac:PayerFinancialAccount")?;
+                w.text_element("cbc:ID", iban)?;
+                w.end("cac:PayerFinancialAccount")?;
+            }
+
+            w.end("cac:PaymentMandate")?;
         }
 
-        if let Some(debit_collection_date) = self.debit_collection_date {
-            e = e.with_text_element("DebitCollectionDate", debit_collection_date);
+        w.end("cac:PaymentMeans")
+    }
+}
+
+/// Writes a CII `SpecifiedTradeSettlementPaymentMeans` with code `59` (SEPA direct debit) and the IBAN of the
+/// debtor. The creditor ID and the mandate reference are written by the invoice, the debit collection date gets
+/// dropped.
+impl ToCii for PaymentMethodSEPADirectDebit<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:SpecifiedTradeSettlementPaymentMeans")?;
+        w.text_element("ram:TypeCode", "59")?;
+
+        if let Some(iban) = &self.iban {
+            w.start("ram:PayerPartyDebtorFinancialAccount")?;
+            w.text_element("ram:IBANID", iban)?;
+            w.end("ram:PayerPartyDebtorFinancialAccount")?;
         }
 
-        e.to_xml()
+        w.end("ram:SpecifiedTradeSettlementPaymentMeans")
+    }
+}
+
+/// Reads a `SEPADirectDebit` element as given, without validating it like the `with_*` builders do.
+impl<'a> FromXml<'a> for PaymentMethodSEPADirectDebit<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(PaymentMethodSEPADirectDebit {
+            direct_debit_type: node.child("Type").map(XmlNode::value).transpose()?,
+            bic: node.child_text("BIC").map(Bic::unchecked),
+            iban: node.child_text("IBAN").map(Iban::unchecked),
+            bank_account_owner: node.child_text("BankAccountOwner"),
+            creditor_id: node.child_text("CreditorID").map(CreditorId::unchecked),
+            mandate_reference: node.child_text("MandateReference"),
+            debit_collection_date: node.child_text("DebitCollectionDate"),
+        })
+    }
+}
+
+impl Validate for PaymentMethodSEPADirectDebit<'_> {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(bic) = &self.bic
+            && let Err(error) = bic.check()
+        {
+            v.error("BIC", error);
+        }
+
+        if let Some(iban) = &self.iban
+            && let Err(error) = iban.check()
+        {
+            v.error("IBAN", error);
+        }
+
+        if let Some(bank_account_owner) = &self.bank_account_owner {
+            v.max_length("BankAccountOwner", bank_account_owner, 70);
+        }
+
+        if let Some(creditor_id) = &self.creditor_id
+            && let Err(error) = creditor_id.check()
+        {
+            v.error("CreditorID", error);
+        }
+
+        if let Some(mandate_reference) = &self.mandate_reference {
+            v.max_length("MandateReference", mandate_reference, 35);
+            v.pattern("MandateReference", mandate_reference, MANDATE_REFERENCE_REGEX_STR, &MANDATE_REFERENCE_REGEX);
+        }
+
+        if let Some(debit_collection_date) = &self.debit_collection_date {
+            v.date("DebitCollectionDate", debit_collection_date);
+        }
     }
 }
 
 /// - bank_code_type: ISO 3166-1 Code
+#[derive(Debug, PartialEq)]
 pub struct PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode<'a> {
     bank_code: i64,
-    bank_code_type: &'a str,
+    bank_code_type: Cow<'a, str>,
 }
 
 impl<'a> PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode<'a> {
-    pub fn new(bank_code: i64, bank_code_type: &'a str) -> Self {
+    pub fn new(bank_code: i64, bank_code_type: impl Into<Cow<'a, str>>) -> Self {
         PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode {
             bank_code,
-            bank_code_type,
+            bank_code_type: bank_code_type.into(),
+        }
+    }
+
+    pub fn into_owned(self) -> PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode<'static> {
+        PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode {
+            bank_code: self.bank_code,
+            bank_code_type: Cow::Owned(self.bank_code_type.into_owned()),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a> {
-    bank_name: Option<&'a str>,
+    bank_name: Option<Cow<'a, str>>,
     bank_code: Option<PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode<'a>>,
-    bic: Option<&'a str>,
-    bank_account_number: Option<&'a str>,
-    iban: Option<&'a str>,
-    bank_account_owner: Option<&'a str>,
+    bic: Option<Bic<'a>>,
+    bank_account_number: Option<Cow<'a, str>>,
+    iban: Option<Iban<'a>>,
+    bank_account_owner: Option<Cow<'a, str>>,
 }
 
-impl MatchBicRegex for PaymentMethodUniversalBankTransactionBeneficiaryAccount<'_> {}
-
 impl<'a> PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a> {
     pub fn new() -> Self {
         PaymentMethodUniversalBankTransactionBeneficiaryAccount {
@@ -190,9 +427,14 @@ impl<'a> PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a> {
         }
     }
 
-    pub fn with_bank_name(mut self, bank_name: &'a str) -> Result<Self, String> {
-        if bank_name.len() > 255 {
-            return Err(format!("BankName {bank_name} is too long!"));
+    pub fn with_bank_name(mut self, bank_name: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let bank_name = bank_name.into();
+        if bank_name.chars().count() > 255 {
+            return Err(Error::TooLong {
+                field: "BankName",
+                max: 255,
+                value: bank_name.into_owned(),
+            });
         }
         self.bank_name = Some(bank_name);
         Ok(self)
@@ -201,91 +443,236 @@ impl<'a> PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a> {
     pub fn with_bank_code(
         mut self,
         bank_code: PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode<'a>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, Error> {
         if bank_code.bank_code_type.len() != 2 {
-            return Err(format!(
-                "BankCodeType {} is not 2 characters long!",
-                bank_code.bank_code_type
-            ));
+            return Err(Error::InvalidLength {
+                field: "BankCodeType",
+                length: 2,
+                value: bank_code.bank_code_type.into_owned(),
+            });
         }
         self.bank_code = Some(bank_code);
         Ok(self)
     }
 
-    pub fn with_bic(mut self, bic: &'a str) -> Result<Self, String> {
-        if !PaymentMethodUniversalBankTransactionBeneficiaryAccount::match_bic_regex(bic) {
-            return Err(format!("BIC {bic} doesn't match regex {BIC_REGEX_STR}!"));
-        }
+    pub fn with_bic(mut self, bic: Bic<'a>) -> Self {
         self.bic = Some(bic);
-        Ok(self)
+        self
     }
 
-    pub fn with_bank_account_number(mut self, bank_account_number: &'a str) -> Self {
-        self.bank_account_number = Some(bank_account_number);
+    pub fn with_bank_account_number(mut self, bank_account_number: impl Into<Cow<'a, str>>) -> Self {
+        self.bank_account_number = Some(bank_account_number.into());
         self
     }
 
-    pub fn with_iban(mut self, iban: &'a str) -> Result<Self, String> {
-        if iban.len() > 34 {
-            return Err(format!("IBAN {iban} is too long!"));
-        }
+    pub fn with_iban(mut self, iban: Iban<'a>) -> Self {
         self.iban = Some(iban);
-        Ok(self)
+        self
     }
 
-    pub fn with_bank_account_owner(mut self, bank_account_owner: &'a str) -> Result<Self, String> {
-        if bank_account_owner.len() > 70 {
-            return Err(format!(
-                "BankAccountOwner {bank_account_owner} is too long!"
-            ));
+    pub fn with_bank_account_owner(mut self, bank_account_owner: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let bank_account_owner = bank_account_owner.into();
+        if bank_account_owner.chars().count() > 70 {
+            return Err(Error::TooLong {
+                field: "BankAccountOwner",
+                max: 70,
+                value: bank_account_owner.into_owned(),
+            });
         }
         self.bank_account_owner = Some(bank_account_owner);
         Ok(self)
     }
+
+    /// Creates an account as read from UBL or CII, without validating it like the `with_*` builders do.
+    pub(crate) fn imported(
+        iban: Option<Cow<'a, str>>,
+        bank_account_number: Option<Cow<'a, str>>,
+        bic: Option<Cow<'a, str>>,
+        bank_account_owner: Option<Cow<'a, str>>,
+    ) -> Self {
+        PaymentMethodUniversalBankTransactionBeneficiaryAccount {
+            iban: iban.map(Iban::unchecked),
+            bank_account_number,
+            bic: bic.map(Bic::unchecked),
+            bank_account_owner,
+            ..Default::default()
+        }
+    }
+
+    pub fn into_owned(self) -> PaymentMethodUniversalBankTransactionBeneficiaryAccount<'static> {
+        PaymentMethodUniversalBankTransactionBeneficiaryAccount {
+            bank_name: self.bank_name.map(|v| Cow::Owned(v.into_owned())),
+            bank_code: self
+                .bank_code
+                .map(PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode::into_owned),
+            bic: self.bic.map(Bic::into_owned),
+            bank_account_number: self.bank_account_number.map(|v| Cow::Owned(v.into_owned())),
+            iban: self.iban.map(Iban::into_owned),
+            bank_account_owner: self.bank_account_owner.map(|v| Cow::Owned(v.into_owned())),
+        }
+    }
 }
 
 impl ToXml for PaymentMethodUniversalBankTransactionBeneficiaryAccount<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("BeneficiaryAccount");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("BeneficiaryAccount")?;
 
-        if let Some(bank_name) = self.bank_name {
-            e = e.with_text_element("BankName", bank_name);
+        if let Some(bank_name) = &self.bank_name {
+            w.text_element("BankName", bank_name)?;
         }
 
         if let Some(bank_code) = &self.bank_code {
-            let bank_code_xml_element = XmlElement::new("BankCode")
-                .with_text(format!("{}", bank_code.bank_code))
-                .with_attr("BankCodeType", bank_code.bank_code_type);
+            w.start("BankCode")?;
+            w.attr("BankCodeType", &bank_code.bank_code_type)?;
+            w.text(bank_code.bank_code)?;
+            w.end("BankCode")?;
+        }
 
-            e = e.with_element(&bank_code_xml_element);
+        if let Some(bic) = &self.bic {
+            w.text_element("BIC", bic)?;
         }
 
-        if let Some(bic) = self.bic {
-            e = e.with_text_element("BIC", bic);
+        if let Some(bank_account_number) = &self.bank_account_number {
+            w.text_element("BankAccountNr", bank_account_number)?;
         }
 
-        if let Some(bank_account_number) = self.bank_account_number {
-            e = e.with_text_element("BankAccountNr", bank_account_number);
+        if let Some(iban) = &self.iban {
+            w.text_element("IBAN", iban)?;
         }
 
-        if let Some(iban) = self.iban {
-            e = e.with_text_element("IBAN", iban);
+        if let Some(bank_account_owner) = &self.bank_account_owner {
+            w.text_element("BankAccountOwner", bank_account_owner)?;
         }
 
-        if let Some(bank_account_owner) = self.bank_account_owner {
-            e = e.with_text_element("BankAccountOwner", bank_account_owner);
+        w.end("BeneficiaryAccount")
+    }
+}
+
+impl PaymentMethodUniversalBankTransactionBeneficiaryAccount<'_> {
+    /// Writes a UBL `PaymentMeans` with code `58` (SEPA credit transfer), or `30` (credit transfer) for accounts
+    /// without IBAN.
+    fn write_ubl(&self, w: &mut UblWriter, payment_reference: Option<&str>) -> fmt::Result {
+        w.start("cac:PaymentMeans")?;
+        w.text_element("cbc:PaymentMeansCode", if self.iban.is_some() { "58" } else { "30" })?;
+
+        if let Some(payment_reference) = payment_reference {
+            w.text_element("cbc:PaymentID", payment_reference)?;
         }
 
-        e.to_xml()
+        if let Some(id) = self.iban.as_ref().map(Iban::as_str).or(self.bank_account_number.as_deref()) {
+            w.start("cac:PayeeFinancialAccount")?;
+            w.text_element("cbc:ID", id)?;
+
+            if let Some(bank_account_owner) = &self.bank_account_owner {
+                w.text_element("cbc:Name", bank_account_owner)?;
+            }
+
+            if let Some(bic) = &self.bic {
+                w.start("cac:FinancialInstitutionBranch")?;
+                w.text_element("cbc:ID", bic)?;
+                w.end("cac:FinancialInstitutionBranch")?;
+            }
+
+            w.end("cac:PayeeFinancialAccount")?;
+        }
+
+        w.end("cac:PaymentMeans")
     }
 }
 
-#[derive(Default)]
+/// Writes a CII `SpecifiedTradeSettlementPaymentMeans` with the codes of the UBL `PaymentMeans`. The payment
+/// reference is written by the invoice.
+impl ToCii for PaymentMethodUniversalBankTransactionBeneficiaryAccount<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:SpecifiedTradeSettlementPaymentMeans")?;
+        w.text_element("ram:TypeCode", if self.iban.is_some() { "58" } else { "30" })?;
+
+        if self.iban.is_some() || self.bank_account_number.is_some() {
+            w.start("ram:PayeePartyCreditorFinancialAccount")?;
+
+            if let Some(iban) = &self.iban {
+                w.text_element("ram:IBANID", iban)?;
+            }
+
+            if let Some(bank_account_owner) = &self.bank_account_owner {
+                w.text_element("ram:AccountName", bank_account_owner)?;
+            }
+
+            if self.iban.is_none()
+                && let Some(bank_account_number) = &self.bank_account_number
+            {
+                w.text_element("ram:ProprietaryID", bank_account_number)?;
+            }
+
+            w.end("ram:PayeePartyCreditorFinancialAccount")?;
+        }
+
+        if let Some(bic) = &self.bic {
+            w.start("ram:PayeeSpecifiedCreditorFinancialInstitution")?;
+            w.text_element("ram:BICID", bic)?;
+            w.end("ram:PayeeSpecifiedCreditorFinancialInstitution")?;
+        }
+
+        w.end("ram:SpecifiedTradeSettlementPaymentMeans")
+    }
+}
+
+/// Reads a `BeneficiaryAccount` element as given, without validating it like the `with_*` builders do.
+impl<'a> FromXml<'a> for PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let bank_code = match node.child("BankCode") {
+            Some(bank_code) => Some(PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode {
+                bank_code: bank_code.value()?,
+                bank_code_type: bank_code.required_attr("BankCodeType")?,
+            }),
+            None => None,
+        };
+
+        Ok(PaymentMethodUniversalBankTransactionBeneficiaryAccount {
+            bank_name: node.child_text("BankName"),
+            bank_code,
+            bic: node.child_text("BIC").map(Bic::unchecked),
+            bank_account_number: node.child_text("BankAccountNr"),
+            iban: node.child_text("IBAN").map(Iban::unchecked),
+            bank_account_owner: node.child_text("BankAccountOwner"),
+        })
+    }
+}
+
+impl Validate for PaymentMethodUniversalBankTransactionBeneficiaryAccount<'_> {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(bank_name) = &self.bank_name {
+            v.max_length("BankName", bank_name, 255);
+        }
+
+        if let Some(bank_code) = &self.bank_code {
+            v.country_code("BankCode/@BankCodeType", &bank_code.bank_code_type);
+        }
+
+        if let Some(bic) = &self.bic
+            && let Err(error) = bic.check()
+        {
+            v.error("BIC", error);
+        }
+
+        if let Some(iban) = &self.iban
+            && let Err(error) = iban.check()
+        {
+            v.error("IBAN", error);
+        }
+
+        if let Some(bank_account_owner) = &self.bank_account_owner {
+            v.max_length("BankAccountOwner", bank_account_owner, 70);
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct PaymentMethodUniversalBankTransaction<'a> {
     consolidator_payable: Option<bool>,
     beneficiary_account: Option<Vec<PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a>>>,
-    payment_reference: Option<&'a str>,
-    payment_reference_checksum: Option<&'a str>,
+    payment_reference: Option<Cow<'a, str>>,
+    payment_reference_checksum: Option<Cow<'a, str>>,
 }
 
 impl<'a> PaymentMethodUniversalBankTransaction<'a> {
@@ -310,67 +697,201 @@ impl<'a> PaymentMethodUniversalBankTransaction<'a> {
         self
     }
 
-    pub fn with_payment_reference(mut self, payment_reference: &'a str) -> Result<Self, String> {
-        if payment_reference.len() > 35 {
-            return Err(format!("PaymentReference {payment_reference} is too long!"));
+    pub fn with_payment_reference(mut self, payment_reference: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let payment_reference = payment_reference.into();
+        if payment_reference.chars().count() > 35 {
+            return Err(Error::TooLong {
+                field: "PaymentReference",
+                max: 35,
+                value: payment_reference.into_owned(),
+            });
         }
         self.payment_reference = Some(payment_reference);
         Ok(self)
     }
 
-    pub fn with_payment_reference_checksum(mut self, payment_reference_checksum: &'a str) -> Self {
-        self.payment_reference_checksum = Some(payment_reference_checksum);
+    pub fn with_payment_reference_checksum(mut self, payment_reference_checksum: impl Into<Cow<'a, str>>) -> Self {
+        self.payment_reference_checksum = Some(payment_reference_checksum.into());
+        self
+    }
+
+    /// Sets an Austrian payment reference, e.g. from `StuzzaPaymentReference::from_invoice_number`, with its check
+    /// digit as `CheckSum`.
+    pub fn with_stuzza_payment_reference(mut self, payment_reference: StuzzaPaymentReference<'a>) -> Self {
+        self.payment_reference_checksum = Some(Cow::Owned(payment_reference.check_digit().to_string()));
+        self.payment_reference = Some(payment_reference.into());
+        self
+    }
+
+    /// Sets an ISO 11649 creditor reference, e.g. from `CreditorReference::from_invoice_number`, with its check
+    /// digits as `CheckSum`.
+    pub fn with_creditor_reference(mut self, creditor_reference: CreditorReference<'a>) -> Self {
+        self.payment_reference_checksum = Some(Cow::Owned(creditor_reference.check_digits().to_string()));
+        self.payment_reference = Some(creditor_reference.into());
         self
     }
+
+    /// Creates a bank transaction as read from UBL or CII, without validating it like the `with_*` builders do.
+    pub(crate) fn imported(
+        beneficiary_accounts: Vec<PaymentMethodUniversalBankTransactionBeneficiaryAccount<'a>>,
+        payment_reference: Option<Cow<'a, str>>,
+    ) -> Self {
+        PaymentMethodUniversalBankTransaction {
+            beneficiary_account: non_empty(beneficiary_accounts),
+            payment_reference,
+            ..Default::default()
+        }
+    }
+
+    pub fn into_owned(self) -> PaymentMethodUniversalBankTransaction<'static> {
+        PaymentMethodUniversalBankTransaction {
+            consolidator_payable: self.consolidator_payable,
+            beneficiary_account: self.beneficiary_account.map(|accounts| {
+                accounts
+                    .into_iter()
+                    .map(PaymentMethodUniversalBankTransactionBeneficiaryAccount::into_owned)
+                    .collect()
+            }),
+            payment_reference: self.payment_reference.map(|v| Cow::Owned(v.into_owned())),
+            payment_reference_checksum: self.payment_reference_checksum.map(|v| Cow::Owned(v.into_owned())),
+        }
+    }
 }
 
 impl ToXml for PaymentMethodUniversalBankTransaction<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("UniversalBankTransaction");
-
-        e = e.with_attr(
-            "ConsolidatorPayable",
-            format!("{}", self.consolidator_payable.unwrap_or(false)),
-        );
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("UniversalBankTransaction")?;
+        w.attr("ConsolidatorPayable", self.consolidator_payable.unwrap_or(false))?;
 
         if let Some(beneficiary_account) = &self.beneficiary_account {
             for account in beneficiary_account {
-                e = e.with_element(account);
+                w.element(account)?;
             }
         }
 
-        if let Some(payment_reference) = self.payment_reference {
-            let mut payment_reference_xml_element =
-                XmlElement::new("PaymentReference").with_text(payment_reference);
+        if let Some(payment_reference) = &self.payment_reference {
+            w.start("PaymentReference")?;
+            if let Some(payment_reference_checksum) = &self.payment_reference_checksum {
+                w.attr("CheckSum", payment_reference_checksum)?;
+            }
+            w.text(payment_reference)?;
+            w.end("PaymentReference")?;
+        }
 
-            if let Some(payment_reference_checksum) = self.payment_reference_checksum {
-                payment_reference_xml_element =
-                    payment_reference_xml_element.with_attr("CheckSum", payment_reference_checksum);
+        w.end("UniversalBankTransaction")
+    }
+}
+
+/// Writes a UBL `PaymentMeans` per beneficiary account, each with the payment reference as `PaymentID`.
+impl ToUbl for PaymentMethodUniversalBankTransaction<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        let payment_reference = self.payment_reference.as_deref();
+
+        match &self.beneficiary_account {
+            Some(beneficiary_account) if !beneficiary_account.is_empty() => {
+                for account in beneficiary_account {
+                    account.write_ubl(w, payment_reference)?;
+                }
+                Ok(())
             }
+            _ => PaymentMethodUniversalBankTransactionBeneficiaryAccount::new().write_ubl(w, payment_reference),
+        }
+    }
+}
+
+/// Writes a CII `SpecifiedTradeSettlementPaymentMeans` per beneficiary account.
+impl ToCii for PaymentMethodUniversalBankTransaction<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        match &self.beneficiary_account {
+            Some(beneficiary_account) if !beneficiary_account.is_empty() => {
+                for account in beneficiary_account {
+                    w.element(account)?;
+                }
+                Ok(())
+            }
+            _ => w.element(&PaymentMethodUniversalBankTransactionBeneficiaryAccount::new()),
+        }
+    }
+}
+
+impl<'a> FromXml<'a> for PaymentMethodUniversalBankTransaction<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let payment_reference = node.child("PaymentReference");
+
+        Ok(PaymentMethodUniversalBankTransaction {
+            consolidator_payable: node.attr_value("ConsolidatorPayable")?,
+            beneficiary_account: non_empty(node.children_from_xml("BeneficiaryAccount")?),
+            payment_reference: payment_reference.map(XmlNode::text),
+            payment_reference_checksum: payment_reference.and_then(|p| p.attr("CheckSum")),
+        })
+    }
+}
 
-            e = e.with_element(&payment_reference_xml_element);
+impl Validate for PaymentMethodUniversalBankTransaction<'_> {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(beneficiary_account) = &self.beneficiary_account {
+            v.children("BeneficiaryAccount", beneficiary_account);
         }
 
-        e.to_xml()
+        if let Some(payment_reference) = &self.payment_reference {
+            v.max_length("PaymentReference", payment_reference, 35);
+
+            // Creditor references are recognised by their prefix. Digits only could be anything, so they are only
+            // taken as a Stuzza payment reference, if they come with a `CheckSum`.
+            let check_digits = if CreditorReference::is_creditor_reference(payment_reference) {
+                let creditor_reference = CreditorReference::unchecked(payment_reference);
+                if let Err(error) = creditor_reference.check() {
+                    v.error("PaymentReference", error);
+                }
+                Some(creditor_reference.check_digits().to_string())
+            } else if self.payment_reference_checksum.is_some()
+                && StuzzaPaymentReference::is_stuzza_payment_reference(payment_reference)
+            {
+                let stuzza_payment_reference = StuzzaPaymentReference::unchecked(payment_reference);
+                if let Err(error) = stuzza_payment_reference.check() {
+                    v.error("PaymentReference", error);
+                }
+                Some(stuzza_payment_reference.check_digit().to_string())
+            } else {
+                None
+            };
+
+            if let Some(expected) = check_digits
+                && let Some(checksum) = &self.payment_reference_checksum
+                && *checksum != expected
+            {
+                v.error(
+                    "PaymentReference/@CheckSum",
+                    Error::ChecksumMismatch {
+                        field: "CheckSum",
+                        expected,
+                        value: checksum.to_string(),
+                    },
+                );
+            }
+        }
     }
 }
 
+const PAYMENT_CARD_REGEX_STR: &str = r"^[0-9]{0,6}\*[0-9]{0,4}$";
+pub(crate) static PAYMENT_CARD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(PAYMENT_CARD_REGEX_STR).unwrap());
+
 /// - primary_account_number: Only provide at most the first 6 and last 4 digits, separated with a "*".
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct PaymentMethodPaymentCard<'a> {
-    primary_account_number: &'a str,
-    card_holder_name: Option<&'a str>,
+    primary_account_number: Cow<'a, str>,
+    card_holder_name: Option<Cow<'a, str>>,
 }
 
 impl<'a> PaymentMethodPaymentCard<'a> {
-    pub fn new(primary_account_number: &'a str) -> Result<Self, String> {
-        static PAYMENT_CARD_REGEX_STR: &str = r"^[0-9]{0,6}\*[0-9]{0,4}$";
-        static PAYMENT_CARD_REGEX: LazyLock<Regex> =
-            LazyLock::new(|| Regex::new(PAYMENT_CARD_REGEX_STR).unwrap());
-        if !PAYMENT_CARD_REGEX.is_match(primary_account_number) {
-            return Err(format!(
-                "Invalid primary account number \"{primary_account_number}\". Only provide at most the first 6 and last 4 digits, separated with a \"*\".",
-            ));
+    pub fn new(primary_account_number: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let primary_account_number = primary_account_number.into();
+        if !PAYMENT_CARD_REGEX.is_match(&primary_account_number) {
+            return Err(Error::RegexMismatch {
+                field: "PrimaryAccountNumber",
+                pattern: PAYMENT_CARD_REGEX_STR,
+                value: primary_account_number.into_owned(),
+            });
         }
         Ok(PaymentMethodPaymentCard {
             primary_account_number,
@@ -378,29 +899,100 @@ impl<'a> PaymentMethodPaymentCard<'a> {
         })
     }
 
-    pub fn with_card_holder_name(mut self, card_holder_name: &'a str) -> Self {
-        self.card_holder_name = Some(card_holder_name);
+    pub fn with_card_holder_name(mut self, card_holder_name: impl Into<Cow<'a, str>>) -> Self {
+        self.card_holder_name = Some(card_holder_name.into());
         self
     }
+
+    /// Creates a payment card as read from UBL or CII, without validating it like `new` does.
+    pub(crate) fn imported(primary_account_number: Cow<'a, str>, card_holder_name: Option<Cow<'a, str>>) -> Self {
+        PaymentMethodPaymentCard {
+            primary_account_number,
+            card_holder_name,
+        }
+    }
+
+    pub fn into_owned(self) -> PaymentMethodPaymentCard<'static> {
+        PaymentMethodPaymentCard {
+            primary_account_number: Cow::Owned(self.primary_account_number.into_owned()),
+            card_holder_name: self.card_holder_name.map(|v| Cow::Owned(v.into_owned())),
+        }
+    }
 }
 
 impl ToXml for PaymentMethodPaymentCard<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("PaymentCard");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("PaymentCard")?;
+
+        w.text_element("PrimaryAccountNumber", &self.primary_account_number)?;
+
+        if let Some(card_holder_name) = &self.card_holder_name {
+            w.text_element("CardHolderName", card_holder_name)?;
+        }
+
+        w.end("PaymentCard")
+    }
+}
+
+/// Writes a UBL `PaymentMeans` with code `48` (bank card). ebInterface doesn't state the card network, so the
+/// required `NetworkID` is `ZZZ` (mutually defined).
+impl ToUbl for PaymentMethodPaymentCard<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:PaymentMeans")?;
+        w.text_element("cbc:PaymentMeansCode", "48")?;
+        w.start("cac:CardAccount")?;
+        w.text_element("cbc:PrimaryAccountNumberID", &self.primary_account_number)?;
+        w.text_element("cbc:NetworkID", "ZZZ")?;
+
+        if let Some(card_holder_name) = &self.card_holder_name {
+            w.text_element("cbc:HolderName", card_holder_name)?;
+        }
+
+        w.end("cac:CardAccount")?;
+        w.end("cac:PaymentMeans")
+    }
+}
 
-        e = e.with_text_element("PrimaryAccountNumber", self.primary_account_number);
+/// Writes a CII `SpecifiedTradeSettlementPaymentMeans` with code `48` (bank card).
+impl ToCii for PaymentMethodPaymentCard<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:SpecifiedTradeSettlementPaymentMeans")?;
+        w.text_element("ram:TypeCode", "48")?;
+        w.start("ram:ApplicableTradeSettlementFinancialCard")?;
+        w.text_element("ram:ID", &self.primary_account_number)?;
 
-        if let Some(card_holder_name) = self.card_holder_name {
-            e = e.with_text_element("CardHolderName", card_holder_name);
+        if let Some(card_holder_name) = &self.card_holder_name {
+            w.text_element("ram:CardholderName", card_holder_name)?;
         }
 
-        e.to_xml()
+        w.end("ram:ApplicableTradeSettlementFinancialCard")?;
+        w.end("ram:SpecifiedTradeSettlementPaymentMeans")
+    }
+}
+
+impl<'a> FromXml<'a> for PaymentMethodPaymentCard<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(PaymentMethodPaymentCard {
+            primary_account_number: node.required_child_text("PrimaryAccountNumber")?,
+            card_holder_name: node.child_text("CardHolderName"),
+        })
+    }
+}
+
+impl Validate for PaymentMethodPaymentCard<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.pattern(
+            "PrimaryAccountNumber",
+            &self.primary_account_number,
+            PAYMENT_CARD_REGEX_STR,
+            &PAYMENT_CARD_REGEX,
+        );
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct PaymentMethod<'a> {
-    comment: Option<&'a str>,
+    comment: Option<Cow<'a, str>>,
     method: PaymentMethodType<'a>,
 }
 
@@ -455,32 +1047,139 @@ impl<'a> PaymentMethod<'a> {
         }
     }
 
-    pub fn with_comment(mut self, comment: &'a str) -> Self {
-        self.comment = Some(comment);
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.comment = Some(comment.into());
         self
     }
+
+    pub fn into_owned(self) -> PaymentMethod<'static> {
+        PaymentMethod {
+            comment: self.comment.map(|v| Cow::Owned(v.into_owned())),
+            method: self.method.into_owned(),
+        }
+    }
+
+    pub(crate) fn payment_reference(&self) -> Option<&str> {
+        match &self.method {
+            PaymentMethodType::UniversalBankTransaction(p) => p.payment_reference.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn creditor_id(&self) -> Option<&str> {
+        match &self.method {
+            PaymentMethodType::SEPADirectDebit(p) => p.creditor_id.as_ref().map(CreditorId::as_str),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn mandate_reference(&self) -> Option<&str> {
+        match &self.method {
+            PaymentMethodType::SEPADirectDebit(p) => p.mandate_reference.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        let construct = match self.method {
+            PaymentMethodType::PaymentCard(_) => "PaymentCard",
+            PaymentMethodType::OtherPayment => "OtherPayment",
+            _ => return Ok(()),
+        };
+        if version.has_payment_card_and_other_payment() {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedByVersion {
+                version,
+                construct: construct.to_string(),
+            })
+        }
+    }
 }
 
 impl ToXml for PaymentMethod<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("PaymentMethod");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("PaymentMethod")?;
 
-        if let Some(comment) = self.comment {
-            e = e.with_text_element("Comment", comment);
+        if let Some(comment) = &self.comment {
+            w.text_element("Comment", comment)?;
         }
 
-        e = e.with_element(&self.method);
+        w.element(&self.method)?;
 
-        e.to_xml()
+        w.end("PaymentMethod")
+    }
+}
+
+/// Writes the UBL `PaymentMeans`, none for `NoPayment` and `ZZZ` (mutually defined) for `OtherPayment`.
+/// The comment gets dropped.
+impl ToUbl for PaymentMethod<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        match &self.method {
+            PaymentMethodType::NoPayment => Ok(()),
+            PaymentMethodType::SEPADirectDebit(p) => p.write_ubl(w),
+            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => p.write_ubl(w, None),
+            PaymentMethodType::UniversalBankTransaction(p) => p.write_ubl(w),
+            PaymentMethodType::PaymentCard(p) => p.write_ubl(w),
+            PaymentMethodType::OtherPayment => {
+                w.start("cac:PaymentMeans")?;
+                w.text_element("cbc:PaymentMeansCode", "ZZZ")?;
+                w.end("cac:PaymentMeans")
+            }
+        }
+    }
+}
+
+/// Writes the CII `SpecifiedTradeSettlementPaymentMeans`, like the UBL `PaymentMeans`. The comment gets dropped.
+impl ToCii for PaymentMethod<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        match &self.method {
+            PaymentMethodType::NoPayment => Ok(()),
+            PaymentMethodType::SEPADirectDebit(p) => w.element(p),
+            PaymentMethodType::UniversalBankTransactionBeneficiaryAccount(p) => w.element(p),
+            PaymentMethodType::UniversalBankTransaction(p) => w.element(p),
+            PaymentMethodType::PaymentCard(p) => w.element(p),
+            PaymentMethodType::OtherPayment => {
+                w.start("ram:SpecifiedTradeSettlementPaymentMeans")?;
+                w.text_element("ram:TypeCode", "ZZZ")?;
+                w.end("ram:SpecifiedTradeSettlementPaymentMeans")
+            }
+        }
+    }
+}
+
+impl<'a> FromXml<'a> for PaymentMethod<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let method = node
+            .children
+            .iter()
+            .find(|c| c.name != "Comment")
+            .ok_or_else(|| node.error(ParseErrorKind::MissingElement("NoPayment".to_string())))?;
+
+        Ok(PaymentMethod {
+            comment: node.child_text("Comment"),
+            method: PaymentMethodType::from_xml(method)?,
+        })
+    }
+}
+
+impl Validate for PaymentMethod<'_> {
+    fn validate(&self, v: &mut Validator) {
+        self.method.validate(v);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::xml::ToXml;
+    use crate::{parse::parse_document, xml::ToXml};
 
     use super::*;
 
+    fn assert_round_trip(payment_method: &PaymentMethod) {
+        let xml = payment_method.to_xml();
+        assert_eq!(&PaymentMethod::from_xml(&parse_document(&xml).unwrap()).unwrap(), payment_method);
+    }
+
     #[test]
     fn default() {
         assert_eq!(
@@ -488,7 +1187,7 @@ mod tests {
                 ..Default::default()
             }
             .to_xml(),
-            "<PaymentMethod><NoPayment></NoPayment></PaymentMethod>"
+            "<PaymentMethod><NoPayment></NoPayment></PaymentMethod>",
         )
     }
 
@@ -496,65 +1195,219 @@ mod tests {
     fn no_payment() {
         assert_eq!(
             PaymentMethod::no_payment().to_xml(),
-            "<PaymentMethod><NoPayment></NoPayment></PaymentMethod>"
+            "<PaymentMethod><NoPayment></NoPayment></PaymentMethod>",
         )
     }
 
     #[test]
     fn sepa_direct_debit() {
+        let payment_method = PaymentMethod::sepa_direct_debit(PaymentMethodSEPADirectDebit {
+            direct_debit_type: Some(SepaDirectDebitType::B2B),
+            bic: Some(Bic::new("BKAUATWW").unwrap()),
+            iban: Some(Iban::new("AT031200011111111111").unwrap()),
+            bank_account_owner: Some("Test".into()),
+            creditor_id: Some(CreditorId::new("AT88ZZZ00000000001").unwrap()),
+            mandate_reference: Some("123".into()),
+            debit_collection_date: Some("2020-01-01".into()),
+        });
+
         assert_eq!(
-            PaymentMethod::sepa_direct_debit(PaymentMethodSEPADirectDebit {
-                direct_debit_type: Some("B2B"),
-                bic: Some("BKAUATWW"),
-                iban: Some("AT491200011111111111"),
-                bank_account_owner: Some("Test"),
-                creditor_id: Some("AT12ZZZ00000000001"),
-                mandate_reference: Some("123"),
-                debit_collection_date: Some("2020-01-01"),
-                ..Default::default()
-            })
-            .to_xml(),
-            "<PaymentMethod><SEPADirectDebit><Type>B2B</Type><BIC>BKAUATWW</BIC><IBAN>AT491200011111111111</IBAN><BankAccountOwner>Test</BankAccountOwner><CreditorID>AT12ZZZ00000000001</CreditorID><MandateReference>123</MandateReference><DebitCollectionDate>2020-01-01</DebitCollectionDate></SEPADirectDebit></PaymentMethod>"
-        )
+            payment_method.to_xml(),
+            "<PaymentMethod><SEPADirectDebit><Type>B2B</Type><BIC>BKAUATWW</BIC><IBAN>AT031200011111111111</IBAN><BankAccountOwner>Test</BankAccountOwner><CreditorID>AT88ZZZ00000000001</CreditorID><MandateReference>123</MandateReference><DebitCollectionDate>2020-01-01</DebitCollectionDate></SEPADirectDebit></PaymentMethod>",
+        );
+        assert_round_trip(&payment_method);
     }
 
     #[test]
     fn universal_bank_transaction() {
+        let payment_method = PaymentMethod::universal_bank_transaction(PaymentMethodUniversalBankTransaction {
+            consolidator_payable: Some(true),
+            beneficiary_account: Some(vec![
+                PaymentMethodUniversalBankTransactionBeneficiaryAccount {
+                    bank_name: Some("Bank".into()),
+                    bank_code: Some(
+                        PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode {
+                            bank_code: 12000,
+                            bank_code_type: "AT".into(),
+                        }
+                    ),
+                    bic: Some(Bic::new("BKAUATWW").unwrap()),
+                    bank_account_number: Some("11111111111".into()),
+                    iban: Some(Iban::new("AT031200011111111111").unwrap()),
+                    bank_account_owner: Some("Name".into()),
+                }
+            ]),
+            payment_reference: Some("123456789012".into()),
+            payment_reference_checksum: Some("X".into()),
+        });
+
         assert_eq!(
-            PaymentMethod::universal_bank_transaction(PaymentMethodUniversalBankTransaction {
-                consolidator_payable: Some(true),
-                beneficiary_account: Some(vec![
-                    PaymentMethodUniversalBankTransactionBeneficiaryAccount {
-                        bank_name: Some("Bank"),
-                        bank_code: Some(
-                            PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode {
-                                bank_code: 12000,
-                                bank_code_type: "AT",
-                            }
-                        ),
-                        bic: Some("BKAUATWW"),
-                        bank_account_number: Some("11111111111"),
-                        iban: Some("AT491200011111111111"),
-                        bank_account_owner: Some("Name"),
-                    }
-                ]),
-                payment_reference: Some("123456789012"),
-                payment_reference_checksum: Some("X"),
-            })
+            payment_method.to_xml(),
+            "<PaymentMethod><UniversalBankTransaction ConsolidatorPayable=\"true\"><BeneficiaryAccount><BankName>Bank</BankName><BankCode BankCodeType=\"AT\">12000</BankCode><BIC>BKAUATWW</BIC><BankAccountNr>11111111111</BankAccountNr><IBAN>AT031200011111111111</IBAN><BankAccountOwner>Name</BankAccountOwner></BeneficiaryAccount><PaymentReference CheckSum=\"X\">123456789012</PaymentReference></UniversalBankTransaction></PaymentMethod>",
+        );
+        assert_round_trip(&payment_method);
+    }
+
+    #[test]
+    fn writes_structured_payment_references() {
+        let transaction = || {
+            PaymentMethodUniversalBankTransaction::new().with_beneficiary_account(
+                PaymentMethodUniversalBankTransactionBeneficiaryAccount::new()
+                    .with_iban(Iban::new("AT611904300234573201").unwrap()),
+            )
+        };
+
+        let payment_method = PaymentMethod::universal_bank_transaction(
+            transaction()
+                .with_stuzza_payment_reference(StuzzaPaymentReference::from_invoice_number("2020001").unwrap()),
+        );
+        assert_eq!(
+            payment_method.to_xml(),
+            "<PaymentMethod><UniversalBankTransaction ConsolidatorPayable=\"false\"><BeneficiaryAccount><IBAN>AT611904300234573201</IBAN></BeneficiaryAccount><PaymentReference CheckSum=\"0\">20200010</PaymentReference></UniversalBankTransaction></PaymentMethod>",
+        );
+
+        let payment_method = PaymentMethod::universal_bank_transaction(
+            transaction().with_creditor_reference(CreditorReference::from_invoice_number("RE-2020/001").unwrap()),
+        );
+        assert_eq!(
+            payment_method.to_xml(),
+            "<PaymentMethod><UniversalBankTransaction ConsolidatorPayable=\"false\"><BeneficiaryAccount><IBAN>AT611904300234573201</IBAN></BeneficiaryAccount><PaymentReference CheckSum=\"03\">RF03RE2020001</PaymentReference></UniversalBankTransaction></PaymentMethod>",
+        );
+    }
+
+    #[test]
+    fn validates_creditor_references() {
+        let validate = |payment_reference: &str| {
+            let mut v = Validator::new();
+            PaymentMethodUniversalBankTransaction::new()
+                .with_payment_reference(payment_reference)
+                .unwrap()
+                .validate(&mut v);
+            v.errors.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        assert!(validate("RF03RE2020001").is_empty());
+        assert!(validate("RFQ-2020-001").is_empty());
+        assert_eq!(
+            validate("RF04RE2020001"),
+            vec!["PaymentReference: PaymentReference RF04RE2020001 has an invalid checksum!"]
+        );
+    }
+
+    #[test]
+    fn validates_payment_reference_checksums() {
+        let validate = |payment_reference: &str, checksum: &str| {
+            let mut v = Validator::new();
+            PaymentMethodUniversalBankTransaction::new()
+                .with_payment_reference(payment_reference)
+                .unwrap()
+                .with_payment_reference_checksum(checksum)
+                .validate(&mut v);
+            v.errors.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        assert!(validate("20200010", "0").is_empty());
+        assert!(validate("RF03RE2020001", "03").is_empty());
+        assert_eq!(
+            validate("20200010", "3"),
+            vec!["PaymentReference/@CheckSum: CheckSum 3 doesn't match the check digits 0!"]
+        );
+        assert_eq!(
+            validate("RF03RE2020001", "04"),
+            vec!["PaymentReference/@CheckSum: CheckSum 04 doesn't match the check digits 03!"]
+        );
+        assert_eq!(
+            validate("20200013", "3"),
+            vec!["PaymentReference: PaymentReference 20200013 has an invalid checksum!"]
+        );
+        // Without a CheckSum, digits only aren't taken as a Stuzza payment reference.
+        let mut v = Validator::new();
+        PaymentMethodUniversalBankTransaction::new()
+            .with_payment_reference("20200013")
+            .unwrap()
+            .validate(&mut v);
+        assert!(v.errors.is_empty());
+    }
+
+    #[test]
+    fn accepts_8_and_11_character_bics() {
+        let direct_debit = PaymentMethodSEPADirectDebit::new().with_bic(Bic::new("BKAUATWW").unwrap());
+        assert_eq!(
+            PaymentMethod::sepa_direct_debit(direct_debit).to_xml(),
+            "<PaymentMethod><SEPADirectDebit><Type>B2C</Type><BIC>BKAUATWW</BIC></SEPADirectDebit></PaymentMethod>",
+        );
+
+        let account =
+            PaymentMethodUniversalBankTransactionBeneficiaryAccount::new().with_bic(Bic::new("rzooat2l303").unwrap());
+        assert_eq!(
+            PaymentMethod::universal_bank_transaction(
+                PaymentMethodUniversalBankTransaction::new().with_beneficiary_account(account),
+            )
             .to_xml(),
-            "<PaymentMethod><UniversalBankTransaction ConsolidatorPayable=\"true\"><BeneficiaryAccount><BankName>Bank</BankName><BankCode BankCodeType=\"AT\">12000</BankCode><BIC>BKAUATWW</BIC><BankAccountNr>11111111111</BankAccountNr><IBAN>AT491200011111111111</IBAN><BankAccountOwner>Name</BankAccountOwner></BeneficiaryAccount><PaymentReference CheckSum=\"X\">123456789012</PaymentReference></UniversalBankTransaction></PaymentMethod>"
-        )
+            "<PaymentMethod><UniversalBankTransaction ConsolidatorPayable=\"false\"><BeneficiaryAccount><BIC>RZOOAT2L303</BIC></BeneficiaryAccount></UniversalBankTransaction></PaymentMethod>",
+        );
+    }
+
+    #[test]
+    fn validates_bank_identifiers_as_read() {
+        let xml = "<PaymentMethod><SEPADirectDebit><BIC>x</BIC><IBAN>AT611904300234573202</IBAN><CreditorID>AT62ZZZ01234567890</CreditorID><MandateReference>MANDATE 1</MandateReference></SEPADirectDebit></PaymentMethod>";
+        let payment_method = PaymentMethod::from_xml(&parse_document(xml).unwrap()).unwrap();
+        let mut v = Validator::new();
+        payment_method.validate(&mut v);
+
+        assert_eq!(
+            v.errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "SEPADirectDebit/BIC: BIC x doesn't match regex ^[A-Z]{4}[A-Z]{2}[0-9A-Z]{2}([0-9A-Z]{3})?$!",
+                "SEPADirectDebit/IBAN: IBAN AT611904300234573202 has an invalid checksum!",
+                "SEPADirectDebit/CreditorID: CreditorID AT62ZZZ01234567890 has an invalid checksum!",
+                "SEPADirectDebit/MandateReference: MandateReference MANDATE 1 doesn't match regex \
+                 ^[0-9A-Za-z?:().,'+-]+(/[0-9A-Za-z?:().,'+-]+)*$!",
+            ]
+        );
+
+        let xml = xml.replace("MANDATE 1", "A//B");
+        let payment_method = PaymentMethod::from_xml(&parse_document(&xml).unwrap()).unwrap();
+        let mut v = Validator::new();
+        payment_method.validate(&mut v);
+        assert!(matches!(
+            v.errors.last().map(|e| &e.error),
+            Some(Error::RegexMismatch {
+                field: "MandateReference",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reads_the_direct_debit_type() {
+        let xml = "<PaymentMethod><SEPADirectDebit><Type>B2B</Type></SEPADirectDebit></PaymentMethod>";
+        assert_eq!(
+            PaymentMethod::from_xml(&parse_document(xml).unwrap()).unwrap(),
+            PaymentMethod::sepa_direct_debit(
+                PaymentMethodSEPADirectDebit::new().with_direct_debit_type(SepaDirectDebitType::B2B)
+            )
+        );
+
+        let xml = "<PaymentMethod><SEPADirectDebit><Type>COR1</Type></SEPADirectDebit></PaymentMethod>";
+        assert!(matches!(
+            PaymentMethod::from_xml(&parse_document(xml).unwrap()),
+            Err(ParseError {
+                kind: ParseErrorKind::InvalidValue { .. },
+                ..
+            })
+        ));
     }
 
     #[test]
     fn payment_card() {
         assert_eq!(
             PaymentMethod::payment_card(PaymentMethodPaymentCard {
-                primary_account_number: "123456*4321",
-                card_holder_name: Some("Name"),
+                primary_account_number: "123456*4321".into(),
+                card_holder_name: Some("Name".into()),
             })
             .to_xml(),
-            "<PaymentMethod><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod>"
+            "<PaymentMethod><PaymentCard><PrimaryAccountNumber>123456*4321</PrimaryAccountNumber><CardHolderName>Name</CardHolderName></PaymentCard></PaymentMethod>",
         )
     }
 
@@ -562,7 +1415,100 @@ mod tests {
     fn other_payment() {
         assert_eq!(
             PaymentMethod::other_payment().to_xml(),
-            "<PaymentMethod><OtherPayment></OtherPayment></PaymentMethod>"
+            "<PaymentMethod><OtherPayment></OtherPayment></PaymentMethod>",
         )
     }
+
+    #[test]
+    fn counts_characters_instead_of_bytes() {
+        let account = PaymentMethodUniversalBankTransactionBeneficiaryAccount::new()
+            .with_bank_name("Ä".repeat(255))
+            .unwrap()
+            .with_bank_account_owner("Ö".repeat(70))
+            .unwrap();
+        let mut v = Validator::new();
+        account.validate(&mut v);
+        assert!(v.errors.is_empty());
+
+        let direct_debit = PaymentMethodSEPADirectDebit::new()
+            .with_bank_account_owner("Ü".repeat(70))
+            .unwrap();
+        let mut v = Validator::new();
+        direct_debit.validate(&mut v);
+        assert!(v.errors.is_empty());
+
+        assert!(matches!(
+            PaymentMethodUniversalBankTransactionBeneficiaryAccount::new().with_bank_account_owner("Ö".repeat(71)),
+            Err(Error::TooLong {
+                field: "BankAccountOwner",
+                max: 70,
+                ..
+            })
+        ));
+        assert!(matches!(
+            PaymentMethodSEPADirectDebit::new().with_bank_account_owner("Ü".repeat(71)),
+            Err(Error::TooLong {
+                field: "BankAccountOwner",
+                max: 70,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn returns_structured_errors() {
+        assert_eq!(
+            PaymentMethodUniversalBankTransactionBeneficiaryAccount::new()
+                .with_bank_code(PaymentMethodUniversalBankTransactionBeneficiaryAccountBankCode::new(12000, "AUT"))
+                .err(),
+            Some(Error::InvalidLength {
+                field: "BankCodeType",
+                length: 2,
+                value: "AUT".to_string(),
+            })
+        );
+
+        let error = PaymentMethodPaymentCard::new("1234567890123456").err().unwrap();
+        assert!(matches!(error, Error::RegexMismatch { field: "PrimaryAccountNumber", .. }));
+        assert_eq!(
+            error.to_string(),
+            "PrimaryAccountNumber 1234567890123456 doesn't match regex ^[0-9]{0,6}\\*[0-9]{0,4}$!"
+        );
+
+        assert_eq!(
+            PaymentMethodSEPADirectDebit::new()
+                .with_mandate_reference("Mandat Nr. 1")
+                .err(),
+            Some(Error::RegexMismatch {
+                field: "MandateReference",
+                pattern: MANDATE_REFERENCE_REGEX_STR,
+                value: "Mandat Nr. 1".to_string(),
+            })
+        );
+        for mandate_reference in ["/ABC", "ABC/", "A//B"] {
+            assert_eq!(
+                PaymentMethodSEPADirectDebit::new()
+                    .with_mandate_reference(mandate_reference)
+                    .err(),
+                Some(Error::RegexMismatch {
+                    field: "MandateReference",
+                    pattern: MANDATE_REFERENCE_REGEX_STR,
+                    value: mandate_reference.to_string(),
+                })
+            );
+        }
+        assert!(
+            PaymentMethodSEPADirectDebit::new()
+                .with_mandate_reference("A/B-1/2")
+                .is_ok()
+        );
+        assert!(matches!(
+            PaymentMethodSEPADirectDebit::new().with_mandate_reference("M".repeat(36)),
+            Err(Error::TooLong {
+                field: "MandateReference",
+                max: 35,
+                ..
+            })
+        ));
+    }
 }
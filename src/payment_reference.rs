@@ -0,0 +1,317 @@
+use std::{borrow::Cow, fmt, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{error::Error, iban::mod_97};
+
+const STUZZA_REGEX_STR: &str = r"^[0-9]{2,35}$";
+static STUZZA_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(STUZZA_REGEX_STR).unwrap());
+
+const STUZZA_DIGITS_REGEX_STR: &str = r"^[0-9]{1,34}$";
+static STUZZA_DIGITS_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(STUZZA_DIGITS_REGEX_STR).unwrap());
+
+const CREDITOR_REFERENCE_REGEX_STR: &str = r"^RF[0-9]{2}[0-9A-Z]{1,21}$";
+static CREDITOR_REFERENCE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(CREDITOR_REFERENCE_REGEX_STR).unwrap());
+
+const CREDITOR_REFERENCE_PAYLOAD_REGEX_STR: &str = r"^[0-9A-Z]{1,21}$";
+static CREDITOR_REFERENCE_PAYLOAD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(CREDITOR_REFERENCE_PAYLOAD_REGEX_STR).unwrap());
+
+/// An Austrian payment reference (Zahlungsreferenz), e.g. `20200010`: up to 34 digits followed by a check digit,
+/// which this crate calculates with the Luhn algorithm (modulo 10 with the weights 2, 1, 2, … from the right).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StuzzaPaymentReference<'a>(Cow<'a, str>);
+
+impl<'a> StuzzaPaymentReference<'a> {
+    /// Validates the payment reference including its check digit, after removing spaces.
+    pub fn new(payment_reference: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let payment_reference = payment_reference.into();
+        let payment_reference = if payment_reference.bytes().any(|b| b.is_ascii_whitespace()) {
+            Cow::Owned(payment_reference.chars().filter(|c| !c.is_ascii_whitespace()).collect())
+        } else {
+            payment_reference
+        };
+        let payment_reference = StuzzaPaymentReference(payment_reference);
+        payment_reference.check()?;
+        Ok(payment_reference)
+    }
+
+    /// Generates the payment reference of an invoice from its number, which has to consist of digits only, e.g.
+    /// `2020001` followed by the check digit `0`. Other characters aren't dropped, as invoice numbers like
+    /// `RE-2020/001` and `GS-2020/001` would get the same reference then.
+    pub fn from_invoice_number(invoice_number: &str) -> Result<StuzzaPaymentReference<'static>, Error> {
+        let mut payment_reference = invoice_number.to_string();
+        if !STUZZA_DIGITS_REGEX.is_match(&payment_reference) {
+            return Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                pattern: STUZZA_DIGITS_REGEX_STR,
+                value: invoice_number.to_string(),
+            });
+        }
+        payment_reference.push(luhn_check_digit(&payment_reference));
+        Ok(StuzzaPaymentReference(Cow::Owned(payment_reference)))
+    }
+
+    /// Wraps a payment reference as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(payment_reference: &'a str) -> Self {
+        StuzzaPaymentReference(Cow::Borrowed(payment_reference))
+    }
+
+    /// Checks the structure and the check digit of the payment reference.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if !STUZZA_REGEX.is_match(&self.0) {
+            return Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                pattern: STUZZA_REGEX_STR,
+                value: self.0.to_string(),
+            });
+        }
+        if luhn_check_digit(self.reference()) != self.0.chars().last().unwrap_or_default() {
+            return Err(Error::InvalidChecksum {
+                field: "PaymentReference",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether a payment reference looks like a Stuzza payment reference, i.e. consists of 2 to 35 digits.
+    pub(crate) fn is_stuzza_payment_reference(payment_reference: &str) -> bool {
+        STUZZA_REGEX.is_match(payment_reference)
+    }
+
+    /// Returns the payment reference including the check digit, as entered by the payer.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the payment reference without the check digit.
+    pub fn reference(&self) -> &str {
+        self.0.get(..self.0.len().saturating_sub(1)).unwrap_or_default()
+    }
+
+    pub fn check_digit(&self) -> &str {
+        self.0.get(self.0.len().saturating_sub(1)..).unwrap_or_default()
+    }
+
+    pub fn into_owned(self) -> StuzzaPaymentReference<'static> {
+        StuzzaPaymentReference(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for StuzzaPaymentReference<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> From<StuzzaPaymentReference<'a>> for Cow<'a, str> {
+    fn from(payment_reference: StuzzaPaymentReference<'a>) -> Self {
+        payment_reference.0
+    }
+}
+
+/// An ISO 11649 structured creditor reference, e.g. `RF18539007547034`: `RF`, two check digits and a reference of
+/// up to 21 letters and digits. Unlike the Stuzza payment reference, it's understood by banks all over the SEPA.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreditorReference<'a>(Cow<'a, str>);
+
+impl<'a> CreditorReference<'a> {
+    /// Validates the creditor reference, after removing spaces and converting it to uppercase, so both the
+    /// electronic (`RF18539007547034`) and the printed (`RF18 5390 0754 7034`) form get accepted.
+    pub fn new(creditor_reference: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let creditor_reference = creditor_reference.into();
+        let creditor_reference = if creditor_reference
+            .bytes()
+            .any(|b| b.is_ascii_whitespace() || b.is_ascii_lowercase())
+        {
+            Cow::Owned(
+                creditor_reference
+                    .chars()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect(),
+            )
+        } else {
+            creditor_reference
+        };
+        let creditor_reference = CreditorReference(creditor_reference);
+        creditor_reference.check()?;
+        Ok(creditor_reference)
+    }
+
+    /// Generates the creditor reference of an invoice from the letters and digits of its number, e.g.
+    /// `RF03RE2020001` for `RE-2020/001`.
+    pub fn from_invoice_number(invoice_number: &str) -> Result<CreditorReference<'static>, Error> {
+        let reference: String = invoice_number
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        if !CREDITOR_REFERENCE_PAYLOAD_REGEX.is_match(&reference) {
+            return Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                pattern: CREDITOR_REFERENCE_PAYLOAD_REGEX_STR,
+                value: invoice_number.to_string(),
+            });
+        }
+        let check_digits = 98 - mod_97(&reference, "RF00");
+        Ok(CreditorReference(Cow::Owned(format!("RF{check_digits:02}{reference}"))))
+    }
+
+    /// Wraps a payment reference as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(creditor_reference: &'a str) -> Self {
+        CreditorReference(Cow::Borrowed(creditor_reference))
+    }
+
+    /// Checks the structure and the check digits of the creditor reference.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if !CREDITOR_REFERENCE_REGEX.is_match(&self.0) {
+            return Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                pattern: CREDITOR_REFERENCE_REGEX_STR,
+                value: self.0.to_string(),
+            });
+        }
+        if mod_97(self.reference(), &self.0[..4]) != 1 {
+            return Err(Error::InvalidChecksum {
+                field: "PaymentReference",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether a payment reference claims to be a creditor reference, i.e. starts with `RF` and two digits.
+    pub(crate) fn is_creditor_reference(payment_reference: &str) -> bool {
+        payment_reference
+            .get(..4)
+            .is_some_and(|prefix| prefix.starts_with("RF") && prefix[2..].bytes().all(|b| b.is_ascii_digit()))
+    }
+
+    /// Returns the creditor reference in its electronic form, without spaces.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn check_digits(&self) -> &str {
+        self.0.get(2..4).unwrap_or_default()
+    }
+
+    /// Returns the reference without `RF` and the check digits.
+    pub fn reference(&self) -> &str {
+        self.0.get(4..).unwrap_or_default()
+    }
+
+    pub fn into_owned(self) -> CreditorReference<'static> {
+        CreditorReference(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for CreditorReference<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> From<CreditorReference<'a>> for Cow<'a, str> {
+    fn from(creditor_reference: CreditorReference<'a>) -> Self {
+        creditor_reference.0
+    }
+}
+
+/// Calculates the check digit, which makes the Luhn sum of the digits followed by it a multiple of 10.
+fn luhn_check_digit(digits: &str) -> char {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { 2 * d / 10 + 2 * d % 10 } else { d })
+        .sum();
+    char::from(b'0' + ((10 - sum % 10) % 10) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_stuzza_payment_references() {
+        let payment_reference = StuzzaPaymentReference::from_invoice_number("2020001").unwrap();
+        assert_eq!(payment_reference.as_str(), "20200010");
+        assert_eq!((payment_reference.reference(), payment_reference.check_digit()), ("2020001", "0"));
+        assert_eq!(StuzzaPaymentReference::new("2020 0010").unwrap(), payment_reference);
+        assert_eq!(
+            StuzzaPaymentReference::from_invoice_number("7992739871")
+                .unwrap()
+                .as_str(),
+            "79927398713"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_stuzza_payment_references() {
+        assert_eq!(
+            StuzzaPaymentReference::new("20200013"),
+            Err(Error::InvalidChecksum {
+                field: "PaymentReference",
+                value: "20200013".to_string(),
+            })
+        );
+        assert!(matches!(
+            StuzzaPaymentReference::new("RE20200012"),
+            Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                ..
+            })
+        ));
+        // Dropping the other characters would give both invoice numbers the same reference.
+        for invoice_number in ["RE-2020/001", "GS-2020/001"] {
+            assert_eq!(
+                StuzzaPaymentReference::from_invoice_number(invoice_number),
+                Err(Error::RegexMismatch {
+                    field: "PaymentReference",
+                    pattern: STUZZA_DIGITS_REGEX_STR,
+                    value: invoice_number.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn generates_creditor_references() {
+        let creditor_reference = CreditorReference::from_invoice_number("539007547034").unwrap();
+        assert_eq!(creditor_reference.as_str(), "RF18539007547034");
+        assert_eq!((creditor_reference.check_digits(), creditor_reference.reference()), ("18", "539007547034"));
+        assert_eq!(CreditorReference::new("rf18 5390 0754 7034").unwrap(), creditor_reference);
+        assert_eq!(CreditorReference::from_invoice_number("RE-2020/001").unwrap().as_str(), "RF03RE2020001");
+        assert_eq!(CreditorReference::from_invoice_number("7").unwrap().as_str(), "RF097");
+    }
+
+    #[test]
+    fn rejects_invalid_creditor_references() {
+        assert_eq!(
+            CreditorReference::new("RF19539007547034"),
+            Err(Error::InvalidChecksum {
+                field: "PaymentReference",
+                value: "RF19539007547034".to_string(),
+            })
+        );
+        assert!(matches!(
+            CreditorReference::new("RF18"),
+            Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                ..
+            })
+        ));
+        assert!(matches!(
+            CreditorReference::from_invoice_number("RE-2020/001-0000000000000000"),
+            Err(Error::RegexMismatch {
+                field: "PaymentReference",
+                pattern: CREDITOR_REFERENCE_PAYLOAD_REGEX_STR,
+                ..
+            })
+        ));
+    }
+}
@@ -1,20 +1,39 @@
 use rust_decimal::Decimal;
+use std::{borrow::Cow, fmt};
 
 use crate::{
+    cii::{CiiWriter, ToCii},
     decimal::CloneAndRescale,
-    xml::{ToXml, XmlElement},
+    parse::{FromXml, ParseError, ParseErrorKind, XmlNode, non_empty},
+    rounding::RoundingPolicy,
+    tax::{TaxItem, TaxItemWithTaxableAmount},
+    ubl::{ToUbl, UblWriter},
+    validation::{Validate, Validator},
+    xml::{ToXml, XmlWriter},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ReductionAndSurchargeValue {
     Percentage(Decimal),
     Amount(Decimal),
     PercentageAndAmount(Decimal, Decimal),
 }
 
+/// Where the base amount of a percentage reduction or surcharge on a line item is taken from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DerivedBaseAmount {
+    /// The net price of the line item, `quantity * unit_price / base_quantity`.
+    Price,
+    /// The net price of the line item minus the amounts of the preceding reductions (cascading discounts).
+    Remaining,
+}
+
+#[derive(Debug, PartialEq)]
 struct ReductionAndSurchargeListLineItemBase<'a> {
     base_amount: Decimal,
     value: ReductionAndSurchargeValue,
-    comment: Option<&'a str>,
+    comment: Option<Cow<'a, str>>,
+    derived_base_amount: Option<DerivedBaseAmount>,
 }
 
 impl<'a> ReductionAndSurchargeListLineItemBase<'a> {
@@ -23,58 +42,176 @@ impl<'a> ReductionAndSurchargeListLineItemBase<'a> {
             base_amount,
             value,
             comment: None,
+            derived_base_amount: None,
+        }
+    }
+
+    /// A percentage, whose base amount gets derived from the line item, when calculating or writing it.
+    fn derived(percentage: Decimal, derived_base_amount: DerivedBaseAmount) -> Self {
+        ReductionAndSurchargeListLineItemBase {
+            derived_base_amount: Some(derived_base_amount),
+            ..ReductionAndSurchargeListLineItemBase::new(
+                Decimal::ZERO,
+                ReductionAndSurchargeValue::Percentage(percentage),
+            )
+        }
+    }
+
+    fn into_owned(self) -> ReductionAndSurchargeListLineItemBase<'static> {
+        ReductionAndSurchargeListLineItemBase {
+            base_amount: self.base_amount,
+            value: self.value,
+            comment: self.comment.map(|c| Cow::Owned(c.into_owned())),
+            derived_base_amount: self.derived_base_amount,
+        }
+    }
+
+    /// Returns a copy with the derived base amount, given the net price of the line item and the amount remaining
+    /// after the preceding reductions. Given base amounts are kept.
+    fn with_line_price(&self, price: Decimal, remaining: Decimal) -> ReductionAndSurchargeListLineItemBase<'_> {
+        ReductionAndSurchargeListLineItemBase {
+            base_amount: match self.derived_base_amount {
+                None => self.base_amount,
+                Some(DerivedBaseAmount::Price) => price,
+                Some(DerivedBaseAmount::Remaining) => remaining,
+            },
+            value: self.value,
+            comment: self.comment.as_deref().map(Cow::Borrowed),
+            derived_base_amount: None,
         }
     }
 
-    fn sum(&self) -> Decimal {
+    /// Reads the base amount, the value and the comment of a reduction or surcharge element.
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let value = match (node.child_decimal("Percentage")?, node.child_decimal("Amount")?) {
+            (Some(percentage), Some(amount)) => ReductionAndSurchargeValue::PercentageAndAmount(percentage, amount),
+            (Some(percentage), None) => ReductionAndSurchargeValue::Percentage(percentage),
+            (None, Some(amount)) => ReductionAndSurchargeValue::Amount(amount),
+            (None, None) => return Err(node.error(ParseErrorKind::MissingElement("Amount".to_string()))),
+        };
+
+        Ok(ReductionAndSurchargeListLineItemBase {
+            base_amount: node.required_child_decimal("BaseAmount")?,
+            value,
+            comment: node.child_text("Comment"),
+            derived_base_amount: None,
+        })
+    }
+
+    fn validate(&self, v: &mut Validator) {
         match self.value {
-            ReductionAndSurchargeValue::Percentage(percentage) => {
-                (self.base_amount * percentage / Decimal::ONE_HUNDRED).clone_with_scale(2)
+            ReductionAndSurchargeValue::Percentage(percentage)
+            | ReductionAndSurchargeValue::PercentageAndAmount(percentage, _) => {
+                v.percentage("Percentage", percentage);
             }
-            ReductionAndSurchargeValue::Amount(amount) => amount.clone_with_scale(2),
-            ReductionAndSurchargeValue::PercentageAndAmount(_, amount) => {
-                amount.clone_with_scale(2)
+            ReductionAndSurchargeValue::Amount(_) => {}
+        }
+    }
+
+    /// Returns the amount of the reduction or surcharge. A percentage applies to the base amount as written, i.e.
+    /// rounded to cents.
+    fn sum(&self, rounding: RoundingPolicy) -> Decimal {
+        match self.value {
+            ReductionAndSurchargeValue::Percentage(percentage) => {
+                rounding.round(rounding.round(self.base_amount) * percentage / Decimal::ONE_HUNDRED)
             }
+            ReductionAndSurchargeValue::Amount(amount) => rounding.round(amount),
+            ReductionAndSurchargeValue::PercentageAndAmount(_, amount) => rounding.round(amount),
         }
     }
 
-    fn to_xml_elements(&self) -> Vec<XmlElement> {
-        let mut es = vec![
-            XmlElement::new("BaseAmount")
-                .with_text(self.base_amount.clone_with_scale(2).to_string()),
-        ];
+    /// Writes the element with the given name, containing the base amount, the value and the comment.
+    /// Root level reductions and surcharges additionally contain a `TaxItem` before the comment.
+    fn write_xml(&self, w: &mut XmlWriter, name: &str, tax_item: Option<&TaxItemWithTaxableAmount>) -> fmt::Result {
+        w.start(name)?;
+        w.text_element("BaseAmount", w.round(self.base_amount))?;
 
         match self.value {
             ReductionAndSurchargeValue::Percentage(percentage) => {
-                es.push(
-                    XmlElement::new("Percentage")
-                        .with_text(percentage.clone_with_scale(2).to_string()),
-                );
+                w.text_element("Percentage", percentage.clone_with_scale(2))?;
             }
             ReductionAndSurchargeValue::Amount(amount) => {
-                es.push(
-                    XmlElement::new("Amount").with_text(amount.clone_with_scale(2).to_string()),
-                );
+                w.text_element("Amount", w.round(amount))?;
             }
             ReductionAndSurchargeValue::PercentageAndAmount(percentage, amount) => {
-                es.push(
-                    XmlElement::new("Percentage")
-                        .with_text(percentage.clone_with_scale(2).to_string()),
-                );
-                es.push(
-                    XmlElement::new("Amount").with_text(amount.clone_with_scale(2).to_string()),
-                );
+                w.text_element("Percentage", percentage.clone_with_scale(2))?;
+                w.text_element("Amount", w.round(amount))?;
             }
         }
 
-        if let Some(comment) = self.comment {
-            es.push(XmlElement::new("Comment").with_text(comment))
+        if let Some(tax_item) = tax_item {
+            w.element(tax_item)?;
         }
 
-        es
+        if let Some(comment) = &self.comment {
+            w.text_element("Comment", comment)?;
+        }
+
+        w.end(name)
+    }
+
+    /// Writes a UBL `AllowanceCharge`. Without a comment, the reason is given as code (`95` discount or `ZZZ`
+    /// mutually defined charge). Root level reductions and surcharges additionally contain a `TaxCategory`.
+    fn write_ubl(&self, w: &mut UblWriter, charge: bool, tax_item: Option<&TaxItem>) -> fmt::Result {
+        w.start("cac:AllowanceCharge")?;
+        w.text_element("cbc:ChargeIndicator", charge)?;
+
+        match &self.comment {
+            Some(comment) => w.text_element("cbc:AllowanceChargeReason", comment)?,
+            None => w.text_element("cbc:AllowanceChargeReasonCode", if charge { "ZZZ" } else { "95" })?,
+        }
+
+        match self.value {
+            ReductionAndSurchargeValue::Percentage(percentage)
+            | ReductionAndSurchargeValue::PercentageAndAmount(percentage, _) => {
+                w.text_element("cbc:MultiplierFactorNumeric", percentage.clone_with_scale(2))?;
+            }
+            ReductionAndSurchargeValue::Amount(_) => {}
+        }
+
+        w.amount("cbc:Amount", self.sum(w.rounding()))?;
+        w.amount("cbc:BaseAmount", self.base_amount)?;
+
+        if let Some(tax_item) = tax_item {
+            tax_item.write_ubl(w, "cac:TaxCategory")?;
+        }
+
+        w.end("cac:AllowanceCharge")
+    }
+
+    /// Writes a CII `SpecifiedTradeAllowanceCharge`, with the same reason codes as `write_ubl`. Root level
+    /// reductions and surcharges additionally contain a `CategoryTradeTax`.
+    fn write_cii(&self, w: &mut CiiWriter, charge: bool, tax_item: Option<&TaxItem>) -> fmt::Result {
+        w.start("ram:SpecifiedTradeAllowanceCharge")?;
+        w.start("ram:ChargeIndicator")?;
+        w.text_element("udt:Indicator", charge)?;
+        w.end("ram:ChargeIndicator")?;
+
+        match self.value {
+            ReductionAndSurchargeValue::Percentage(percentage)
+            | ReductionAndSurchargeValue::PercentageAndAmount(percentage, _) => {
+                w.text_element("ram:CalculationPercent", percentage.clone_with_scale(2))?;
+            }
+            ReductionAndSurchargeValue::Amount(_) => {}
+        }
+
+        w.amount("ram:BasisAmount", self.base_amount)?;
+        w.amount("ram:ActualAmount", self.sum(w.rounding()))?;
+
+        match &self.comment {
+            Some(comment) => w.text_element("ram:Reason", comment)?,
+            None => w.text_element("ram:ReasonCode", if charge { "ZZZ" } else { "95" })?,
+        }
+
+        if let Some(tax_item) = tax_item {
+            tax_item.write_cii(w, "ram:CategoryTradeTax")?;
+        }
+
+        w.end("ram:SpecifiedTradeAllowanceCharge")
     }
 }
 
+#[derive(Debug, PartialEq)]
 pub struct ReductionListLineItem<'a> {
     base: ReductionAndSurchargeListLineItemBase<'a>,
 }
@@ -86,28 +223,54 @@ impl<'a> ReductionListLineItem<'a> {
         }
     }
 
-    pub fn with_comment(mut self, comment: &'a str) -> Self {
-        self.base.comment = Some(comment);
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.base.comment = Some(comment.into());
         self
     }
 
-    fn sum(&self) -> Decimal {
-        self.base.sum()
+    pub fn into_owned(self) -> ReductionListLineItem<'static> {
+        ReductionListLineItem {
+            base: self.base.into_owned(),
+        }
+    }
+
+    /// A percentage reduction on the net price of the line item, see `DetailsItem::with_percentage_reduction`.
+    pub(crate) fn percentage(percentage: Decimal) -> Self {
+        ReductionListLineItem {
+            base: ReductionAndSurchargeListLineItemBase::derived(percentage, DerivedBaseAmount::Price),
+        }
+    }
+
+    /// A percentage reduction on the amount remaining after the preceding reductions, see
+    /// `DetailsItem::with_cascading_percentage_reduction`.
+    pub(crate) fn cascading_percentage(percentage: Decimal) -> Self {
+        ReductionListLineItem {
+            base: ReductionAndSurchargeListLineItemBase::derived(percentage, DerivedBaseAmount::Remaining),
+        }
     }
 }
 
 impl ToXml for ReductionListLineItem<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("ReductionListLineItem");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        self.base.write_xml(w, "ReductionListLineItem", None)
+    }
+}
 
-        for base_element in self.base.to_xml_elements() {
-            e = e.with_element(&base_element);
-        }
+impl<'a> FromXml<'a> for ReductionListLineItem<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(ReductionListLineItem {
+            base: ReductionAndSurchargeListLineItemBase::from_xml(node)?,
+        })
+    }
+}
 
-        e.to_xml()
+impl Validate for ReductionListLineItem<'_> {
+    fn validate(&self, v: &mut Validator) {
+        self.base.validate(v);
     }
 }
 
+#[derive(Debug, PartialEq)]
 pub struct SurchargeListLineItem<'a> {
     base: ReductionAndSurchargeListLineItemBase<'a>,
 }
@@ -119,29 +282,46 @@ impl<'a> SurchargeListLineItem<'a> {
         }
     }
 
-    pub fn with_comment(mut self, comment: &'a str) -> Self {
-        self.base.comment = Some(comment);
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.base.comment = Some(comment.into());
         self
     }
 
-    fn sum(&self) -> Decimal {
-        self.base.sum()
+    pub fn into_owned(self) -> SurchargeListLineItem<'static> {
+        SurchargeListLineItem {
+            base: self.base.into_owned(),
+        }
+    }
+
+    /// A percentage surcharge on the net price of the line item, see `DetailsItem::with_percentage_surcharge`.
+    pub(crate) fn percentage(percentage: Decimal) -> Self {
+        SurchargeListLineItem {
+            base: ReductionAndSurchargeListLineItemBase::derived(percentage, DerivedBaseAmount::Price),
+        }
     }
 }
 
 impl ToXml for SurchargeListLineItem<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("SurchargeListLineItem");
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        self.base.write_xml(w, "SurchargeListLineItem", None)
+    }
+}
 
-        for base_element in self.base.to_xml_elements() {
-            e = e.with_element(&base_element);
-        }
+impl<'a> FromXml<'a> for SurchargeListLineItem<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(SurchargeListLineItem {
+            base: ReductionAndSurchargeListLineItemBase::from_xml(node)?,
+        })
+    }
+}
 
-        e.to_xml()
+impl Validate for SurchargeListLineItem<'_> {
+    fn validate(&self, v: &mut Validator) {
+        self.base.validate(v);
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct ReductionAndSurchargeListLineItemDetails<'a> {
     reduction_list_line_items: Option<Vec<ReductionListLineItem<'a>>>,
     surcharge_list_line_items: Option<Vec<SurchargeListLineItem<'a>>>,
@@ -168,36 +348,346 @@ impl<'a> ReductionAndSurchargeListLineItemDetails<'a> {
         self
     }
 
-    pub(crate) fn sum(&self) -> Decimal {
-        let surcharge_sum = match &self.surcharge_list_line_items {
-            Some(s) => s.iter().fold(Decimal::ZERO, |sum, s| sum + s.sum()),
-            None => Decimal::ZERO,
-        };
-        let reduction_sum = match &self.reduction_list_line_items {
-            Some(r) => r.iter().fold(Decimal::ZERO, |sum, r| sum + r.sum()),
-            None => Decimal::ZERO,
-        };
+    pub fn into_owned(self) -> ReductionAndSurchargeListLineItemDetails<'static> {
+        ReductionAndSurchargeListLineItemDetails {
+            reduction_list_line_items: self
+                .reduction_list_line_items
+                .map(|r| r.into_iter().map(ReductionListLineItem::into_owned).collect()),
+            surcharge_list_line_items: self
+                .surcharge_list_line_items
+                .map(|s| s.into_iter().map(SurchargeListLineItem::into_owned).collect()),
+        }
+    }
+
+    /// Pairs the reductions and surcharges with the net price of their line item, which derived base amounts are
+    /// taken from.
+    pub(crate) fn line_price(&self, price: Decimal) -> ReductionAndSurchargeListLineItemDetailsWithLinePrice<'_> {
+        ReductionAndSurchargeListLineItemDetailsWithLinePrice { details: self, price }
+    }
+}
+
+pub(crate) struct ReductionAndSurchargeListLineItemDetailsWithLinePrice<'a> {
+    details: &'a ReductionAndSurchargeListLineItemDetails<'a>,
+    price: Decimal,
+}
+
+impl ReductionAndSurchargeListLineItemDetailsWithLinePrice<'_> {
+    /// Returns the reductions, in the order of application, and the surcharges with their base amounts. A cascading
+    /// reduction applies to the net price minus the (rounded) amounts of all reductions before it.
+    fn resolved(
+        &self,
+        rounding: RoundingPolicy,
+    ) -> (Vec<ReductionAndSurchargeListLineItemBase<'_>>, Vec<ReductionAndSurchargeListLineItemBase<'_>>) {
+        let mut remaining = self.price;
+        let mut reductions = Vec::new();
+        for reduction_list_line_item in self.details.reduction_list_line_items.iter().flatten() {
+            let reduction = reduction_list_line_item.base.with_line_price(self.price, remaining);
+            remaining -= reduction.sum(rounding);
+            reductions.push(reduction);
+        }
+
+        let surcharges = self
+            .details
+            .surcharge_list_line_items
+            .iter()
+            .flatten()
+            .map(|s| s.base.with_line_price(self.price, self.price))
+            .collect();
+
+        (reductions, surcharges)
+    }
+
+    pub(crate) fn sum(&self, rounding: RoundingPolicy) -> Decimal {
+        let (reductions, surcharges) = self.resolved(rounding);
+        let surcharge_sum = surcharges.iter().fold(Decimal::ZERO, |sum, s| sum + s.sum(rounding));
+        let reduction_sum = reductions.iter().fold(Decimal::ZERO, |sum, r| sum + r.sum(rounding));
         surcharge_sum - reduction_sum
     }
 }
 
-impl ToXml for ReductionAndSurchargeListLineItemDetails<'_> {
-    fn to_xml(&self) -> String {
-        let mut e = XmlElement::new("ReductionAndSurchargeListLineItemDetails");
+impl ToXml for ReductionAndSurchargeListLineItemDetailsWithLinePrice<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        let (reductions, surcharges) = self.resolved(w.rounding());
+
+        w.start("ReductionAndSurchargeListLineItemDetails")?;
+
+        for reduction in &reductions {
+            reduction.write_xml(w, "ReductionListLineItem", None)?;
+        }
+
+        for surcharge in &surcharges {
+            surcharge.write_xml(w, "SurchargeListLineItem", None)?;
+        }
+
+        w.end("ReductionAndSurchargeListLineItemDetails")
+    }
+}
+
+impl ToUbl for ReductionAndSurchargeListLineItemDetailsWithLinePrice<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        let (reductions, surcharges) = self.resolved(w.rounding());
+
+        for reduction in &reductions {
+            reduction.write_ubl(w, false, None)?;
+        }
+
+        for surcharge in &surcharges {
+            surcharge.write_ubl(w, true, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToCii for ReductionAndSurchargeListLineItemDetailsWithLinePrice<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        let (reductions, surcharges) = self.resolved(w.rounding());
+
+        for reduction in &reductions {
+            reduction.write_cii(w, false, None)?;
+        }
+
+        for surcharge in &surcharges {
+            surcharge.write_cii(w, true, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> FromXml<'a> for ReductionAndSurchargeListLineItemDetails<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(ReductionAndSurchargeListLineItemDetails {
+            reduction_list_line_items: non_empty(node.children_from_xml("ReductionListLineItem")?),
+            surcharge_list_line_items: non_empty(node.children_from_xml("SurchargeListLineItem")?),
+        })
+    }
+}
 
+impl Validate for ReductionAndSurchargeListLineItemDetails<'_> {
+    fn validate(&self, v: &mut Validator) {
         if let Some(reduction_list_line_items) = &self.reduction_list_line_items {
-            for reduction_list_line_item in reduction_list_line_items {
-                e = e.with_element(reduction_list_line_item);
-            }
+            v.children("ReductionListLineItem", reduction_list_line_items);
         }
 
         if let Some(surcharge_list_line_items) = &self.surcharge_list_line_items {
-            for surcharge_list_line_item in surcharge_list_line_items {
-                e = e.with_element(surcharge_list_line_item);
-            }
+            v.children("SurchargeListLineItem", surcharge_list_line_items);
+        }
+    }
+}
+
+/// A reduction on the whole invoice, e.g. an early-order discount.
+#[derive(Debug, PartialEq)]
+pub struct Reduction<'a> {
+    base: ReductionAndSurchargeListLineItemBase<'a>,
+    tax_item: TaxItem,
+}
+
+impl<'a> Reduction<'a> {
+    pub fn new(base_amount: Decimal, value: ReductionAndSurchargeValue, tax_item: TaxItem) -> Self {
+        Reduction {
+            base: ReductionAndSurchargeListLineItemBase::new(base_amount, value),
+            tax_item,
+        }
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.base.comment = Some(comment.into());
+        self
+    }
+
+    pub fn into_owned(self) -> Reduction<'static> {
+        Reduction {
+            base: self.base.into_owned(),
+            tax_item: self.tax_item,
+        }
+    }
+
+    fn sum(&self, rounding: RoundingPolicy) -> Decimal {
+        self.base.sum(rounding)
+    }
+}
+
+impl ToXml for Reduction<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        let rounding = w.rounding();
+        let tax_item = self.tax_item.taxable_amount(self.sum(rounding), rounding);
+        self.base.write_xml(w, "Reduction", Some(&tax_item))
+    }
+}
+
+impl<'a> FromXml<'a> for Reduction<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(Reduction {
+            base: ReductionAndSurchargeListLineItemBase::from_xml(node)?,
+            tax_item: TaxItem::from_xml(node.required_child("TaxItem")?)?,
+        })
+    }
+}
+
+impl Validate for Reduction<'_> {
+    fn validate(&self, v: &mut Validator) {
+        self.base.validate(v);
+        v.child("TaxItem", |v| self.tax_item.validate(v));
+    }
+}
+
+/// A surcharge on the whole invoice, e.g. a flat freight surcharge.
+#[derive(Debug, PartialEq)]
+pub struct Surcharge<'a> {
+    base: ReductionAndSurchargeListLineItemBase<'a>,
+    tax_item: TaxItem,
+}
+
+impl<'a> Surcharge<'a> {
+    pub fn new(base_amount: Decimal, value: ReductionAndSurchargeValue, tax_item: TaxItem) -> Self {
+        Surcharge {
+            base: ReductionAndSurchargeListLineItemBase::new(base_amount, value),
+            tax_item,
+        }
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<Cow<'a, str>>) -> Self {
+        self.base.comment = Some(comment.into());
+        self
+    }
+
+    pub fn into_owned(self) -> Surcharge<'static> {
+        Surcharge {
+            base: self.base.into_owned(),
+            tax_item: self.tax_item,
+        }
+    }
+
+    fn sum(&self, rounding: RoundingPolicy) -> Decimal {
+        self.base.sum(rounding)
+    }
+}
+
+impl ToXml for Surcharge<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        let rounding = w.rounding();
+        let tax_item = self.tax_item.taxable_amount(self.sum(rounding), rounding);
+        self.base.write_xml(w, "Surcharge", Some(&tax_item))
+    }
+}
+
+impl<'a> FromXml<'a> for Surcharge<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(Surcharge {
+            base: ReductionAndSurchargeListLineItemBase::from_xml(node)?,
+            tax_item: TaxItem::from_xml(node.required_child("TaxItem")?)?,
+        })
+    }
+}
+
+impl Validate for Surcharge<'_> {
+    fn validate(&self, v: &mut Validator) {
+        self.base.validate(v);
+        v.child("TaxItem", |v| self.tax_item.validate(v));
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ReductionAndSurchargeDetails<'a> {
+    reductions: Vec<Reduction<'a>>,
+    surcharges: Vec<Surcharge<'a>>,
+}
+
+impl<'a> ReductionAndSurchargeDetails<'a> {
+    pub(crate) fn with_reduction(mut self, reduction: Reduction<'a>) -> Self {
+        self.reductions.push(reduction);
+        self
+    }
+
+    pub(crate) fn with_surcharge(mut self, surcharge: Surcharge<'a>) -> Self {
+        self.surcharges.push(surcharge);
+        self
+    }
+
+    pub(crate) fn into_owned(self) -> ReductionAndSurchargeDetails<'static> {
+        ReductionAndSurchargeDetails {
+            reductions: self.reductions.into_iter().map(Reduction::into_owned).collect(),
+            surcharges: self.surcharges.into_iter().map(Surcharge::into_owned).collect(),
         }
+    }
 
-        e.to_xml()
+    /// Returns the sum of all reductions and the sum of all surcharges.
+    pub(crate) fn allowance_and_charge_totals(&self, rounding: RoundingPolicy) -> (Decimal, Decimal) {
+        (
+            self.reductions.iter().fold(Decimal::ZERO, |sum, r| sum + r.sum(rounding)),
+            self.surcharges.iter().fold(Decimal::ZERO, |sum, s| sum + s.sum(rounding)),
+        )
+    }
+
+    /// Returns the signed amount of every reduction (negative) and surcharge (positive),
+    /// together with its `TaxItem`.
+    pub(crate) fn taxable_amounts(&self, rounding: RoundingPolicy) -> Vec<(&TaxItem, Decimal)> {
+        self.reductions
+            .iter()
+            .map(|r| (&r.tax_item, -r.sum(rounding)))
+            .chain(self.surcharges.iter().map(|s| (&s.tax_item, s.sum(rounding))))
+            .collect()
+    }
+}
+
+impl ToXml for ReductionAndSurchargeDetails<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("ReductionAndSurchargeDetails")?;
+
+        for reduction in &self.reductions {
+            w.element(reduction)?;
+        }
+
+        for surcharge in &self.surcharges {
+            w.element(surcharge)?;
+        }
+
+        w.end("ReductionAndSurchargeDetails")
+    }
+}
+
+impl ToUbl for ReductionAndSurchargeDetails<'_> {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        for reduction in &self.reductions {
+            reduction.base.write_ubl(w, false, Some(&reduction.tax_item))?;
+        }
+
+        for surcharge in &self.surcharges {
+            surcharge.base.write_ubl(w, true, Some(&surcharge.tax_item))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToCii for ReductionAndSurchargeDetails<'_> {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        for reduction in &self.reductions {
+            reduction.base.write_cii(w, false, Some(&reduction.tax_item))?;
+        }
+
+        for surcharge in &self.surcharges {
+            surcharge.base.write_cii(w, true, Some(&surcharge.tax_item))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> FromXml<'a> for ReductionAndSurchargeDetails<'a> {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        Ok(ReductionAndSurchargeDetails {
+            reductions: node.children_from_xml("Reduction")?,
+            surcharges: node.children_from_xml("Surcharge")?,
+        })
+    }
+}
+
+impl Validate for ReductionAndSurchargeDetails<'_> {
+    fn validate(&self, v: &mut Validator) {
+        v.children("Reduction", &self.reductions);
+        v.children("Surcharge", &self.surcharges);
     }
 }
 
@@ -205,7 +695,7 @@ impl ToXml for ReductionAndSurchargeListLineItemDetails<'_> {
 mod tests {
     use super::*;
 
-    use crate::xml::ToXml;
+    use crate::{tax::TaxCategory, xml::ToXml};
 
     #[test]
     fn generates_reduction_and_surcharge_list_line_item() {
@@ -224,11 +714,12 @@ mod tests {
                 )
                 .with_comment("surcharge"),
             )
+            .line_price(Decimal::from(100))
             .to_xml();
 
         assert_eq!(
             result,
-            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage><Comment>reduction</Comment></ReductionListLineItem><SurchargeListLineItem><BaseAmount>200.00</BaseAmount><Amount>3.00</Amount><Comment>surcharge</Comment></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails>"
+            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage><Comment>reduction</Comment></ReductionListLineItem><SurchargeListLineItem><BaseAmount>200.00</BaseAmount><Amount>3.00</Amount><Comment>surcharge</Comment></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails>",
         );
 
         let result = ReductionAndSurchargeListLineItemDetails::new()
@@ -246,11 +737,12 @@ mod tests {
                 )
                 .with_comment("surcharge"),
             )
+            .line_price(Decimal::from(100))
             .to_xml();
 
         assert_eq!(
             result,
-            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Amount>2.00</Amount><Comment>reduction</Comment></ReductionListLineItem><SurchargeListLineItem><BaseAmount>200.00</BaseAmount><Percentage>3.00</Percentage><Comment>surcharge</Comment></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails>"
+            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Amount>2.00</Amount><Comment>reduction</Comment></ReductionListLineItem><SurchargeListLineItem><BaseAmount>200.00</BaseAmount><Percentage>3.00</Percentage><Comment>surcharge</Comment></SurchargeListLineItem></ReductionAndSurchargeListLineItemDetails>",
         );
 
         let result = ReductionAndSurchargeListLineItemDetails::new()
@@ -264,11 +756,36 @@ mod tests {
                 )
                 .with_comment("reduction"),
             )
+            .line_price(Decimal::from(100))
+            .to_xml();
+
+        assert_eq!(
+            result,
+            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage><Amount>3.46</Amount><Comment>reduction</Comment></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails>",
+        );
+    }
+
+    #[test]
+    fn generates_reduction_and_surcharge_details() {
+        let result = ReductionAndSurchargeDetails::default()
+            .with_reduction(
+                Reduction::new(
+                    Decimal::from(1000),
+                    ReductionAndSurchargeValue::Percentage(Decimal::from(3)),
+                    TaxItem::new(Decimal::from(20), TaxCategory::S),
+                )
+                .with_comment("early-order discount"),
+            )
+            .with_surcharge(Surcharge::new(
+                Decimal::from(1000),
+                ReductionAndSurchargeValue::Amount(Decimal::from(15)),
+                TaxItem::new(Decimal::from(20), TaxCategory::S),
+            ))
             .to_xml();
 
         assert_eq!(
             result,
-            "<ReductionAndSurchargeListLineItemDetails><ReductionListLineItem><BaseAmount>100.00</BaseAmount><Percentage>2.00</Percentage><Amount>3.46</Amount><Comment>reduction</Comment></ReductionListLineItem></ReductionAndSurchargeListLineItemDetails>"
+            "<ReductionAndSurchargeDetails><Reduction><BaseAmount>1000.00</BaseAmount><Percentage>3.00</Percentage><TaxItem><TaxableAmount>30.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>6.00</TaxAmount></TaxItem><Comment>early-order discount</Comment></Reduction><Surcharge><BaseAmount>1000.00</BaseAmount><Amount>15.00</Amount><TaxItem><TaxableAmount>15.00</TaxableAmount><TaxPercent TaxCategoryCode=\"S\">20</TaxPercent><TaxAmount>3.00</TaxAmount></TaxItem></Surcharge></ReductionAndSurchargeDetails>",
         );
     }
 }
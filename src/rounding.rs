@@ -0,0 +1,69 @@
+//! How and where amounts get rounded to cents.
+//!
+//! By default, every reduction and surcharge and every line item amount gets rounded and the tax gets calculated and
+//! rounded once per tax rate, all with `MidpointAwayFromZero`. The total gross amount is the sum of these rounded
+//! amounts, so it matches the tax summary written into the documents.
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Where the tax amounts get rounded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TaxRounding {
+    /// The tax gets calculated from the taxable amount per tax rate and rounded once.
+    #[default]
+    PerRate,
+    /// The tax gets calculated and rounded per line item (and per reduction or surcharge on the whole invoice), the
+    /// tax per tax rate is the sum of the rounded amounts.
+    PerLine,
+}
+
+/// The rounding of an invoice, see [`Invoice::with_rounding_policy`](crate::invoice::Invoice::with_rounding_policy).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundingPolicy {
+    strategy: RoundingStrategy,
+    tax_rounding: TaxRounding,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        RoundingPolicy::new(RoundingStrategy::MidpointAwayFromZero)
+    }
+}
+
+impl RoundingPolicy {
+    /// Rounds with the given strategy, e.g. `MidpointNearestEven` for banker's rounding, and the tax per tax rate.
+    pub fn new(strategy: RoundingStrategy) -> Self {
+        RoundingPolicy {
+            strategy,
+            tax_rounding: TaxRounding::default(),
+        }
+    }
+
+    pub fn with_tax_rounding(mut self, tax_rounding: TaxRounding) -> Self {
+        self.tax_rounding = tax_rounding;
+        self
+    }
+
+    pub fn strategy(&self) -> RoundingStrategy {
+        self.strategy
+    }
+
+    pub fn tax_rounding(&self) -> TaxRounding {
+        self.tax_rounding
+    }
+
+    /// Rounds the amount to cents, keeping two decimal places.
+    pub(crate) fn round(&self, amount: Decimal) -> Decimal {
+        let mut rounded = amount.round_dp_with_strategy(2, self.strategy);
+        rounded.rescale(2);
+        rounded
+    }
+
+    /// Rounds the tax amount of a single line item, if the tax gets rounded per line.
+    pub(crate) fn line_tax(&self, tax_amount: Decimal) -> Decimal {
+        match self.tax_rounding {
+            TaxRounding::PerRate => tax_amount,
+            TaxRounding::PerLine => self.round(tax_amount),
+        }
+    }
+}
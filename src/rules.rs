@@ -0,0 +1,463 @@
+//! Business rules, which go beyond the schema, e.g. whether the tax amounts add up.
+//!
+//! The rule IDs are this crate's own; ebInterface doesn't number its rules. Where a rule corresponds to one of
+//! EN 16931 or the law, it's given as the source.
+//!
+//! | Rule                      | Severity | Checks                                                                     | Source                 |
+//! |---------------------------|----------|----------------------------------------------------------------------------|------------------------|
+//! | `tax-amount`              | error    | `TaxAmount` of each `TaxItem` is `TaxableAmount` × `TaxPercent` / 100      | EN 16931 BR-CO-17      |
+//! | `tax-summary`             | error    | the `Tax` summary matches the tax items of the line items, reductions and surcharges | EN 16931 BR-S-08 |
+//! | `total-gross-amount`      | error    | `TotalGrossAmount` is the sum of the taxable amounts and tax amounts of the `Tax` summary | EN 16931 BR-CO-15 |
+//! | `payable-amount`          | error    | `PayableAmount` is `TotalGrossAmount` − `PrepaidAmount` + `RoundingAmount` + below the line items | EN 16931 BR-CO-16 |
+//! | `federal-order-reference` | error    | federal recipients (`Rules::with_federal_recipient`) require an order reference | e-Rechnung an den Bund |
+//! | `recipient-vat-id`        | error    | invoices above € 10,000 (gross) require the VAT ID of the recipient        | § 11 Abs. 1 Z 2 UStG   |
+//! | `due-date`                | warning  | `DueDate` isn't before `InvoiceDate`                                       |                        |
+//!
+//! Amounts are compared exactly: a `TaxAmount` has to be rounded to the nearest cent, in the `Tax` summary it may
+//! also be the sum of the rounded tax amounts of its items, and the totals have to be the sums of the rounded amounts.
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    parse::{ParseError, ParseErrorKind, XmlNode, parse_document},
+    vat_id::VatId,
+};
+
+/// The gross amount (in EUR) above which the VAT ID of the recipient is required (§ 11 UStG).
+const VAT_ID_THRESHOLD: Decimal = Decimal::from_parts(10000, 0, 0, false, 0);
+
+const HALF_CENT: Decimal = Decimal::from_parts(5, 0, 0, false, 3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The document is wrong.
+    Error,
+    /// The document gets accepted, but is most likely wrong.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            }
+        )
+    }
+}
+
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+}
+
+pub const TAX_AMOUNT: Rule = Rule {
+    id: "tax-amount",
+    severity: Severity::Error,
+};
+pub const TAX_SUMMARY: Rule = Rule {
+    id: "tax-summary",
+    severity: Severity::Error,
+};
+pub const TOTAL_GROSS_AMOUNT: Rule = Rule {
+    id: "total-gross-amount",
+    severity: Severity::Error,
+};
+pub const PAYABLE_AMOUNT: Rule = Rule {
+    id: "payable-amount",
+    severity: Severity::Error,
+};
+pub const FEDERAL_ORDER_REFERENCE: Rule = Rule {
+    id: "federal-order-reference",
+    severity: Severity::Error,
+};
+pub const RECIPIENT_VAT_ID: Rule = Rule {
+    id: "recipient-vat-id",
+    severity: Severity::Error,
+};
+pub const DUE_DATE: Rule = Rule {
+    id: "due-date",
+    severity: Severity::Warning,
+};
+
+/// A violated rule, with the path of the affected element (e.g. `Details/ItemList/ListLineItem[2]/TaxItem`)
+/// and its (1-based) line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleViolation {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {} at line {}, column {}",
+            self.severity, self.rule, self.path, self.message, self.line, self.column
+        )
+    }
+}
+
+/// The rules engine. Use `Invoice::check_rules` for invoices built with this crate
+/// and `Rules::check` for any other ebInterface document.
+#[derive(Debug, Default)]
+pub struct Rules {
+    federal_recipient: bool,
+}
+
+impl Rules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The invoice is sent to the federal government (e-Rechnung an den Bund),
+    /// which requires an order reference.
+    pub fn with_federal_recipient(mut self, federal_recipient: bool) -> Self {
+        self.federal_recipient = federal_recipient;
+        self
+    }
+
+    /// Checks all rules against the given ebInterface document. Elements, which are missing or can't be read,
+    /// are left to the schema validation, so the rules depending on them get skipped.
+    pub fn check(&self, xml: &str) -> Result<Vec<RuleViolation>, ParseError> {
+        let root = parse_document(xml)?;
+        if root.name != "Invoice" {
+            return Err(root.error(ParseErrorKind::UnexpectedElement(root.name.to_string())));
+        }
+
+        let mut checker = Checker { violations: Vec::new() };
+        checker.tax(&root);
+        checker.payable_amount(&root);
+        checker.recipient(&root, self.federal_recipient);
+        checker.due_date(&root);
+        Ok(checker.violations)
+    }
+}
+
+fn decimal(node: &XmlNode, name: &str) -> Option<Decimal> {
+    node.child_decimal(name).ok().flatten()
+}
+
+/// Returns the tax percent and category of a `TaxItem`, together with its taxable amount and tax amount.
+fn tax_item(node: &XmlNode) -> Option<((Decimal, String), Decimal, Decimal)> {
+    let tax_percent = node.child("TaxPercent")?;
+    Some((
+        (tax_percent.decimal().ok()?.normalize(), tax_percent.attr("TaxCategoryCode")?.into_owned()),
+        decimal(node, "TaxableAmount")?,
+        decimal(node, "TaxAmount")?,
+    ))
+}
+
+/// Numbers repeated elements like `ListLineItem[2]`.
+fn indexed<'n, 'a>(node: &'n XmlNode<'a>, name: &'n str) -> impl Iterator<Item = (String, &'n XmlNode<'a>)> {
+    node.children(name)
+        .enumerate()
+        .map(move |(i, c)| (format!("{name}[{}]", i + 1), c))
+}
+
+struct Checker {
+    violations: Vec<RuleViolation>,
+}
+
+impl Checker {
+    fn report(&mut self, rule: &Rule, node: &XmlNode, path: impl Into<String>, message: String) {
+        self.violations.push(RuleViolation {
+            rule: rule.id,
+            severity: rule.severity,
+            path: path.into(),
+            line: node.line,
+            column: node.column,
+            message,
+        });
+    }
+
+    /// Checks the tax amount of a `TaxItem`, which has to be rounded to the nearest cent (either way at the midpoint)
+    /// or, in the `Tax` summary, may be the sum of the rounded tax amounts of its items.
+    fn tax_amount(
+        &mut self,
+        node: &XmlNode,
+        path: &str,
+        items_tax_amount: Option<Decimal>,
+    ) -> Option<((Decimal, String), Decimal, Decimal)> {
+        let item = tax_item(node)?;
+        let ((tax_percent, _), taxable_amount, tax_amount) = &item;
+        let exact = taxable_amount * tax_percent / Decimal::ONE_HUNDRED;
+        let expected = exact.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+        let rounded = tax_amount.round_dp(2) == *tax_amount && (tax_amount - exact).abs() <= HALF_CENT;
+        if !rounded && items_tax_amount != Some(*tax_amount) {
+            self.report(
+                &TAX_AMOUNT,
+                node,
+                path,
+                format!("TaxAmount {tax_amount} should be {expected} ({taxable_amount} × {tax_percent} %)"),
+            );
+        }
+        Some(item)
+    }
+
+    fn tax(&mut self, root: &XmlNode) {
+        // Taxable amounts and tax amounts of the line items and root level reductions and surcharges, by tax percent
+        // and category.
+        let mut expected: BTreeMap<(Decimal, String), (Decimal, Decimal)> = BTreeMap::new();
+
+        if let Some(details) = root.child("Details") {
+            let lists = details.children("ItemList").count();
+            for (list_path, list) in indexed(details, "ItemList") {
+                let list_path = if lists > 1 { list_path } else { "ItemList".to_string() };
+                for (item_path, item) in indexed(list, "ListLineItem") {
+                    let path = format!("Details/{list_path}/{item_path}/TaxItem");
+                    if let Some(tax_item) = item.child("TaxItem")
+                        && let Some((key, taxable_amount, tax_amount)) = self.tax_amount(tax_item, &path, None)
+                    {
+                        let sum = expected.entry(key).or_default();
+                        sum.0 += taxable_amount;
+                        sum.1 += tax_amount;
+                    }
+                }
+            }
+        }
+
+        if let Some(rs) = root.child("ReductionAndSurchargeDetails") {
+            for (name, sign) in [("Reduction", Decimal::NEGATIVE_ONE), ("Surcharge", Decimal::ONE)] {
+                for (path, node) in indexed(rs, name) {
+                    let path = format!("ReductionAndSurchargeDetails/{path}/TaxItem");
+                    if let Some(tax_item) = node.child("TaxItem")
+                        && let Some((key, taxable_amount, tax_amount)) = self.tax_amount(tax_item, &path, None)
+                    {
+                        let sum = expected.entry(key).or_default();
+                        sum.0 += sign * taxable_amount;
+                        sum.1 += sign * tax_amount;
+                    }
+                }
+            }
+        }
+
+        let Some(tax) = root.child("Tax") else {
+            return;
+        };
+
+        let mut total = Decimal::ZERO;
+        for (path, node) in indexed(tax, "TaxItem") {
+            let path = format!("Tax/{path}");
+            let items_tax_amount = tax_item(node)
+                .and_then(|(key, _, _)| expected.get(&key))
+                .map(|sum| sum.1);
+            let Some((key, taxable_amount, tax_amount)) = self.tax_amount(node, &path, items_tax_amount) else {
+                return;
+            };
+            total += taxable_amount + tax_amount;
+
+            let (tax_percent, tax_category) = &key;
+            match expected.remove(&key).map(|sum| sum.0) {
+                Some(sum) if sum == taxable_amount => {}
+                sum => self.report(
+                    &TAX_SUMMARY,
+                    node,
+                    path,
+                    format!(
+                        "TaxableAmount {taxable_amount} ({tax_percent} %, {tax_category}) should be {}",
+                        sum.unwrap_or_default()
+                    ),
+                ),
+            }
+        }
+        for ((tax_percent, tax_category), (sum, _)) in expected {
+            self.report(
+                &TAX_SUMMARY,
+                tax,
+                "Tax",
+                format!("missing TaxItem for {tax_percent} % ({tax_category}) with TaxableAmount {sum}"),
+            );
+        }
+
+        if let Some(total_gross_amount) = decimal(root, "TotalGrossAmount")
+            && total_gross_amount != total
+        {
+            let node = root.child("TotalGrossAmount").unwrap_or(root);
+            self.report(
+                &TOTAL_GROSS_AMOUNT,
+                node,
+                "TotalGrossAmount",
+                format!("TotalGrossAmount {total_gross_amount} should be {total}"),
+            );
+        }
+    }
+
+    fn payable_amount(&mut self, root: &XmlNode) {
+        let (Some(total_gross_amount), Some(payable_amount)) =
+            (decimal(root, "TotalGrossAmount"), decimal(root, "PayableAmount"))
+        else {
+            return;
+        };
+
+        let below_the_line_items = root
+            .children("BelowTheLineItem")
+            .filter_map(|i| decimal(i, "LineItemAmount"))
+            .sum::<Decimal>();
+        let expected = total_gross_amount - decimal(root, "PrepaidAmount").unwrap_or_default()
+            + decimal(root, "RoundingAmount").unwrap_or_default()
+            + below_the_line_items;
+
+        if payable_amount != expected {
+            let node = root.child("PayableAmount").unwrap_or(root);
+            self.report(
+                &PAYABLE_AMOUNT,
+                node,
+                "PayableAmount",
+                format!("PayableAmount {payable_amount} should be {expected}"),
+            );
+        }
+    }
+
+    fn recipient(&mut self, root: &XmlNode, federal_recipient: bool) {
+        let Some(recipient) = root.child("InvoiceRecipient") else {
+            return;
+        };
+
+        let order_id = recipient
+            .child("OrderReference")
+            .and_then(|r| r.child_text("OrderID"))
+            .unwrap_or_default();
+        if federal_recipient && order_id.trim().is_empty() {
+            self.report(
+                &FEDERAL_ORDER_REFERENCE,
+                recipient,
+                "InvoiceRecipient/OrderReference/OrderID",
+                "federal recipients require an order reference".to_string(),
+            );
+        }
+
+        let vat_id = recipient.child_text("VATIdentificationNumber").unwrap_or_default();
+        if root.attr("InvoiceCurrency").as_deref() == Some("EUR")
+            && let Some(total_gross_amount) = decimal(root, "TotalGrossAmount")
+            && total_gross_amount > VAT_ID_THRESHOLD
+            && (vat_id.trim().is_empty() || vat_id == VatId::none().as_str())
+        {
+            self.report(
+                &RECIPIENT_VAT_ID,
+                recipient,
+                "InvoiceRecipient/VATIdentificationNumber",
+                format!("invoices above {VAT_ID_THRESHOLD} EUR require the VAT ID of the recipient"),
+            );
+        }
+    }
+
+    fn due_date(&mut self, root: &XmlNode) {
+        // Both dates are xs:date, so they can be compared as strings.
+        if let Some(invoice_date) = root.child_text("InvoiceDate")
+            && let Some(conditions) = root.child("PaymentConditions")
+            && let Some(due_date) = conditions.child("DueDate")
+            && due_date.text().trim() < invoice_date.trim()
+        {
+            self.report(
+                &DUE_DATE,
+                due_date,
+                "PaymentConditions/DueDate",
+                format!("DueDate {} is before InvoiceDate {invoice_date}", due_date.text()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<Invoice xmlns="http://www.ebinterface.at/schema/6p1/" GeneratingSystem="test" DocumentType="Invoice" InvoiceCurrency="EUR">
+<InvoiceNumber>1</InvoiceNumber><InvoiceDate>2020-01-10</InvoiceDate>
+<Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller>
+<InvoiceRecipient><VATIdentificationNumber>00000000</VATIdentificationNumber></InvoiceRecipient>
+<Details><ItemList>
+<ListLineItem><Quantity Unit="STK">1</Quantity><UnitPrice>10000</UnitPrice><TaxItem><TaxableAmount>10000.00</TaxableAmount><TaxPercent TaxCategoryCode="S">20</TaxPercent><TaxAmount>2000.00</TaxAmount></TaxItem><LineItemAmount>10000.00</LineItemAmount></ListLineItem>
+<ListLineItem><Quantity Unit="STK">1</Quantity><UnitPrice>100</UnitPrice><TaxItem><TaxableAmount>100.00</TaxableAmount><TaxPercent TaxCategoryCode="AA">10</TaxPercent><TaxAmount>20.00</TaxAmount></TaxItem><LineItemAmount>100.00</LineItemAmount></ListLineItem>
+</ItemList></Details>
+<Tax><TaxItem><TaxableAmount>10000.00</TaxableAmount><TaxPercent TaxCategoryCode="S">20</TaxPercent><TaxAmount>2000.00</TaxAmount></TaxItem></Tax>
+<TotalGrossAmount>12110.00</TotalGrossAmount><PayableAmount>12100.00</PayableAmount>
+<PaymentConditions><DueDate>2020-01-01</DueDate></PaymentConditions>
+</Invoice>"#;
+
+    #[test]
+    fn reports_violated_rules() {
+        assert_eq!(
+            Rules::new()
+                .with_federal_recipient(true)
+                .check(XML)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "error tax-amount Details/ItemList/ListLineItem[2]/TaxItem: TaxAmount 20.00 should be 10.00 (100.00 × 10 %) at line 7, column 74",
+                "error tax-summary Tax: missing TaxItem for 10 % (AA) with TaxableAmount 100.00 at line 9, column 1",
+                "error total-gross-amount TotalGrossAmount: TotalGrossAmount 12110.00 should be 12000.00 at line 10, column 1",
+                "error payable-amount PayableAmount: PayableAmount 12100.00 should be 12110.00 at line 10, column 46",
+                "error federal-order-reference InvoiceRecipient/OrderReference/OrderID: federal recipients require an order reference at line 4, column 1",
+                "error recipient-vat-id InvoiceRecipient/VATIdentificationNumber: invoices above 10000 EUR require the VAT ID of the recipient at line 4, column 1",
+                "warning due-date PaymentConditions/DueDate: DueDate 2020-01-01 is before InvoiceDate 2020-01-10 at line 11, column 20",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_optional_rules() {
+        let xml = XML
+            .replace("<TaxAmount>20.00</TaxAmount>", "<TaxAmount>10.00</TaxAmount>")
+            .replace("</TaxItem></Tax>", "</TaxItem><TaxItem><TaxableAmount>100.00</TaxableAmount><TaxPercent TaxCategoryCode=\"AA\">10</TaxPercent><TaxAmount>10.00</TaxAmount></TaxItem></Tax>")
+            .replace("<PayableAmount>", "<RoundingAmount>-10.00</RoundingAmount><PayableAmount>")
+            .replace("00000000", "ATU18708637")
+            .replace("2020-01-01", "2020-01-31");
+
+        assert_eq!(Rules::new().check(&xml).unwrap(), vec![]);
+        assert_eq!(
+            Rules::new().check("<Biller/>").unwrap_err().kind,
+            ParseErrorKind::UnexpectedElement("Biller".to_string())
+        );
+    }
+
+    #[test]
+    fn compares_amounts_exactly() {
+        let xml = r#"<Invoice xmlns="http://www.ebinterface.at/schema/6p1/" GeneratingSystem="test" DocumentType="Invoice" InvoiceCurrency="EUR">
+<InvoiceNumber>1</InvoiceNumber><InvoiceDate>2020-01-10</InvoiceDate>
+<Biller><VATIdentificationNumber>ATU51507409</VATIdentificationNumber></Biller>
+<InvoiceRecipient><VATIdentificationNumber>ATU18708637</VATIdentificationNumber></InvoiceRecipient>
+<Details><ItemList>
+<ListLineItem><Quantity Unit="STK">1</Quantity><UnitPrice>0.05</UnitPrice><TaxItem><TaxableAmount>0.05</TaxableAmount><TaxPercent TaxCategoryCode="S">10</TaxPercent><TaxAmount>0.01</TaxAmount></TaxItem><LineItemAmount>0.05</LineItemAmount></ListLineItem>
+<ListLineItem><Quantity Unit="STK">1</Quantity><UnitPrice>0.05</UnitPrice><TaxItem><TaxableAmount>0.05</TaxableAmount><TaxPercent TaxCategoryCode="S">10</TaxPercent><TaxAmount>0.01</TaxAmount></TaxItem><LineItemAmount>0.05</LineItemAmount></ListLineItem>
+</ItemList></Details>
+<Tax><TaxItem><TaxableAmount>0.10</TaxableAmount><TaxPercent TaxCategoryCode="S">10</TaxPercent><TaxAmount>0.02</TaxAmount></TaxItem></Tax>
+<TotalGrossAmount>0.12</TotalGrossAmount><PayableAmount>0.12</PayableAmount>
+</Invoice>"#;
+
+        // The tax amounts of the items may be rounded at the midpoint either way, and summed up in the summary.
+        assert_eq!(Rules::new().check(xml).unwrap(), vec![]);
+        assert_eq!(
+            Rules::new()
+                .check(&xml.replace("0.12", "0.13"))
+                .unwrap()
+                .iter()
+                .map(|v| v.rule)
+                .collect::<Vec<_>>(),
+            vec![TOTAL_GROSS_AMOUNT.id]
+        );
+        assert_eq!(
+            Rules::new()
+                .check(&xml.replace("<TaxAmount>0.02</TaxAmount>", "<TaxAmount>0.03</TaxAmount>"))
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "error tax-amount Tax/TaxItem[1]: TaxAmount 0.03 should be 0.01 (0.10 × 10 %) at line 9, column 6",
+                "error total-gross-amount TotalGrossAmount: TotalGrossAmount 0.12 should be 0.13 at line 10, column 1",
+            ]
+        );
+    }
+}
@@ -1,11 +1,18 @@
 use rust_decimal::Decimal;
+use std::{fmt, str::FromStr};
 
 use crate::{
-    decimal::CloneAndRescale,
-    xml::{ToXml, XmlElement},
+    cii::{CiiWriter, ToCii},
+    error::Error,
+    parse::{FromXml, ParseError, XmlNode},
+    rounding::RoundingPolicy,
+    ubl::{ToUbl, UblWriter, write_tax_scheme},
+    validation::{Validate, Validator},
+    version::EbInterfaceVersion,
+    xml::{ToXml, XmlWriter},
 };
 
-#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone, Default)]
+#[derive(Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone, Default)]
 pub enum TaxCategory {
     #[default]
     S,
@@ -45,7 +52,70 @@ impl std::fmt::Display for TaxCategory {
     }
 }
 
-#[derive(Default)]
+impl TaxCategory {
+    /// The code of the VAT category in EN 16931 (a subset of UNCL5305), where reduced rates (`AA`) are standard
+    /// rated (`S`) as well. `D`, `F`, `I` and `J` have no equivalent.
+    pub(crate) fn en16931_code(&self) -> Option<&'static str> {
+        match self {
+            TaxCategory::S | TaxCategory::AA => Some("S"),
+            TaxCategory::O => Some("O"),
+            TaxCategory::E => Some("E"),
+            TaxCategory::G => Some("G"),
+            TaxCategory::K => Some("K"),
+            TaxCategory::AE => Some("AE"),
+            TaxCategory::Z => Some("Z"),
+            TaxCategory::D | TaxCategory::F | TaxCategory::I | TaxCategory::J => None,
+        }
+    }
+
+    /// The category of the given EN 16931 code, `None` for codes without equivalent (`L` and `M`) and unknown codes.
+    pub(crate) fn from_en16931_code(code: &str) -> Option<TaxCategory> {
+        code.parse()
+            .ok()
+            .filter(|c: &TaxCategory| c.en16931_code() == Some(code))
+    }
+
+    /// The VATEX exemption reason code, which EN 16931 requires for the categories without VAT (except `E`, which
+    /// has a reason code per exemption).
+    fn vatex_code(&self) -> Option<&'static str> {
+        match self {
+            TaxCategory::G => Some("VATEX-EU-G"),
+            TaxCategory::K => Some("VATEX-EU-IC"),
+            TaxCategory::AE => Some("VATEX-EU-AE"),
+            TaxCategory::O => Some("VATEX-EU-O"),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for TaxCategory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "S" => TaxCategory::S,
+            "AA" => TaxCategory::AA,
+            "O" => TaxCategory::O,
+            "D" => TaxCategory::D,
+            "E" => TaxCategory::E,
+            "F" => TaxCategory::F,
+            "G" => TaxCategory::G,
+            "I" => TaxCategory::I,
+            "J" => TaxCategory::J,
+            "K" => TaxCategory::K,
+            "AE" => TaxCategory::AE,
+            "Z" => TaxCategory::Z,
+            _ => {
+                return Err(Error::UnknownValue {
+                    field: "TaxCategory",
+                    value: s.to_string(),
+                });
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct TaxItem {
     pub(crate) tax_percent: Decimal,
     pub(crate) tax_category: TaxCategory,
@@ -59,36 +129,183 @@ impl TaxItem {
         }
     }
 
-    pub(crate) fn taxable_amount(&self, taxable_amount: Decimal) -> TaxItemWithTaxableAmount {
+    pub(crate) fn check_version(&self, version: EbInterfaceVersion) -> Result<(), Error> {
+        if version.supports_tax_category(self.tax_category) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedByVersion {
+                version,
+                construct: format!("TaxCategoryCode {}", self.tax_category),
+            })
+        }
+    }
+
+    pub(crate) fn check_format(&self, format: &'static str) -> Result<(), Error> {
+        match self.tax_category.en16931_code() {
+            Some(_) => Ok(()),
+            None => Err(Error::UnsupportedByFormat {
+                format,
+                construct: format!("TaxCategoryCode {}", self.tax_category),
+            }),
+        }
+    }
+
+    /// Writes the UBL tax category with the given name (`TaxCategory` or `ClassifiedTaxCategory` of an item).
+    pub(crate) fn write_ubl(&self, w: &mut UblWriter, name: &str) -> fmt::Result {
+        write_ubl_tax_category(w, name, self.tax_percent, self.tax_category, false)
+    }
+
+    /// Writes the CII trade tax with the given name (`ApplicableTradeTax` of an item or `CategoryTradeTax` of a
+    /// reduction or surcharge).
+    pub(crate) fn write_cii(&self, w: &mut CiiWriter, name: &str) -> fmt::Result {
+        w.start(name)?;
+        w.text_element("ram:TypeCode", "VAT")?;
+        w.text_element("ram:CategoryCode", self.tax_category.en16931_code().unwrap_or_default())?;
+        if self.tax_category != TaxCategory::O {
+            w.text_element("ram:RateApplicablePercent", self.tax_percent)?;
+        }
+        w.end(name)
+    }
+
+    pub(crate) fn taxable_amount(&self, taxable_amount: Decimal, rounding: RoundingPolicy) -> TaxItemWithTaxableAmount {
         TaxItemWithTaxableAmount {
             tax_percent: self.tax_percent,
             tax_category: self.tax_category,
             taxable_amount,
+            tax_amount: rounding.line_tax(taxable_amount * (self.tax_percent / Decimal::ONE_HUNDRED)),
+            rounding,
         }
     }
 }
 
-pub(crate) struct TaxItemWithTaxableAmount {
+/// A tax item together with its taxable amount, as written into the `TaxItem` of a line item or the tax summary.
+/// The taxable amount is already rounded to cents, like the line item amount or the amount of a reduction or
+/// surcharge it comes from. The tax amount is calculated from it and rounded with the rounding policy of the
+/// invoice, per line or, in the tax summary, per tax rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxItemWithTaxableAmount {
     tax_percent: Decimal,
     tax_category: TaxCategory,
     taxable_amount: Decimal,
+    tax_amount: Decimal,
+    rounding: RoundingPolicy,
+}
+
+impl TaxItemWithTaxableAmount {
+    pub fn tax_percent(&self) -> Decimal {
+        self.tax_percent
+    }
+
+    pub fn tax_category(&self) -> TaxCategory {
+        self.tax_category
+    }
+
+    pub fn taxable_amount(&self) -> Decimal {
+        self.rounding.round(self.taxable_amount)
+    }
+
+    pub fn tax_amount(&self) -> Decimal {
+        self.rounding.round(self.tax_amount)
+    }
+
+    /// Adds the amounts of another item of the same tax rate and category, for the tax summary.
+    pub(crate) fn add(&mut self, other: &TaxItemWithTaxableAmount) {
+        self.taxable_amount += other.taxable_amount;
+        self.tax_amount += other.tax_amount;
+    }
+
+    /// Writes the entry of the tax summary of ebInterface 4.3 and 5.0 (`Tax/VAT/VATItem`).
+    pub(crate) fn write_vat_item(&self, w: &mut XmlWriter) -> fmt::Result {
+        w.start("VATItem")?;
+        w.text_element("TaxedAmount", self.taxable_amount())?;
+        w.text_element(w.version().tax_rate_element(), self.tax_percent)?;
+        w.text_element("Amount", self.tax_amount())?;
+        w.end("VATItem")
+    }
 }
 
+/// Writes a `TaxItem`, or just the rate in ebInterface 4.3 (`VATRate`) and 5.0 (`TaxRate`).
 impl ToXml for TaxItemWithTaxableAmount {
-    fn to_xml(&self) -> String {
-        let tax_amount = self.taxable_amount * (self.tax_percent / Decimal::ONE_HUNDRED);
-
-        XmlElement::new("TaxItem")
-            .with_text_element(
-                "TaxableAmount",
-                self.taxable_amount.clone_with_scale(2).to_string(),
-            )
-            .with_element(
-                &XmlElement::new("TaxPercent")
-                    .with_attr("TaxCategoryCode", self.tax_category.to_string())
-                    .with_text(self.tax_percent.to_string()),
-            )
-            .with_text_element("TaxAmount", tax_amount.clone_with_scale(2).to_string())
-            .to_xml()
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+        if !w.version().has_tax_item() {
+            return w.text_element(w.version().tax_rate_element(), self.tax_percent);
+        }
+
+        w.start("TaxItem")?;
+        w.text_element("TaxableAmount", self.taxable_amount())?;
+        w.start("TaxPercent")?;
+        w.attr("TaxCategoryCode", self.tax_category)?;
+        w.text(self.tax_percent)?;
+        w.end("TaxPercent")?;
+        w.text_element("TaxAmount", self.tax_amount())?;
+        w.end("TaxItem")
+    }
+}
+
+/// Writes a UBL tax category. Categories without VAT (`O`) have no percentage and only the tax summary states the
+/// exemption reason.
+fn write_ubl_tax_category(
+    w: &mut UblWriter,
+    name: &str,
+    tax_percent: Decimal,
+    tax_category: TaxCategory,
+    exemption_reason: bool,
+) -> fmt::Result {
+    w.start(name)?;
+    w.text_element("cbc:ID", tax_category.en16931_code().unwrap_or_default())?;
+    if tax_category != TaxCategory::O {
+        w.text_element("cbc:Percent", tax_percent)?;
+    }
+    if exemption_reason && let Some(vatex_code) = tax_category.vatex_code() {
+        w.text_element("cbc:TaxExemptionReasonCode", vatex_code)?;
+    }
+    write_tax_scheme(w)?;
+    w.end(name)
+}
+
+/// Writes a UBL `TaxSubtotal` of the tax summary.
+impl ToUbl for TaxItemWithTaxableAmount {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result {
+        w.start("cac:TaxSubtotal")?;
+        w.amount("cbc:TaxableAmount", self.taxable_amount())?;
+        w.amount("cbc:TaxAmount", self.tax_amount())?;
+        write_ubl_tax_category(w, "cac:TaxCategory", self.tax_percent, self.tax_category, true)?;
+        w.end("cac:TaxSubtotal")
+    }
+}
+
+/// Writes a CII `ApplicableTradeTax` of the tax summary.
+impl ToCii for TaxItemWithTaxableAmount {
+    fn write_cii(&self, w: &mut CiiWriter) -> fmt::Result {
+        w.start("ram:ApplicableTradeTax")?;
+        w.amount("ram:CalculatedAmount", self.tax_amount())?;
+        w.text_element("ram:TypeCode", "VAT")?;
+        w.amount("ram:BasisAmount", self.taxable_amount())?;
+        w.text_element("ram:CategoryCode", self.tax_category.en16931_code().unwrap_or_default())?;
+        if let Some(vatex_code) = self.tax_category.vatex_code() {
+            w.text_element("ram:ExemptionReasonCode", vatex_code)?;
+        }
+        if self.tax_category != TaxCategory::O {
+            w.text_element("ram:RateApplicablePercent", self.tax_percent)?;
+        }
+        w.end("ram:ApplicableTradeTax")
+    }
+}
+
+/// Reads a `TaxItem` element. The taxable amount and the tax amount are derived from the line item
+/// when writing the invoice, so they get ignored.
+impl<'a> FromXml<'a> for TaxItem {
+    fn from_xml(node: &XmlNode<'a>) -> Result<Self, ParseError> {
+        let tax_percent = node.required_child("TaxPercent")?;
+        Ok(TaxItem {
+            tax_percent: tax_percent.decimal()?,
+            tax_category: tax_percent.required_attr_value("TaxCategoryCode")?,
+        })
+    }
+}
+
+impl Validate for TaxItem {
+    fn validate(&self, v: &mut Validator) {
+        v.percentage("TaxPercent", self.tax_percent);
     }
 }
@@ -0,0 +1,566 @@
+use rust_decimal::Decimal;
+use std::{
+    borrow::Cow,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    address::Address,
+    contact::Contact,
+    delivery::Delivery,
+    details::DetailsItem,
+    document::{DocumentType, RelatedDocument},
+    import::{AllowanceCharge, DerivedTotal, GENERATING_SYSTEM, Party, PaymentDetails, PaymentMeans, Reader},
+    invoice::{Invoice, Totals},
+    order_reference::OrderReference,
+    parse::{ParseError, ParseErrorKind, XmlNode},
+    payment_conditions::PaymentConditions,
+    payment_method::{PaymentMethodPaymentCard, PaymentMethodUniversalBankTransactionBeneficiaryAccount},
+    reduction_and_surcharge::ReductionAndSurchargeValue,
+    rounding::RoundingPolicy,
+    tax::TaxItem,
+    xml::XmlWriter,
+};
+
+pub(crate) const INVOICE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:Invoice-2";
+pub(crate) const CREDIT_NOTE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2";
+pub(crate) const CAC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2";
+pub(crate) const CBC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2";
+
+/// Peppol BIS Billing 3.0.
+pub(crate) const CUSTOMIZATION_ID: &str = "urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0";
+pub(crate) const PROFILE_ID: &str = "urn:fdc:peppol.eu:2017:poacc:billing:01:1.0";
+
+/// Writes a UBL 2.1 document. Amounts get the `currencyID` of the invoice and the names of the line elements depend
+/// on whether an `Invoice` or a `CreditNote` is written.
+pub(crate) struct UblWriter<'w, 'c> {
+    xml: XmlWriter<'w>,
+    currency: &'c str,
+    credit_note: bool,
+}
+
+impl<'w, 'c> UblWriter<'w, 'c> {
+    pub(crate) fn new(out: &'w mut dyn fmt::Write, currency: &'c str, credit_note: bool) -> Self {
+        UblWriter {
+            xml: XmlWriter::new(out),
+            currency,
+            credit_note,
+        }
+    }
+
+    pub(crate) fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.xml = self.xml.with_rounding(rounding);
+        self
+    }
+
+    pub(crate) fn currency(&self) -> &'c str {
+        self.currency
+    }
+
+    pub(crate) fn is_credit_note(&self) -> bool {
+        self.credit_note
+    }
+
+    pub(crate) fn amount(&mut self, name: &str, amount: Decimal) -> fmt::Result {
+        let currency = self.currency;
+        let amount = self.round(amount);
+        self.start(name)?;
+        self.attr("currencyID", currency)?;
+        self.text(amount)?;
+        self.end(name)
+    }
+
+    pub(crate) fn element(&mut self, element: &impl ToUbl) -> fmt::Result {
+        element.write_ubl(self)
+    }
+}
+
+impl<'w> Deref for UblWriter<'w, '_> {
+    type Target = XmlWriter<'w>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.xml
+    }
+}
+
+impl DerefMut for UblWriter<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.xml
+    }
+}
+
+pub(crate) trait ToUbl {
+    fn write_ubl(&self, w: &mut UblWriter) -> fmt::Result;
+}
+
+/// Writes the `Party` of the biller (`AccountingSupplierParty`) or the invoice recipient (`AccountingCustomerParty`).
+/// The VAT identification number `00000000` (none) gets omitted.
+pub(crate) fn write_party(
+    w: &mut UblWriter,
+    name: &str,
+    vat_identification_number: &str,
+    address: Option<&Address>,
+    contact: Option<&Contact>,
+) -> fmt::Result {
+    w.start(name)?;
+    w.start("cac:Party")?;
+
+    if let Some(address) = address {
+        w.start("cac:PartyName")?;
+        w.text_element("cbc:Name", address.name())?;
+        w.end("cac:PartyName")?;
+        address.write_ubl(w, "cac:PostalAddress")?;
+    }
+
+    if vat_identification_number != "00000000" {
+        w.start("cac:PartyTaxScheme")?;
+        w.text_element("cbc:CompanyID", vat_identification_number)?;
+        write_tax_scheme(w)?;
+        w.end("cac:PartyTaxScheme")?;
+    }
+
+    w.start("cac:PartyLegalEntity")?;
+    w.text_element("cbc:RegistrationName", address.map_or(vat_identification_number, Address::name))?;
+    w.end("cac:PartyLegalEntity")?;
+
+    match (contact, address) {
+        (Some(contact), _) => w.element(contact)?,
+        (None, Some(address)) => address.write_ubl_contact(w)?,
+        (None, None) => {}
+    }
+
+    w.end("cac:Party")?;
+    w.end(name)
+}
+
+pub(crate) fn write_tax_scheme(w: &mut UblWriter) -> fmt::Result {
+    w.start("cac:TaxScheme")?;
+    w.text_element("cbc:ID", "VAT")?;
+    w.end("cac:TaxScheme")
+}
+
+/// Reads a UBL 2.1 `Invoice` or `CreditNote`, the counterpart of the `ToUbl` implementations.
+pub(crate) fn read_invoice<'n, 'a>(r: &mut Reader<'n, 'a>, root: &'n XmlNode<'a>) -> Result<Invoice<'a>, ParseError> {
+    let (credit_note, namespace, type_code, line, quantity) = match root.name {
+        "Invoice" => (false, INVOICE_NAMESPACE, "InvoiceTypeCode", "InvoiceLine", "InvoicedQuantity"),
+        "CreditNote" => (true, CREDIT_NOTE_NAMESPACE, "CreditNoteTypeCode", "CreditNoteLine", "CreditedQuantity"),
+        name => return Err(root.error(ParseErrorKind::UnexpectedElement(name.to_string()))),
+    };
+    match root.namespace() {
+        Some(ns) if ns == namespace => {}
+        ns => return Err(root.error(ParseErrorKind::UnsupportedNamespace(ns.unwrap_or_default().to_string()))),
+    }
+
+    for name in ["UBLVersionID", "CustomizationID", "ProfileID"] {
+        r.skip(root, name);
+    }
+
+    let supplier = r.required_child(root, "AccountingSupplierParty")?;
+    let supplier = r.required_child(supplier, "Party")?;
+    let customer = r.required_child(root, "AccountingCustomerParty")?;
+    let customer = r.required_child(customer, "Party")?;
+
+    let biller = read_party(r, supplier).biller();
+    let mut invoice_recipient = read_party(r, customer).invoice_recipient();
+    if let Some(order_reference) = read_order_reference(r, root) {
+        invoice_recipient = invoice_recipient.with_order_reference(order_reference);
+    }
+
+    let mut invoice = Invoice::new(
+        GENERATING_SYSTEM,
+        r.required_text(root, "DocumentCurrencyCode")?,
+        r.required_text(root, "ID")?,
+        r.required_text(root, "IssueDate")?,
+        biller,
+        invoice_recipient,
+    );
+
+    let fallback = if credit_note {
+        DocumentType::CreditMemo
+    } else {
+        DocumentType::Invoice
+    };
+    let document_type = match r.child(root, type_code) {
+        Some(node) => DocumentType::from_type_code(&node.text()).unwrap_or_else(|| {
+            r.loss(node, format!("unknown type code {}, read as {fallback}", node.text()));
+            fallback
+        }),
+        None => fallback,
+    };
+    invoice = invoice.with_document_type(document_type);
+
+    if let Some(note) = r.text(root, &["Note"]) {
+        invoice = invoice.with_document_title(note);
+    }
+
+    if let Some(delivery) = read_delivery(r, root) {
+        invoice = invoice.with_delivery(delivery);
+    }
+
+    for billing_reference in r.children(root, "BillingReference") {
+        if let Some(document) = r.child(billing_reference, "InvoiceDocumentReference") {
+            let mut related_document = RelatedDocument::new(r.required_text(document, "ID")?);
+            if let Some(date) = r.text(document, &["IssueDate"]) {
+                related_document = related_document.with_invoice_date(date);
+            }
+            invoice = invoice.with_related_document(related_document);
+        }
+    }
+
+    let mut due_date = r.text(root, &["DueDate"]);
+    let mut payment_means = Vec::new();
+    let mut details = PaymentDetails {
+        creditor_id: read_creditor_id(r, supplier),
+        ..Default::default()
+    };
+    for node in r.children(root, "PaymentMeans") {
+        let means = read_payment_means(r, node, &mut details)?;
+        if !matches!(means, PaymentMeans::DirectDebit { .. }) && due_date.is_none() {
+            due_date = r.text(node, &["PaymentDueDate"]);
+        }
+        payment_means.push((node, means));
+    }
+    if let Some(payment_method) = r.payment_method(payment_means, details) {
+        invoice = invoice.with_payment_method(payment_method);
+    }
+
+    let payment_terms = r.child(root, "PaymentTerms");
+    let comment = payment_terms.and_then(|t| r.text(t, &["Note"]));
+    match (due_date, payment_terms) {
+        (Some(due_date), _) => {
+            let mut payment_conditions = PaymentConditions::new(due_date);
+            if let Some(comment) = comment {
+                payment_conditions = payment_conditions.with_comment(comment);
+            }
+            invoice = invoice.with_payment_conditions(payment_conditions);
+        }
+        (None, Some(payment_terms)) => r.loss(payment_terms, "payment terms without due date, dropped"),
+        (None, None) => {}
+    }
+
+    for node in r.children(root, "AllowanceCharge") {
+        let allowance_charge = read_allowance_charge(r, node)?;
+        let tax_category = r.required_child(node, "TaxCategory")?;
+        let tax_item = read_tax_category(r, tax_category)?;
+        invoice = allowance_charge.add_to_invoice(invoice, tax_item);
+    }
+
+    let tax_total = root.child("TaxTotal");
+    r.skip(root, "TaxTotal");
+    r.stated_total(tax_total.and_then(|t| t.child("TaxAmount")), |t| t.tax_total_amount);
+
+    let total = r.required_child(root, "LegalMonetaryTotal")?;
+    if let Some(prepaid_amount) = r.decimal(total, &["PrepaidAmount"])? {
+        invoice = invoice.with_prepaid_amount(prepaid_amount);
+    }
+    if let Some(rounding_amount) = r.decimal(total, &["PayableRoundingAmount"])? {
+        invoice = invoice.with_rounding_amount(rounding_amount);
+    }
+    let stated_totals: [(&str, DerivedTotal); 6] = [
+        ("LineExtensionAmount", |t| t.line_total_amount),
+        ("TaxExclusiveAmount", Totals::tax_basis_total_amount),
+        ("TaxInclusiveAmount", |t| t.total_gross_amount),
+        ("AllowanceTotalAmount", |t| t.allowance_total_amount),
+        ("ChargeTotalAmount", |t| t.charge_total_amount),
+        ("PayableAmount", |t| t.payable_amount),
+    ];
+    for (name, derived) in stated_totals {
+        let node = r.child(total, name);
+        r.stated_total(node, derived);
+    }
+
+    for node in r.children(root, line) {
+        invoice = invoice.with_item(read_line(r, node, quantity)?);
+    }
+
+    Ok(invoice)
+}
+
+/// Reads a `Party`, the first VAT registration (`PartyTaxScheme`) is the VAT identification number.
+fn read_party<'n, 'a>(r: &mut Reader<'n, 'a>, party: &'n XmlNode<'a>) -> Party<'a> {
+    let mut vat_identification_number = None;
+    for party_tax_scheme in r.children(party, "PartyTaxScheme") {
+        let company_id = party_tax_scheme.child("CompanyID");
+        let tax_scheme = party_tax_scheme.child("TaxScheme").and_then(|t| t.child_text("ID"));
+        match company_id {
+            Some(company_id) if tax_scheme.as_deref() == Some("VAT") && vat_identification_number.is_none() => {
+                r.skip(party_tax_scheme, "CompanyID");
+                r.skip(party_tax_scheme, "TaxScheme");
+                vat_identification_number = Some(company_id.text());
+            }
+            _ => r.dropped(party_tax_scheme, "tax registration besides the VAT identification number, dropped"),
+        }
+    }
+
+    let party_name = r.descendant(party, &["PartyName", "Name"]);
+    let registration_name = r.descendant(party, &["PartyLegalEntity", "RegistrationName"]);
+    let name = party_name.or(registration_name).map(XmlNode::text);
+
+    let mut address = match r.child(party, "PostalAddress") {
+        Some(postal_address) => Some(read_address(r, postal_address, name.clone().unwrap_or_default())),
+        None => {
+            if let Some(party_name) = party_name {
+                r.loss(party_name, "name without postal address, dropped");
+            }
+            None
+        }
+    };
+    if let (Some(registration_name), Some(name)) = (registration_name, &name)
+        && registration_name.text() != *name
+        && address.is_some()
+    {
+        r.loss(registration_name, "registration name besides the party name, dropped");
+    }
+
+    let contact = match r.child(party, "Contact") {
+        Some(contact) => {
+            let name = r.text(contact, &["Name"]);
+            let phone = r.text(contact, &["Telephone"]);
+            let email = r.text(contact, &["ElectronicMail"]);
+            r.contact(contact, name, phone, email, &mut address)
+        }
+        None => None,
+    };
+
+    Party {
+        vat_identification_number,
+        address,
+        contact,
+    }
+}
+
+/// Reads a `PostalAddress` or the `Address` of a delivery location. The country code is also the country name.
+fn read_address<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>, name: Cow<'a, str>) -> Address<'a> {
+    let country_code = r.text(node, &["Country", "IdentificationCode"]);
+    let mut address = Address::new(
+        name,
+        r.text(node, &["CityName"]).unwrap_or_default(),
+        r.text(node, &["PostalZone"]).unwrap_or_default(),
+        country_code.clone().unwrap_or_default(),
+    );
+    if let Some(street) = r.text(node, &["StreetName"]) {
+        address = address.with_street(street);
+    }
+    if let Some(country_code) = country_code {
+        address = address.with_country_code(country_code);
+    }
+    address
+}
+
+/// Reads the `OrderReference`, or else the `BuyerReference` as order ID.
+fn read_order_reference<'n, 'a>(r: &mut Reader<'n, 'a>, root: &'n XmlNode<'a>) -> Option<OrderReference<'a>> {
+    let buyer_reference = r.child(root, "BuyerReference");
+    let Some(node) = r.child(root, "OrderReference") else {
+        return buyer_reference.map(|b| OrderReference::new(b.text()));
+    };
+
+    let order_id = r.text(node, &["ID"]).unwrap_or_default();
+    if let Some(buyer_reference) = buyer_reference
+        && buyer_reference.text() != order_id
+    {
+        r.loss(buyer_reference, "buyer reference besides the order reference, dropped");
+    }
+
+    let mut order_reference = OrderReference::new(order_id);
+    if let Some(date) = r.text(node, &["IssueDate"]) {
+        order_reference = order_reference.with_reference_date(date);
+    }
+    Some(order_reference)
+}
+
+/// Reads the `InvoicePeriod` and the `Delivery` of the invoice or a line. ebInterface has either a delivery date or
+/// a period, so a delivery date besides the period gets dropped.
+fn read_delivery<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Option<Delivery<'a>> {
+    let period = r.child(node, "InvoicePeriod").and_then(|period| {
+        match (r.text(period, &["StartDate"]), r.text(period, &["EndDate"])) {
+            (Some(start_date), Some(end_date)) => Some(Delivery::period(start_date, end_date)),
+            _ => {
+                r.dropped(period, "invoice period without start and end date, dropped");
+                None
+            }
+        }
+    });
+    let delivery_node = r.child(node, "Delivery");
+    let date = delivery_node.and_then(|d| r.child(d, "ActualDeliveryDate"));
+
+    let mut delivery = match (period, date) {
+        (Some(period), Some(date)) => {
+            r.loss(date, "delivery date besides the invoice period, dropped");
+            period
+        }
+        (Some(period), None) => period,
+        (None, Some(date)) => Delivery::date(date.text()),
+        (None, None) => {
+            if let Some(delivery_node) = delivery_node {
+                r.dropped(delivery_node, "delivery without date, dropped");
+            }
+            return None;
+        }
+    };
+
+    if let Some(delivery_node) = delivery_node {
+        if let Some(id) = r.text(delivery_node, &["ID"]) {
+            delivery = delivery.with_delivery_id(id);
+        }
+        if let Some(address) = r.descendant(delivery_node, &["DeliveryLocation", "Address"]) {
+            let name = r.text(delivery_node, &["DeliveryParty", "PartyName", "Name"]);
+            delivery = delivery.with_address(read_address(r, address, name.unwrap_or_default()));
+        }
+    }
+
+    Some(delivery)
+}
+
+/// Reads the SEPA creditor identifier, a `PartyIdentification` of the biller with `schemeID` `SEPA`.
+fn read_creditor_id<'n, 'a>(r: &mut Reader<'n, 'a>, party: &'n XmlNode<'a>) -> Option<&'n XmlNode<'a>> {
+    let party_identification = party
+        .children("PartyIdentification")
+        .find(|p| p.child("ID").and_then(|id| id.attr("schemeID")).as_deref() == Some("SEPA"))?;
+    r.skip(party, "PartyIdentification");
+    r.child(party_identification, "ID")
+}
+
+/// Reads a `PaymentMeans` by its UNTDID 4461 code: credit transfers (`30`, `31`, `42`, `58`), direct debits (`49`,
+/// `59`) and payment cards (`48`, `54`, `55`). Other codes are `OtherPayment`.
+fn read_payment_means<'n, 'a>(
+    r: &mut Reader<'n, 'a>,
+    node: &'n XmlNode<'a>,
+    details: &mut PaymentDetails<'n, 'a>,
+) -> Result<PaymentMeans<'a>, ParseError> {
+    let code_node = r.required_child(node, "PaymentMeansCode")?;
+    let code = code_node.text();
+
+    if let Some(payment_id) = r.child(node, "PaymentID") {
+        details.payment_reference.get_or_insert(payment_id);
+    }
+
+    Ok(match code.as_ref() {
+        "30" | "31" | "42" | "58" => {
+            let account = r.child(node, "PayeeFinancialAccount");
+            let id = account.and_then(|a| r.text(a, &["ID"]));
+            let (iban, bank_account_number) = if code == "58" { (id, None) } else { (None, id) };
+            PaymentMeans::CreditTransfer(PaymentMethodUniversalBankTransactionBeneficiaryAccount::imported(
+                iban,
+                bank_account_number,
+                account.and_then(|a| r.text(a, &["FinancialInstitutionBranch", "ID"])),
+                account.and_then(|a| r.text(a, &["Name"])),
+            ))
+        }
+        "49" | "59" => {
+            details.debit_collection_date = r.text(node, &["PaymentDueDate"]);
+            let mandate = r.child(node, "PaymentMandate");
+            if let Some(mandate_reference) = mandate.and_then(|m| r.child(m, "ID")) {
+                details.mandate_reference.get_or_insert(mandate_reference);
+            }
+            PaymentMeans::DirectDebit {
+                iban: mandate.and_then(|m| r.text(m, &["PayerFinancialAccount", "ID"])),
+            }
+        }
+        "48" | "54" | "55" => {
+            let card_account = r.required_child(node, "CardAccount")?;
+            r.skip(card_account, "NetworkID");
+            PaymentMeans::PaymentCard(PaymentMethodPaymentCard::imported(
+                r.required_text(card_account, "PrimaryAccountNumberID")?,
+                r.text(card_account, &["HolderName"]),
+            ))
+        }
+        "1" | "ZZZ" => PaymentMeans::Other,
+        _ => {
+            r.loss(code_node, format!("payment means code {code} has no counterpart, read as OtherPayment"));
+            PaymentMeans::Other
+        }
+    })
+}
+
+/// Reads an `AllowanceCharge`. Without `MultiplierFactorNumeric`, only the amount is given. Reason codes other than
+/// the ones written by `to_ubl` (`95` discount or `ZZZ` mutually defined charge) get dropped.
+fn read_allowance_charge<'n, 'a>(
+    r: &mut Reader<'n, 'a>,
+    node: &'n XmlNode<'a>,
+) -> Result<AllowanceCharge<'a>, ParseError> {
+    let charge = r.required_child(node, "ChargeIndicator")?.value()?;
+
+    if let Some(reason_code) = r.child(node, "AllowanceChargeReasonCode")
+        && reason_code.text() != if charge { "ZZZ" } else { "95" }
+    {
+        r.loss(reason_code, format!("reason code {} has no counterpart, dropped", reason_code.text()));
+    }
+
+    let amount = r
+        .decimal(node, &["Amount"])?
+        .ok_or_else(|| node.error(ParseErrorKind::MissingElement("Amount".to_string())))?;
+    let value = match r.decimal(node, &["MultiplierFactorNumeric"])? {
+        Some(percentage) => ReductionAndSurchargeValue::PercentageAndAmount(percentage, amount),
+        None => ReductionAndSurchargeValue::Amount(amount),
+    };
+
+    Ok(AllowanceCharge {
+        charge,
+        base_amount: r.decimal(node, &["BaseAmount"])?.unwrap_or(amount),
+        value,
+        comment: r.text(node, &["AllowanceChargeReason"]),
+    })
+}
+
+/// Reads a `TaxCategory` or `ClassifiedTaxCategory`.
+fn read_tax_category<'n, 'a>(r: &mut Reader<'n, 'a>, node: &'n XmlNode<'a>) -> Result<TaxItem, ParseError> {
+    let code = r.text(node, &["ID"]);
+    let percent = r.decimal(node, &["Percent"])?;
+    r.skip(node, "TaxScheme");
+    Ok(r.tax_item(node, code.as_deref(), percent))
+}
+
+/// Reads an `InvoiceLine` or `CreditNoteLine` with the given quantity element. The name of the item becomes the first description.
+fn read_line<'n, 'a>(
+    r: &mut Reader<'n, 'a>,
+    node: &'n XmlNode<'a>,
+    quantity: &str,
+) -> Result<DetailsItem<'a>, ParseError> {
+    let quantity = r.required_child(node, quantity)?;
+    let item = r.required_child(node, "Item")?;
+    let tax_category = r.required_child(item, "ClassifiedTaxCategory")?;
+    let price = r.required_child(node, "Price")?;
+    let price_amount = r.required_child(price, "PriceAmount")?;
+
+    let mut line = DetailsItem::new(
+        quantity.decimal()?,
+        quantity.required_attr("unitCode")?,
+        price_amount.decimal()?,
+        read_tax_category(r, tax_category)?,
+    );
+
+    let id = r.required_child(node, "ID")?;
+    match id.value() {
+        Ok(position_number) => line = line.with_position_number(position_number),
+        Err(_) => r.loss(id, format!("line ID {} isn't a position number, dropped", id.text())),
+    }
+
+    r.skip(node, "LineExtensionAmount");
+
+    if let Some(name) = r.text(item, &["Name"]) {
+        line = line.with_description(name);
+    }
+    for description in r.children(item, "Description") {
+        line = line.with_description(description.text());
+    }
+
+    if let Some(base_quantity) = r.descendant(price, &["BaseQuantity"]) {
+        line = line.with_base_quantity(base_quantity.decimal()?).map_err(|_| {
+            base_quantity.error(ParseErrorKind::InvalidValue {
+                name: base_quantity.name.to_string(),
+                value: base_quantity.text().into_owned(),
+            })
+        })?;
+    }
+
+    if let Some(delivery) = read_delivery(r, node) {
+        line = line.with_delivery(delivery);
+    }
+
+    for allowance_charge in r.children(node, "AllowanceCharge") {
+        line = read_allowance_charge(r, allowance_charge)?.add_to_item(line);
+    }
+
+    Ok(line)
+}
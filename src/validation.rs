@@ -0,0 +1,210 @@
+use regex::Regex;
+use rust_decimal::Decimal;
+use std::{fmt, sync::LazyLock};
+
+use crate::{
+    codes::{COUNTRY_CODES, CURRENCY_CODES},
+    error::{Error, ValidationError},
+};
+
+const DATE_REGEX_STR: &str = r"^[0-9]{4}-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])$";
+
+/// Collects the errors of a whole document, together with the path of the element, which is currently validated.
+pub(crate) struct Validator {
+    path: String,
+    pub(crate) errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    pub(crate) fn new() -> Self {
+        Validator {
+            path: String::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn path(&self, field: &str) -> String {
+        if self.path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}/{field}", self.path)
+        }
+    }
+
+    /// Validates a child element, e.g. `Biller` or `ListLineItem[3]`.
+    pub(crate) fn child(&mut self, element: impl fmt::Display, f: impl FnOnce(&mut Validator)) {
+        let len = self.path.len();
+        if !self.path.is_empty() {
+            self.path.push('/');
+        }
+        self.path.push_str(&element.to_string());
+        f(self);
+        self.path.truncate(len);
+    }
+
+    /// Validates each of the given child elements, numbering them like `ListLineItem[1]`, `ListLineItem[2]`, ….
+    pub(crate) fn children<T: Validate>(&mut self, element: &str, children: &[T]) {
+        for (i, child) in children.iter().enumerate() {
+            self.child(format_args!("{element}[{}]", i + 1), |v| child.validate(v));
+        }
+    }
+
+    pub(crate) fn error(&mut self, field: &str, error: Error) {
+        let path = self.path(field);
+        self.errors.push(ValidationError { path, error });
+    }
+
+    pub(crate) fn required(&mut self, field: &'static str, value: &str) {
+        if value.trim().is_empty() {
+            self.error(field, Error::Missing { field: name(field) });
+        }
+    }
+
+    pub(crate) fn max_length(&mut self, field: &'static str, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.error(
+                field,
+                Error::TooLong {
+                    field: name(field),
+                    max,
+                    value: value.to_string(),
+                },
+            );
+        }
+    }
+
+    pub(crate) fn pattern(&mut self, field: &'static str, value: &str, pattern: &'static str, regex: &Regex) {
+        if !regex.is_match(value) {
+            self.error(
+                field,
+                Error::RegexMismatch {
+                    field: name(field),
+                    pattern,
+                    value: value.to_string(),
+                },
+            );
+        }
+    }
+
+    fn unknown_value(&mut self, field: &'static str, value: &str) {
+        self.error(
+            field,
+            Error::UnknownValue {
+                field: name(field),
+                value: value.to_string(),
+            },
+        );
+    }
+
+    /// Checks an `xs:date` (`YYYY-MM-DD`).
+    pub(crate) fn date(&mut self, field: &'static str, value: &str) {
+        static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(DATE_REGEX_STR).unwrap());
+        self.pattern(field, value, DATE_REGEX_STR, &DATE_REGEX);
+    }
+
+    pub(crate) fn currency_code(&mut self, field: &'static str, value: &str) {
+        if CURRENCY_CODES.binary_search(&value).is_err() {
+            self.unknown_value(field, value);
+        }
+    }
+
+    pub(crate) fn country_code(&mut self, field: &'static str, value: &str) {
+        if COUNTRY_CODES.binary_search(&value).is_err() {
+            self.unknown_value(field, value);
+        }
+    }
+
+    /// Checks a value, which has to be greater than zero, e.g. because it's used as a divisor.
+    pub(crate) fn positive(&mut self, field: &'static str, value: Decimal) {
+        if value <= Decimal::ZERO {
+            self.error(
+                field,
+                Error::NotPositive {
+                    field: name(field),
+                    value,
+                },
+            );
+        }
+    }
+
+    /// Checks a percentage, which has to be between 0 and 100.
+    pub(crate) fn percentage(&mut self, field: &'static str, value: Decimal) {
+        if value < Decimal::ZERO || value > Decimal::ONE_HUNDRED {
+            self.error(
+                field,
+                Error::OutOfRange {
+                    field: name(field),
+                    min: Decimal::ZERO,
+                    max: Decimal::ONE_HUNDRED,
+                    value,
+                },
+            );
+        }
+    }
+}
+
+/// Returns the name of the element or attribute at the end of the given relative path,
+/// e.g. `Unit` for `Quantity/@Unit`.
+fn name(field: &'static str) -> &'static str {
+    let name = field.rsplit('/').next().unwrap_or(field);
+    name.strip_prefix('@').unwrap_or(name)
+}
+
+pub(crate) trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_paths() {
+        let mut v = Validator::new();
+        v.required("InvoiceNumber", "");
+        v.child("Details/ItemList", |v| {
+            v.child("ListLineItem[3]", |v| v.required("Quantity/@Unit", " "));
+        });
+        v.child("Biller", |v| v.date("InvoiceDate", "2020-13-01"));
+
+        assert_eq!(
+            v.errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "InvoiceNumber: InvoiceNumber is required!",
+                "Details/ItemList/ListLineItem[3]/Quantity/@Unit: Unit is required!",
+                "Biller/InvoiceDate: InvoiceDate 2020-13-01 doesn't match regex ^[0-9]{4}-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])$!",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_codes_and_percentages() {
+        let mut v = Validator::new();
+        v.currency_code("@InvoiceCurrency", "EUR");
+        v.currency_code("@InvoiceCurrency", "EURO");
+        v.country_code("@CountryCode", "AT");
+        v.country_code("@CountryCode", "at");
+        v.percentage("Percentage", Decimal::ONE_HUNDRED);
+        v.percentage("Percentage", Decimal::from(101));
+
+        assert_eq!(
+            v.errors.into_iter().map(|e| e.error).collect::<Vec<_>>(),
+            vec![
+                Error::UnknownValue {
+                    field: "InvoiceCurrency",
+                    value: "EURO".to_string()
+                },
+                Error::UnknownValue {
+                    field: "CountryCode",
+                    value: "at".to_string()
+                },
+                Error::OutOfRange {
+                    field: "Percentage",
+                    min: Decimal::ZERO,
+                    max: Decimal::ONE_HUNDRED,
+                    value: Decimal::from(101)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use std::{borrow::Cow, fmt, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{codes::is_country_code, error::Error, iban::mod_97};
+
+const NO_VAT_ID: &str = "00000000";
+
+const OTHER_REGEX_STR: &str = r"^[A-Z]{2}[0-9A-Z]{2,15}$";
+static OTHER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(OTHER_REGEX_STR).unwrap());
+
+/// Checks the number after the prefix, e.g. `U13585627` for `ATU13585627`.
+type Checksum = fn(&str) -> bool;
+
+/// The formats and check digit algorithms of the VAT IDs of the EU member states and of Northern Ireland (`XI`),
+/// sorted by prefix.
+const EU_FORMATS: &[(&str, &str, Option<Checksum>)] = &[
+    ("AT", r"^ATU[0-9]{8}$", Some(at)),
+    ("BE", r"^BE[01][0-9]{9}$", Some(be)),
+    ("BG", r"^BG[0-9]{9,10}$", None),
+    ("CY", r"^CY[0-9]{8}[A-Z]$", None),
+    ("CZ", r"^CZ[0-9]{8,10}$", None),
+    ("DE", r"^DE[1-9][0-9]{8}$", Some(mod_11_10)),
+    ("DK", r"^DK[1-9][0-9]{7}$", Some(dk)),
+    ("EE", r"^EE10[0-9]{7}$", Some(ee)),
+    ("EL", r"^EL[0-9]{9}$", Some(el)),
+    ("ES", r"^ES[0-9A-Z][0-9]{7}[0-9A-Z]$", None),
+    ("FI", r"^FI[0-9]{8}$", Some(fi)),
+    ("FR", r"^FR[0-9A-HJ-NP-Z]{2}[0-9]{9}$", Some(fr)),
+    ("HR", r"^HR[0-9]{11}$", Some(mod_11_10)),
+    ("HU", r"^HU[0-9]{8}$", Some(hu)),
+    ("IE", r"^IE([0-9]{7}[A-W][A-I]?|[0-9][A-Z+*][0-9]{5}[A-W])$", None),
+    ("IT", r"^IT[0-9]{11}$", Some(luhn)),
+    ("LT", r"^LT([0-9]{9}|[0-9]{12})$", None),
+    ("LU", r"^LU[0-9]{8}$", Some(lu)),
+    ("LV", r"^LV[0-9]{11}$", None),
+    ("MT", r"^MT[1-9][0-9]{7}$", None),
+    ("NL", r"^NL[0-9]{9}B[0-9]{2}$", Some(nl)),
+    ("PL", r"^PL[0-9]{10}$", Some(pl)),
+    ("PT", r"^PT[0-9]{9}$", Some(pt)),
+    ("RO", r"^RO[1-9][0-9]{1,9}$", None),
+    ("SE", r"^SE[0-9]{10}01$", Some(se)),
+    ("SI", r"^SI[1-9][0-9]{7}$", Some(si)),
+    ("SK", r"^SK[1-9][0-9]{9}$", Some(sk)),
+    ("XI", r"^XI([0-9]{9}|[0-9]{12}|GD[0-4][0-9]{2}|HA[5-9][0-9]{2})$", None),
+];
+static EU_REGEXES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    EU_FORMATS
+        .iter()
+        .map(|(_, pattern, _)| Regex::new(pattern).unwrap())
+        .collect()
+});
+
+/// A VAT identification number, e.g. `ATU13585627`, or the value `00000000` for recipients without one.
+///
+/// The VAT IDs of the EU member states (with the prefix `EL` for Greece) and of Northern Ireland (`XI`) have to
+/// match the format of the country and, where there is one, the check digit algorithm, like the Austrian `ATU`
+/// numbers. Other VAT IDs only have to start with an ISO 3166 country code.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct VatId<'a>(Cow<'a, str>);
+
+impl<'a> VatId<'a> {
+    /// Validates the VAT ID, after removing spaces, dots and dashes and converting it to uppercase.
+    pub fn new(vat_id: impl Into<Cow<'a, str>>) -> Result<Self, Error> {
+        let vat_id = vat_id.into();
+        let vat_id = if vat_id.chars().any(|c| is_separator(c) || c.is_ascii_lowercase()) {
+            Cow::Owned(
+                vat_id
+                    .chars()
+                    .filter(|c| !is_separator(*c))
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect(),
+            )
+        } else {
+            vat_id
+        };
+        let vat_id = VatId(vat_id);
+        vat_id.check()?;
+        Ok(vat_id)
+    }
+
+    /// The value `00000000`, which ebInterface allows for recipients without a VAT ID.
+    pub fn none() -> Self {
+        VatId(Cow::Borrowed(NO_VAT_ID))
+    }
+
+    /// Wraps a VAT ID as read from a document, without validating or normalising it like `new` does.
+    pub(crate) fn unchecked(vat_id: Cow<'a, str>) -> Self {
+        VatId(vat_id)
+    }
+
+    /// Checks the format and the check digits of the VAT ID.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.0.trim().is_empty() {
+            return Err(Error::Missing {
+                field: "VATIdentificationNumber",
+            });
+        }
+        if self.is_none() {
+            return Ok(());
+        }
+
+        let prefix = self.0.get(0..2).unwrap_or_default();
+        let Ok(i) = EU_FORMATS.binary_search_by_key(&prefix, |(prefix, _, _)| prefix) else {
+            if !OTHER_REGEX.is_match(&self.0) {
+                return Err(self.regex_mismatch(OTHER_REGEX_STR));
+            }
+            if !is_country_code(prefix) {
+                return Err(Error::UnknownValue {
+                    field: "VATIdentificationNumber",
+                    value: self.0.to_string(),
+                });
+            }
+            return Ok(());
+        };
+
+        let (_, pattern, checksum) = EU_FORMATS[i];
+        if !EU_REGEXES[i].is_match(&self.0) {
+            return Err(self.regex_mismatch(pattern));
+        }
+        if let Some(checksum) = checksum
+            && !checksum(&self.0[2..])
+        {
+            return Err(Error::InvalidChecksum {
+                field: "VATIdentificationNumber",
+                value: self.0.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn regex_mismatch(&self, pattern: &'static str) -> Error {
+        Error::RegexMismatch {
+            field: "VATIdentificationNumber",
+            pattern,
+            value: self.0.to_string(),
+        }
+    }
+
+    /// Returns `true` for the value `00000000` (no VAT ID).
+    pub fn is_none(&self) -> bool {
+        self.0 == NO_VAT_ID
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the prefix of the VAT ID, e.g. `AT`, or `EL` for Greece, and `None` for no VAT ID.
+    pub fn country_code(&self) -> Option<&str> {
+        if self.is_none() { None } else { self.0.get(0..2) }
+    }
+
+    pub fn into_owned(self) -> VatId<'static> {
+        VatId(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for VatId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '.' || c == '-'
+}
+
+fn digits(number: &str) -> Vec<u32> {
+    number.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Sums the digits multiplied by the weights, ignoring the digits without weight.
+fn weighted_sum(digits: &[u32], weights: &[u32]) -> u32 {
+    digits.iter().zip(weights).map(|(d, w)| d * w).sum()
+}
+
+fn at(number: &str) -> bool {
+    let d = digits(number);
+    let sum: u32 = d[..7]
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 0 { d } else { 2 * d / 10 + 2 * d % 10 })
+        .sum();
+    (10 - (sum + 4) % 10) % 10 == d[7]
+}
+
+fn be(number: &str) -> bool {
+    let (base, check) = number.split_at(8);
+    base.parse::<u32>().ok().map(|base| 97 - base % 97) == check.parse().ok()
+}
+
+fn dk(number: &str) -> bool {
+    weighted_sum(&digits(number), &[2, 7, 6, 5, 4, 3, 2, 1]).is_multiple_of(11)
+}
+
+fn ee(number: &str) -> bool {
+    let d = digits(number);
+    (10 - weighted_sum(&d, &[3, 7, 1, 3, 7, 1, 3, 7]) % 10) % 10 == d[8]
+}
+
+fn el(number: &str) -> bool {
+    let d = digits(number);
+    weighted_sum(&d, &[256, 128, 64, 32, 16, 8, 4, 2]) % 11 % 10 == d[8]
+}
+
+fn fi(number: &str) -> bool {
+    let d = digits(number);
+    match weighted_sum(&d, &[7, 9, 10, 5, 8, 4, 2]) % 11 {
+        0 => d[7] == 0,
+        1 => false,
+        r => d[7] == 11 - r,
+    }
+}
+
+/// Checks the numeric key of a French VAT ID against the SIREN, keys with letters have no public algorithm.
+fn fr(number: &str) -> bool {
+    let (key, siren) = number.split_at(2);
+    match (key.parse::<u64>(), siren.parse::<u64>()) {
+        (Ok(key), Ok(siren)) => key == (12 + 3 * (siren % 97)) % 97,
+        _ => true,
+    }
+}
+
+fn hu(number: &str) -> bool {
+    let d = digits(number);
+    (10 - weighted_sum(&d, &[9, 7, 3, 1, 9, 7, 3]) % 10) % 10 == d[7]
+}
+
+fn lu(number: &str) -> bool {
+    let (base, check) = number.split_at(6);
+    base.parse::<u32>().ok().map(|base| base % 89) == check.parse().ok()
+}
+
+/// Checks the 11 check of the old numbers and the MOD 97-10 check of the numbers of sole proprietors.
+fn nl(number: &str) -> bool {
+    let d = digits(&number[..9]);
+    weighted_sum(&d, &[9, 8, 7, 6, 5, 4, 3, 2]) % 11 == d[8] || mod_97("NL", number) == 1
+}
+
+fn pl(number: &str) -> bool {
+    let d = digits(number);
+    weighted_sum(&d, &[6, 5, 7, 2, 3, 4, 5, 6, 7]) % 11 == d[9]
+}
+
+fn pt(number: &str) -> bool {
+    let d = digits(number);
+    let check = 11 - weighted_sum(&d, &[9, 8, 7, 6, 5, 4, 3, 2]) % 11;
+    (if check > 9 { 0 } else { check }) == d[8]
+}
+
+fn se(number: &str) -> bool {
+    luhn(&number[..10])
+}
+
+fn si(number: &str) -> bool {
+    let d = digits(number);
+    match 11 - weighted_sum(&d, &[8, 7, 6, 5, 4, 3, 2]) % 11 {
+        11 => false,
+        10 => d[7] == 0,
+        check => d[7] == check,
+    }
+}
+
+fn sk(number: &str) -> bool {
+    number.parse::<u64>().is_ok_and(|number| number.is_multiple_of(11))
+}
+
+/// ISO 7064 MOD 11,10, used by DE and HR.
+fn mod_11_10(number: &str) -> bool {
+    let d = digits(number);
+    let Some((check, d)) = d.split_last() else {
+        return false;
+    };
+    let product = d.iter().fold(10, |product, d| {
+        let sum = (d + product) % 10;
+        2 * (if sum == 0 { 10 } else { sum }) % 11
+    });
+    (11 - product) % 10 == *check
+}
+
+fn luhn(number: &str) -> bool {
+    digits(number)
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 1 { 2 * d / 10 + 2 * d % 10 } else { d })
+        .sum::<u32>()
+        % 10
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_vat_ids() {
+        for vat_id in [
+            "ATU13585627",
+            "ATU51507409",
+            "BE0411905847",
+            "DE136695976",
+            "DK13585628",
+            "EE100207415",
+            "EL094259216",
+            "FI20774740",
+            "FR40303265045",
+            "HR33392005961",
+            "HU12892312",
+            "IT00743110157",
+            "LU15027442",
+            "NL004495445B01",
+            "NL123456782B01",
+            "PL5260250274",
+            "PT501964843",
+            "SE556188840401",
+            "SI50223054",
+            "SK2022749619",
+            "ESX1234567A",
+            "CHE123456789",
+            "XK330123456",
+            "00000000",
+        ] {
+            assert_eq!(VatId::new(vat_id).map(|v| v.to_string()), Ok(vat_id.to_string()));
+        }
+    }
+
+    #[test]
+    fn normalises_vat_ids() {
+        let vat_id = VatId::new("atu 1358-5627").unwrap();
+        assert_eq!(vat_id.as_str(), "ATU13585627");
+        assert_eq!(vat_id.country_code(), Some("AT"));
+        assert_eq!(VatId::new("CHE-123.456.789").unwrap().as_str(), "CHE123456789");
+        assert!(matches!(VatId::new("ATU13585627").unwrap(), VatId(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn models_no_vat_id() {
+        assert!(VatId::none().is_none());
+        assert_eq!(VatId::new("00000000"), Ok(VatId::none()));
+        assert_eq!(VatId::none().country_code(), None);
+        assert!(!VatId::new("ATU13585627").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_vat_ids() {
+        for vat_id in [
+            "ATU13585628",
+            "BE0411905848",
+            "DE136695977",
+            "DK13585629",
+            "EL094259217",
+            "FI20774741",
+            "FR41303265045",
+            "HR33392005962",
+            "IT00743110158",
+            "LU15027443",
+            "NL123456783B01",
+            "PL5260250275",
+            "PT501964844",
+            "SE556188840301",
+            "SI50223055",
+            "SK2022749618",
+        ] {
+            assert_eq!(
+                VatId::new(vat_id),
+                Err(Error::InvalidChecksum {
+                    field: "VATIdentificationNumber",
+                    value: vat_id.to_string(),
+                }),
+                "{vat_id}"
+            );
+        }
+
+        assert_eq!(
+            VatId::new("ATU1358562"),
+            Err(Error::RegexMismatch {
+                field: "VATIdentificationNumber",
+                pattern: r"^ATU[0-9]{8}$",
+                value: "ATU1358562".to_string(),
+            })
+        );
+        assert_eq!(
+            VatId::new("XX123456789"),
+            Err(Error::UnknownValue {
+                field: "VATIdentificationNumber",
+                value: "XX123456789".to_string(),
+            })
+        );
+        assert!(matches!(VatId::new("1234"), Err(Error::RegexMismatch { .. })));
+        assert_eq!(
+            VatId::new(" "),
+            Err(Error::Missing {
+                field: "VATIdentificationNumber"
+            })
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::tax::TaxCategory;
+
+/// The ebInterface version of a written document, see `Invoice::to_xml_version`.
+///
+/// ebInterface 4.3 and 5.0 qualify elements and attributes with the namespace prefix `eb`. They state the tax of a
+/// line item as a plain rate (`VATRate` in 4.3, `TaxRate` in 5.0) and summarize it in `Tax/VAT/VATItem`, so they
+/// only support the tax categories with a rate (`S`, `AA` and `Z`).
+/// `Contact` and `PrepaidAmount` / `RoundingAmount` were introduced with 6.0, `BelowTheLineItem` as
+/// well as the payment methods `PaymentCard` and `OtherPayment` with 5.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EbInterfaceVersion {
+    V4p3,
+    V5p0,
+    V6p0,
+    #[default]
+    V6p1,
+}
+
+impl fmt::Display for EbInterfaceVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EbInterfaceVersion::V4p3 => "4.3",
+                EbInterfaceVersion::V5p0 => "5.0",
+                EbInterfaceVersion::V6p0 => "6.0",
+                EbInterfaceVersion::V6p1 => "6.1",
+            }
+        )
+    }
+}
+
+impl EbInterfaceVersion {
+    pub fn namespace(&self) -> &'static str {
+        match self {
+            EbInterfaceVersion::V4p3 => "http://www.ebinterface.at/schema/4p3/",
+            EbInterfaceVersion::V5p0 => "http://www.ebinterface.at/schema/5p0/",
+            EbInterfaceVersion::V6p0 => "http://www.ebinterface.at/schema/6p0/",
+            EbInterfaceVersion::V6p1 => "http://www.ebinterface.at/schema/6p1/",
+        }
+    }
+
+    /// Whether elements and attributes are qualified with the prefix `eb` (before 6.0).
+    pub(crate) fn has_qualified_names(&self) -> bool {
+        *self < EbInterfaceVersion::V6p0
+    }
+
+    /// Whether taxes are written as `TaxItem` (6.0 and later) instead of a rate and `VATItem`.
+    pub(crate) fn has_tax_item(&self) -> bool {
+        *self >= EbInterfaceVersion::V6p0
+    }
+
+    /// The element of the tax rate of line items, reductions, surcharges and `VATItem`s before 6.0.
+    pub(crate) fn tax_rate_element(&self) -> &'static str {
+        match self {
+            EbInterfaceVersion::V4p3 => "VATRate",
+            _ => "TaxRate",
+        }
+    }
+
+    pub(crate) fn supports_tax_category(&self, tax_category: TaxCategory) -> bool {
+        self.has_tax_item() || matches!(tax_category, TaxCategory::S | TaxCategory::AA | TaxCategory::Z)
+    }
+
+    pub(crate) fn has_contact(&self) -> bool {
+        *self >= EbInterfaceVersion::V6p0
+    }
+
+    pub(crate) fn has_prepaid_and_rounding_amount(&self) -> bool {
+        *self >= EbInterfaceVersion::V6p0
+    }
+
+    pub(crate) fn has_below_the_line_items(&self) -> bool {
+        *self >= EbInterfaceVersion::V5p0
+    }
+
+    pub(crate) fn has_payment_card_and_other_payment(&self) -> bool {
+        *self >= EbInterfaceVersion::V5p0
+    }
+}
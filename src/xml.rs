@@ -1,106 +1,172 @@
-use std::borrow::Cow;
-
-fn xml_escape(s: &mut Cow<str>) {
-    if s.contains(['&', '"', '\'', '<', '>']) {
-        let mut o = String::with_capacity(s.len());
-        for c in s.chars() {
-            match c {
-                '&' => o.push_str("&amp;"),
-                '"' => o.push_str("&quot;"),
-                '\'' => o.push_str("&apos;"),
-                '<' => o.push_str("&lt;"),
-                '>' => o.push_str("&gt;"),
-                _ => o.push(c),
-            }
+use std::{fmt, io};
+
+use rust_decimal::Decimal;
+
+use crate::{rounding::RoundingPolicy, version::EbInterfaceVersion};
+
+/// Escapes everything written through it, before passing it on to the inner writer.
+struct XmlEscape<'a, 'w>(&'a mut (dyn fmt::Write + 'w));
+
+impl fmt::Write for XmlEscape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(i) = rest.find(['&', '"', '\'', '<', '>']) {
+            self.0.write_str(&rest[..i])?;
+            self.0.write_str(match rest.as_bytes()[i] {
+                b'&' => "&amp;",
+                b'"' => "&quot;",
+                b'\'' => "&apos;",
+                b'<' => "&lt;",
+                _ => "&gt;",
+            })?;
+            rest = &rest[i + 1..];
         }
-        *s.to_mut() = o;
+        self.0.write_str(rest)
     }
 }
 
-pub(crate) trait ToXml {
-    fn to_xml(&self) -> String;
+/// Adapts an `io::Write` to a `fmt::Write`, keeping the underlying `io::Error`.
+pub(crate) struct IoWrite<W: io::Write> {
+    inner: W,
+    pub(crate) error: Option<io::Error>,
 }
 
-struct XmlText<'a> {
-    text: Cow<'a, str>,
+impl<W: io::Write> IoWrite<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        IoWrite { inner, error: None }
+    }
 }
 
-impl XmlText<'_> {
-    fn to_xml(&mut self) -> String {
-        xml_escape(&mut self.text);
-        self.text.to_string()
+impl<W: io::Write> fmt::Write for IoWrite<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
     }
 }
 
-struct XmlAttribute<'a> {
-    name: Cow<'a, str>,
-    value: Cow<'a, str>,
+/// Streams XML directly into the underlying writer, without building intermediate strings.
+///
+/// Attributes have to be written right after `start`, the start tag gets closed by the first
+/// text, child element or `end`. Names get the prefix `eb`, if the version qualifies them.
+pub(crate) struct XmlWriter<'w> {
+    out: &'w mut dyn fmt::Write,
+    start_tag_open: bool,
+    version: EbInterfaceVersion,
+    rounding: RoundingPolicy,
 }
 
-impl XmlAttribute<'_> {
-    fn to_xml(&mut self) -> String {
-        xml_escape(&mut self.name);
-        xml_escape(&mut self.value);
-        format!("{}=\"{}\"", &self.name, &self.value)
+impl<'w> XmlWriter<'w> {
+    pub(crate) fn new(out: &'w mut dyn fmt::Write) -> Self {
+        XmlWriter {
+            out,
+            start_tag_open: false,
+            version: EbInterfaceVersion::default(),
+            rounding: RoundingPolicy::default(),
+        }
     }
-}
 
-#[derive(Default)]
-pub(crate) struct XmlElement {
-    name: String,
-    attrs: Option<Vec<String>>,
-    body: Vec<String>,
-}
+    /// Writes the elements in the structure of the given ebInterface version, instead of 6.1.
+    pub(crate) fn with_version(mut self, version: EbInterfaceVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub(crate) fn version(&self) -> EbInterfaceVersion {
+        self.version
+    }
 
-impl XmlElement {
-    pub(crate) fn new<'a>(name: impl Into<Cow<'a, str>>) -> Self {
-        let mut name = name.into();
-        xml_escape(&mut name);
+    /// Calculates and rounds the amounts with the given policy.
+    pub(crate) fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub(crate) fn rounding(&self) -> RoundingPolicy {
+        self.rounding
+    }
 
-        XmlElement {
-            name: name.to_string(),
-            ..Default::default()
+    /// Rounds the amount to cents with the rounding policy of the document.
+    pub(crate) fn round(&self, amount: Decimal) -> Decimal {
+        self.rounding.round(amount)
+    }
+
+    fn close_start_tag(&mut self) -> fmt::Result {
+        if self.start_tag_open {
+            self.start_tag_open = false;
+            self.out.write_char('>')?;
         }
+        Ok(())
     }
 
-    pub(crate) fn with_attr<'a>(mut self, name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
-        self.attrs.get_or_insert_with(Vec::new).push(
-            XmlAttribute {
-                name: name.into(),
-                value: value.into(),
-            }
-            .to_xml(),
-        );
-        self
+    fn prefix(&mut self) -> fmt::Result {
+        if self.version.has_qualified_names() {
+            self.out.write_str("eb:")?;
+        }
+        Ok(())
     }
 
-    pub(crate) fn with_element(mut self, element: &impl ToXml) -> Self {
-        self.body.push(element.to_xml());
-        self
+    /// Writes the given string unescaped, e.g. the XML declaration.
+    pub(crate) fn raw(&mut self, raw: &str) -> fmt::Result {
+        self.close_start_tag()?;
+        self.out.write_str(raw)
     }
 
-    pub(crate) fn with_text_element<'a>(
-        mut self,
-        name: impl Into<Cow<'a, str>>,
-        text: impl Into<Cow<'a, str>>,
-    ) -> Self {
-        self.body.push(XmlElement::new(name).with_text(text).to_xml());
-        self
+    pub(crate) fn start(&mut self, name: &str) -> fmt::Result {
+        self.close_start_tag()?;
+        self.out.write_char('<')?;
+        self.prefix()?;
+        fmt::Write::write_str(&mut XmlEscape(self.out), name)?;
+        self.start_tag_open = true;
+        Ok(())
     }
 
-    pub(crate) fn with_text<'a>(mut self, text: impl Into<Cow<'a, str>>) -> Self {
-        self.body.push(XmlText { text: text.into() }.to_xml());
-        self
+    pub(crate) fn attr(&mut self, name: &str, value: impl fmt::Display) -> fmt::Result {
+        debug_assert!(self.start_tag_open, "attribute {name} written outside of a start tag");
+        self.out.write_char(' ')?;
+        if !name.starts_with("xmlns") {
+            self.prefix()?;
+        }
+        fmt::Write::write_str(&mut XmlEscape(self.out), name)?;
+        self.out.write_str("=\"")?;
+        fmt::Write::write_fmt(&mut XmlEscape(self.out), format_args!("{value}"))?;
+        self.out.write_char('"')
     }
-}
 
-impl ToXml for XmlElement {
-    fn to_xml(&self) -> String {
-        let attrs = self.attrs.as_ref().map_or("".to_string(), |a| a.join(" "));
+    pub(crate) fn text(&mut self, text: impl fmt::Display) -> fmt::Result {
+        self.close_start_tag()?;
+        fmt::Write::write_fmt(&mut XmlEscape(self.out), format_args!("{text}"))
+    }
 
-        let body = self.body.join("");
+    pub(crate) fn end(&mut self, name: &str) -> fmt::Result {
+        self.close_start_tag()?;
+        self.out.write_str("</")?;
+        self.prefix()?;
+        fmt::Write::write_str(&mut XmlEscape(self.out), name)?;
+        self.out.write_char('>')
+    }
 
-        format!("<{}{}{attrs}>{body}</{}>", self.name, if !attrs.is_empty() { " " } else { "" }, self.name)
+    pub(crate) fn text_element(&mut self, name: &str, text: impl fmt::Display) -> fmt::Result {
+        self.start(name)?;
+        self.text(text)?;
+        self.end(name)
+    }
+
+    pub(crate) fn element(&mut self, element: &impl ToXml) -> fmt::Result {
+        element.write_xml(self)
+    }
+}
+
+pub(crate) trait ToXml {
+    fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result;
+
+    #[cfg(test)]
+    fn to_xml(&self) -> String {
+        let mut s = String::new();
+        self.write_xml(&mut XmlWriter::new(&mut s))
+            .expect("writing to a String doesn't fail");
+        s
     }
 }
 
@@ -108,84 +174,105 @@ impl ToXml for XmlElement {
 mod tests {
     use super::*;
 
+    fn write(f: impl FnOnce(&mut XmlWriter) -> fmt::Result) -> String {
+        let mut s = String::new();
+        f(&mut XmlWriter::new(&mut s)).unwrap();
+        s
+    }
+
+    struct A;
+
+    impl ToXml for A {
+        fn write_xml(&self, w: &mut XmlWriter) -> fmt::Result {
+            w.start("a")?;
+            w.end("a")
+        }
+    }
+
     #[test]
     fn escapes_xml() {
-        let mut t1 = Cow::from("&");
-        xml_escape(&mut t1);
-        let mut t2 = Cow::from("\"");
-        xml_escape(&mut t2);
-        let mut t3 = Cow::from("'");
-        xml_escape(&mut t3);
-        let mut t4 = Cow::from("<");
-        xml_escape(&mut t4);
-        let mut t5 = Cow::from(">");
-        xml_escape(&mut t5);
-        let mut t6 = Cow::from("&\"");
-        xml_escape(&mut t6);
-        let mut t7 = Cow::from("&ü\"");
-        xml_escape(&mut t7);
-        let mut t8 = Cow::from("<test foo=\"bar\">baz</test>");
-        xml_escape(&mut t8);
-        assert_eq!(t1.as_ref(), "&amp;");
-        assert_eq!(t2.as_ref(), "&quot;");
-        assert_eq!(t3.as_ref(), "&apos;");
-        assert_eq!(t4.as_ref(), "&lt;");
-        assert_eq!(t5.as_ref(), "&gt;");
-        assert_eq!(t6.as_ref(), "&amp;&quot;");
-        assert_eq!(t7.as_ref(), "&amp;ü&quot;");
-        assert_eq!(t8.as_ref(), "&lt;test foo=&quot;bar&quot;&gt;baz&lt;/test&gt;");
+        let escape = |s: &str| write(|w| w.text(s));
+        assert_eq!(escape("&"), "&amp;");
+        assert_eq!(escape("\""), "&quot;");
+        assert_eq!(escape("'"), "&apos;");
+        assert_eq!(escape("<"), "&lt;");
+        assert_eq!(escape(">"), "&gt;");
+        assert_eq!(escape("&\""), "&amp;&quot;");
+        assert_eq!(escape("&ü\""), "&amp;ü&quot;");
+        assert_eq!(escape("<test foo=\"bar\">baz</test>"), "&lt;test foo=&quot;bar&quot;&gt;baz&lt;/test&gt;");
     }
 
     #[test]
     fn generates_xml_element() {
-        assert_eq!(XmlElement::new("foo").to_xml(), "<foo></foo>");
+        assert_eq!(write(|w| w.start("foo").and_then(|_| w.end("foo"))), "<foo></foo>");
     }
 
     #[test]
     fn generates_xml_text() {
-        assert_eq!(XmlElement::new("foo").with_text("<bar&>").to_xml(), "<foo>&lt;bar&amp;&gt;</foo>");
+        assert_eq!(write(|w| w.text_element("foo", "<bar&>")), "<foo>&lt;bar&amp;&gt;</foo>");
     }
 
     #[test]
     fn generates_xml_texts() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_text("b")
-                .with_text("a")
-                .with_text("r")
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.text("b")?;
+                w.text("a")?;
+                w.text("r")?;
+                w.end("foo")
+            }),
             "<foo>bar</foo>"
         );
     }
 
     #[test]
     fn generates_xml_attribute() {
-        assert_eq!(XmlElement::new("foo").with_attr("a", "b").with_text("bar").to_xml(), "<foo a=\"b\">bar</foo>");
+        assert_eq!(
+            write(|w| {
+                w.start("foo")?;
+                w.attr("a", "b")?;
+                w.text("bar")?;
+                w.end("foo")
+            }),
+            "<foo a=\"b\">bar</foo>"
+        );
     }
 
     #[test]
     fn generates_xml_attributes() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_attr("a", "b")
-                .with_attr("c", "d")
-                .with_text("bar")
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.attr("a", "b")?;
+                w.attr("c", "d")?;
+                w.text("bar")?;
+                w.end("foo")
+            }),
             "<foo a=\"b\" c=\"d\">bar</foo>"
         );
     }
 
     #[test]
     fn generates_nested_xml_element() {
-        assert_eq!(XmlElement::new("foo").with_element(&XmlElement::new("a")).to_xml(), "<foo><a></a></foo>");
+        assert_eq!(
+            write(|w| {
+                w.start("foo")?;
+                w.element(&A)?;
+                w.end("foo")
+            }),
+            "<foo><a></a></foo>"
+        );
     }
 
     #[test]
     fn generates_nested_xml_element_with_text() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_element(&XmlElement::new("a").with_text("b"))
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.text_element("a", "b")?;
+                w.end("foo")
+            }),
             "<foo><a>b</a></foo>"
         );
     }
@@ -193,10 +280,12 @@ mod tests {
     #[test]
     fn generates_nested_xml_element_before_text() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_element(&XmlElement::new("a"))
-                .with_text("b")
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.element(&A)?;
+                w.text("b")?;
+                w.end("foo")
+            }),
             "<foo><a></a>b</foo>"
         );
     }
@@ -204,11 +293,14 @@ mod tests {
     #[test]
     fn generates_nested_xml_element_between_text() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_text("a")
-                .with_element(&XmlElement::new("b"))
-                .with_text("c")
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.text("a")?;
+                w.start("b")?;
+                w.end("b")?;
+                w.text("c")?;
+                w.end("foo")
+            }),
             "<foo>a<b></b>c</foo>"
         );
     }
@@ -216,10 +308,13 @@ mod tests {
     #[test]
     fn generates_nested_xml_element_after_text() {
         assert_eq!(
-            XmlElement::new("foo")
-                .with_text("a")
-                .with_element(&XmlElement::new("b"))
-                .to_xml(),
+            write(|w| {
+                w.start("foo")?;
+                w.text("a")?;
+                w.start("b")?;
+                w.end("b")?;
+                w.end("foo")
+            }),
             "<foo>a<b></b></foo>"
         );
     }
@@ -227,10 +322,14 @@ mod tests {
     #[test]
     fn generates_escaped_xml() {
         assert_eq!(
-            XmlElement::new("a")
-                .with_attr("foo", "b<>ar")
-                .with_element(&XmlElement::new("b").with_attr("c", "\"d&e"))
-                .to_xml(),
+            write(|w| {
+                w.start("a")?;
+                w.attr("foo", "b<>ar")?;
+                w.start("b")?;
+                w.attr("c", "\"d&e")?;
+                w.end("b")?;
+                w.end("a")
+            }),
             "<a foo=\"b&lt;&gt;ar\"><b c=\"&quot;d&amp;e\"></b></a>"
         );
     }
@@ -238,8 +337,20 @@ mod tests {
     #[test]
     fn escapes_with_text_element() {
         assert_eq!(
-            XmlElement::new("a&b").with_text_element("c&d", "f&g").to_xml(),
+            write(|w| {
+                w.start("a&b")?;
+                w.text_element("c&d", "f&g")?;
+                w.end("a&b")
+            }),
             "<a&amp;b><c&amp;d>f&amp;g</c&amp;d></a&amp;b>"
         );
     }
+
+    #[test]
+    fn streams_to_io_write() {
+        let mut out = IoWrite::new(Vec::new());
+        XmlWriter::new(&mut out).text_element("a", "b&c").unwrap();
+        assert!(out.error.is_none());
+        assert_eq!(out.inner, b"<a>b&amp;c</a>");
+    }
 }